@@ -0,0 +1,105 @@
+use crate::entities::containers::validate_changes;
+use crate::entities::images::{image_reference, validate_image_reference};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveOperation {
+    ExportContainer,
+    ImportImage,
+}
+
+/// Emitted while an export or import is running and once more with `done` set when it ends.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchiveProgress {
+    pub transfer_id: String,
+    pub operation: ArchiveOperation,
+    pub bytes_transferred: u64,
+
+    /// Expected number of bytes. Only an estimate for exports, which are
+    /// measured against the size of the container filesystem.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_bytes: Option<u64>,
+
+    pub done: bool,
+}
+
+impl ArchiveProgress {
+    pub fn new(transfer_id: &str, operation: ArchiveOperation, total_bytes: Option<u64>) -> Self {
+        ArchiveProgress {
+            transfer_id: transfer_id.to_string(),
+            operation,
+            bytes_transferred: 0,
+            total_bytes,
+            done: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExportContainerOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transfer_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportResult {
+    pub transfer_id: String,
+    pub path: String,
+
+    /// Size of the written file.
+    pub bytes_written: u64,
+    pub compressed: bool,
+}
+
+/// Options used to create an image from a filesystem tarball.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImportImageOptions {
+    /// Repository of the new image. The image is left untagged when not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+
+    /// Tag of the new image, `latest` when not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+
+    /// Stored as the comment of the image history entry.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+
+    /// Dockerfile instructions applied to the image config, as for commits.
+    pub changes: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transfer_id: Option<String>,
+}
+
+impl ImportImageOptions {
+    /// Validate the options before reading the archive.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors = validate_image_reference(self.repository.as_deref(), self.tag.as_deref());
+        errors.extend(validate_changes(&self.changes));
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+
+    /// `repository:tag` the image is created as, if any.
+    pub fn reference(&self) -> Option<String> {
+        image_reference(self.repository.as_deref(), self.tag.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImportResult {
+    pub transfer_id: String,
+    pub image_id: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+}
@@ -0,0 +1,434 @@
+use crate::entities::images::{split_reference, validate_image_reference};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Options used to build an image from a local build context.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BuildImageOptions {
+    /// Directory sent to the engine as the build context.
+    pub context: String,
+
+    /// Path of the Dockerfile relative to the context, `Dockerfile` when not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dockerfile: Option<String>,
+
+    /// `repository[:tag]` references the image is tagged as. The image is left untagged when empty.
+    pub tags: Vec<String>,
+
+    pub build_args: HashMap<String, String>,
+
+    /// Stage of a multi-stage Dockerfile to stop at.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<String>,
+
+    pub no_cache: bool,
+
+    /// Pull base images even if they exist locally.
+    pub pull: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_id: Option<String>,
+}
+
+impl BuildImageOptions {
+    /// Validate the options before the context is read.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+
+        if self.context.trim().is_empty() {
+            errors.push("A build context is required".to_string());
+        }
+        if let Some(dockerfile) = &self.dockerfile {
+            if dockerfile
+                .split(['/', '\\'])
+                .any(|component| component == "..")
+            {
+                errors.push(format!(
+                    "Dockerfile {} must be inside the build context",
+                    dockerfile
+                ));
+            }
+        }
+        for tag in &self.tags {
+            let (repository, tag) = split_reference(tag);
+            errors.extend(validate_image_reference(Some(repository), tag));
+        }
+        if self.build_args.keys().any(|key| key.trim().is_empty()) {
+            errors.push("Build argument names cannot be empty".to_string());
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+
+    /// Dockerfile path as the engine expects it, with `/` separators.
+    pub fn dockerfile(&self) -> String {
+        self.dockerfile
+            .as_deref()
+            .map(|path| path.replace('\\', "/").trim_start_matches("./").to_string())
+            .filter(|path| !path.is_empty())
+            .unwrap_or_else(|| "Dockerfile".to_string())
+    }
+}
+
+/// Dockerfile step the engine is running, e.g. `Step 3/7 : RUN make`.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct BuildStep {
+    pub number: u32,
+    pub total: u32,
+    pub instruction: String,
+}
+
+/// Emitted with the output lines of a build and once more with `done` set when it ends.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BuildProgress {
+    pub build_id: String,
+
+    /// Output lines received since the previous event.
+    pub lines: Vec<String>,
+
+    /// Step running when the lines were received.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub step: Option<BuildStep>,
+
+    pub done: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct BuildResult {
+    pub build_id: String,
+    pub image_id: String,
+    pub tags: Vec<String>,
+
+    /// Number of steps of the Dockerfile that were run.
+    pub steps: u32,
+}
+
+/// Collects the output the engine streams while building an image.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct BuildOutputTracker {
+    pub step: Option<BuildStep>,
+    pub image_id: Option<String>,
+    pub error: Option<String>,
+
+    /// Part of a line that was not terminated yet.
+    partial: String,
+}
+
+impl BuildOutputTracker {
+    /// Apply one message of the stream and return the complete lines it finished.
+    pub fn update(
+        &mut self,
+        stream: Option<String>,
+        image_id: Option<String>,
+        error: Option<String>,
+    ) -> Vec<String> {
+        if image_id.is_some() {
+            self.image_id = image_id;
+        }
+
+        let mut lines = Vec::new();
+        if let Some(stream) = stream {
+            self.partial.push_str(&stream);
+            while let Some(index) = self.partial.find('\n') {
+                let line: String = self.partial.drain(..=index).collect();
+                let line = line.trim_end().to_string();
+                self.apply_line(&line);
+                lines.push(line);
+            }
+        }
+        if let Some(error) = error {
+            lines.extend(self.flush());
+            lines.push(error.trim_end().to_string());
+            self.error = Some(error.trim_end().to_string());
+        }
+        lines
+    }
+
+    /// Return the last line if the stream ended without a line break.
+    pub fn flush(&mut self) -> Option<String> {
+        if self.partial.is_empty() {
+            return None;
+        }
+        let line = std::mem::take(&mut self.partial).trim_end().to_string();
+        self.apply_line(&line);
+        Some(line)
+    }
+
+    /// Error message naming the step that failed, if any.
+    pub fn failure(&self) -> Option<String> {
+        let error = self.error.as_ref()?;
+        Some(match &self.step {
+            Some(step) => format!(
+                "Step {}/{} ({}) failed: {}",
+                step.number, step.total, step.instruction, error
+            ),
+            None => error.clone(),
+        })
+    }
+
+    fn apply_line(&mut self, line: &str) {
+        if let Some(step) = parse_step(line) {
+            self.step = Some(step);
+        } else if let Some(id) = line.strip_prefix("Successfully built ") {
+            // Older engines only report the short id
+            if self.image_id.is_none() {
+                self.image_id = Some(id.trim().to_string());
+            }
+        }
+    }
+}
+
+fn parse_step(line: &str) -> Option<BuildStep> {
+    let (counts, instruction) = line.strip_prefix("Step ")?.split_once(" : ")?;
+    let (number, total) = counts.split_once('/')?;
+    Some(BuildStep {
+        number: number.trim().parse().ok()?,
+        total: total.trim().parse().ok()?,
+        instruction: instruction.trim().to_string(),
+    })
+}
+
+/// Patterns of a `.dockerignore` file. As with the Docker CLI, the last pattern
+/// matching a path decides whether it is excluded, `!` re-includes paths and a
+/// pattern matching a directory matches everything below it.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct DockerIgnore {
+    patterns: Vec<IgnorePattern>,
+}
+
+#[derive(Debug, Clone)]
+struct IgnorePattern {
+    components: Vec<String>,
+    exception: bool,
+}
+
+impl DockerIgnore {
+    pub fn parse(content: &str) -> Self {
+        let patterns = content
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .filter_map(|line| {
+                let (exception, pattern) = match line.strip_prefix('!') {
+                    Some(pattern) => (true, pattern.trim()),
+                    None => (false, line),
+                };
+                let components = clean_path(pattern);
+                (!components.is_empty()).then_some(IgnorePattern {
+                    components,
+                    exception,
+                })
+            })
+            .collect();
+        DockerIgnore { patterns }
+    }
+
+    /// Whether `path`, relative to the context and separated by `/`, is left out.
+    pub fn is_excluded(&self, path: &str) -> bool {
+        let path: Vec<&str> = path.split('/').filter(|c| !c.is_empty()).collect();
+        let mut excluded = false;
+        for pattern in &self.patterns {
+            if matches_components(&pattern.components, &path) {
+                excluded = !pattern.exception;
+            }
+        }
+        excluded
+    }
+
+    /// Whether an excluded directory may still contain paths that are re-included.
+    pub fn has_exceptions(&self) -> bool {
+        self.patterns.iter().any(|pattern| pattern.exception)
+    }
+}
+
+/// Components of a pattern with `.`, `..` and leading slashes resolved.
+fn clean_path(pattern: &str) -> Vec<String> {
+    let mut components: Vec<String> = Vec::new();
+    for component in pattern.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component.to_string()),
+        }
+    }
+    components
+}
+
+/// Match path components against pattern components, where `**` matches any
+/// number of components. A pattern that matches a parent of the path matches as well.
+fn matches_components(pattern: &[String], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => true,
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|skip| matches_components(rest, &path[skip..]))
+        }
+        Some((first, rest)) => match path.split_first() {
+            Some((component, remaining)) => {
+                matches_wildcard(first.as_bytes(), component.as_bytes())
+                    && matches_components(rest, remaining)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Match one path component against `*`, `?`, `[...]` and `\` escapes.
+fn matches_wildcard(pattern: &[u8], name: &[u8]) -> bool {
+    match pattern.split_first() {
+        None => name.is_empty(),
+        Some((b'*', rest)) => (0..=name.len()).any(|skip| matches_wildcard(rest, &name[skip..])),
+        Some((b'?', rest)) => !name.is_empty() && matches_wildcard(rest, &name[1..]),
+        Some((b'[', rest)) => {
+            let Some((&c, remaining)) = name.split_first() else {
+                return false;
+            };
+            match match_class(rest, c) {
+                Some((true, after)) => matches_wildcard(after, remaining),
+                _ => false,
+            }
+        }
+        Some((b'\\', rest)) if !rest.is_empty() => {
+            name.first() == rest.first() && matches_wildcard(&rest[1..], &name[1..])
+        }
+        Some((&p, rest)) => name.first() == Some(&p) && matches_wildcard(rest, &name[1..]),
+    }
+}
+
+/// Match `c` against a character class whose opening `[` was consumed.
+/// Returns whether it matched and the pattern after the closing `]`.
+fn match_class(class: &[u8], c: u8) -> Option<(bool, &[u8])> {
+    let (negated, mut class) = match class.first() {
+        Some(b'^') | Some(b'!') => (true, &class[1..]),
+        _ => (false, class),
+    };
+    let mut matched = false;
+    let mut first = true;
+    loop {
+        match class {
+            [] => return None,
+            [b']', rest @ ..] if !first => return Some((matched != negated, rest)),
+            [low, b'-', high, rest @ ..] if *high != b']' => {
+                matched |= (*low..=*high).contains(&c);
+                class = rest;
+            }
+            [b'\\', escaped, rest @ ..] => {
+                matched |= *escaped == c;
+                class = rest;
+            }
+            [other, rest @ ..] => {
+                matched |= *other == c;
+                class = rest;
+            }
+        }
+        first = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_docker_ignore() {
+        let ignore = DockerIgnore::parse(
+            "# comment\n\
+             node_modules\n\
+             /target\n\
+             **/*.log\n\
+             !important.log\n\
+             docs/**/draft-?.md\n\
+             *.[ot]mp\n\
+             build/\n\
+             !build/keep\n",
+        );
+
+        assert!(ignore.is_excluded("node_modules"));
+        assert!(ignore.is_excluded("node_modules/react/index.js"));
+        assert!(!ignore.is_excluded("src/node_modules"));
+        assert!(ignore.is_excluded("target/debug/app"));
+        assert!(ignore.is_excluded("debug.log"));
+        assert!(ignore.is_excluded("logs/app/debug.log"));
+        assert!(!ignore.is_excluded("important.log"));
+        assert!(ignore.is_excluded("docs/a/b/draft-1.md"));
+        assert!(ignore.is_excluded("docs/draft-2.md"));
+        assert!(!ignore.is_excluded("docs/draft-10.md"));
+        assert!(ignore.is_excluded("cache.tmp"));
+        assert!(ignore.is_excluded("cache.omp"));
+        assert!(!ignore.is_excluded("cache.amp"));
+        assert!(ignore.is_excluded("build/output.bin"));
+        assert!(!ignore.is_excluded("build/keep"));
+        assert!(!ignore.is_excluded("Dockerfile"));
+        assert!(ignore.has_exceptions());
+    }
+
+    #[test]
+    fn test_build_output_tracker() {
+        let mut tracker = BuildOutputTracker::default();
+
+        let lines = tracker.update(Some("Step 1/3 : FROM alpine\n".to_string()), None, None);
+        assert_eq!(lines, vec!["Step 1/3 : FROM alpine"]);
+        assert_eq!(tracker.step.as_ref().map(|step| step.number), Some(1));
+
+        // Lines may be split across messages
+        assert!(tracker
+            .update(
+                Some(" ---> abc123\nStep 2/3 : RUN ma".to_string()),
+                None,
+                None
+            )
+            .eq(&vec![" ---> abc123"]));
+        let lines = tracker.update(Some("ke\n".to_string()), None, None);
+        assert_eq!(lines, vec!["Step 2/3 : RUN make"]);
+
+        tracker.update(
+            None,
+            None,
+            Some("The command '/bin/sh -c make' returned a non-zero code: 2".to_string()),
+        );
+        assert_eq!(
+            tracker.failure().as_deref(),
+            Some("Step 2/3 (RUN make) failed: The command '/bin/sh -c make' returned a non-zero code: 2")
+        );
+
+        let mut tracker = BuildOutputTracker::default();
+        tracker.update(
+            Some("Successfully built 0123abcd\n".to_string()),
+            None,
+            None,
+        );
+        assert_eq!(tracker.image_id.as_deref(), Some("0123abcd"));
+        tracker.update(None, Some("sha256:0123abcdef".to_string()), None);
+        assert_eq!(tracker.image_id.as_deref(), Some("sha256:0123abcdef"));
+    }
+
+    #[test]
+    fn test_build_image_options() {
+        let mut options = BuildImageOptions {
+            context: "/src/app".to_string(),
+            tags: vec!["app".to_string(), "localhost:5000/team/app:1.0".to_string()],
+            ..Default::default()
+        };
+        assert!(options.validate().is_ok());
+        assert_eq!(options.dockerfile(), "Dockerfile");
+
+        options.dockerfile = Some("./docker/Dockerfile.dev".to_string());
+        assert_eq!(options.dockerfile(), "docker/Dockerfile.dev");
+
+        options.dockerfile = Some("../Dockerfile".to_string());
+        options.tags.push("App:-bad".to_string());
+        let error = options.validate().unwrap_err();
+        assert!(error.contains("must be inside the build context"));
+        assert!(error.contains("Invalid repository: App"));
+        assert!(error.contains("Invalid tag: -bad"));
+    }
+}
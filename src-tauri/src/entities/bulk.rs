@@ -0,0 +1,33 @@
+use serde::{Deserialize, Serialize};
+
+/// Outcome of a bulk operation for a single item.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct BulkItemResult {
+    pub id: String,
+    pub success: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Result of a bulk operation. Every requested id has an entry in `results`,
+/// in the order the ids were given.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkOperationReport {
+    pub operation_id: String,
+    pub operation: String,
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub results: Vec<BulkItemResult>,
+}
+
+/// Emitted every time an item of a bulk operation completes.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BulkOperationProgress {
+    pub operation_id: String,
+    pub operation: String,
+    pub result: BulkItemResult,
+    pub completed: usize,
+    pub total: usize,
+}
@@ -0,0 +1,622 @@
+use crate::entities::compose::parse_dotenv;
+use crate::entities::compose::{
+    dependency_order, ComposeProjectConfig, ServiceConfig, ServiceNetworkConfig,
+    COMPOSE_CONFIG_FILES_LABEL, COMPOSE_CONFIG_HASH_LABEL, COMPOSE_CONTAINER_NUMBER_LABEL,
+    COMPOSE_DEPENDS_ON_LABEL, COMPOSE_NETWORK_LABEL, COMPOSE_ONEOFF_LABEL, COMPOSE_PROJECT_LABEL,
+    COMPOSE_SERVICE_LABEL, COMPOSE_VOLUME_LABEL, COMPOSE_WORKING_DIR_LABEL,
+};
+use crate::entities::containers::{
+    parse_restart_policy, ContainerSpec, EnvVar, MountPointType, NetworkAttachment,
+    ReplacementConfig, ResourceLimits,
+};
+use bollard::models::{EndpointIpamConfig, EndpointSettings, HealthConfig};
+use bollard::network::CreateNetworkOptions;
+use bollard::volume::CreateVolumeOptions;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::path::{Component, Path, PathBuf};
+
+/// Network services are attached to when they do not list any.
+const DEFAULT_NETWORK: &str = "default";
+
+impl ComposeProjectConfig {
+    /// Name of a network on the engine: its `name`, the key of an external
+    /// network, or `{project}_{key}`.
+    pub fn network_name(&self, key: &str) -> String {
+        match self.file.networks.get(key) {
+            Some(network) if network.name.is_some() => network.name.clone().unwrap_or_default(),
+            Some(network) if network.external => key.to_string(),
+            _ => format!("{}_{}", self.name, key),
+        }
+    }
+
+    /// Name of a volume on the engine, following the same rules as networks.
+    pub fn volume_name(&self, key: &str) -> String {
+        match self.file.volumes.get(key) {
+            Some(volume) if volume.name.is_some() => volume.name.clone().unwrap_or_default(),
+            Some(volume) if volume.external => key.to_string(),
+            _ => format!("{}_{}", self.name, key),
+        }
+    }
+
+    /// `container_name` of the service, or `{project}-{service}-1`.
+    pub fn container_name(&self, service: &str) -> String {
+        self.file
+            .services
+            .get(service)
+            .and_then(|config| config.container_name.clone())
+            .unwrap_or_else(|| format!("{}-{}-1", self.name, service))
+    }
+
+    /// Networks of a service by key, `default` when none are listed.
+    /// Services with a `network_mode` are not attached to any.
+    pub fn service_networks(&self, service: &ServiceConfig) -> Vec<(String, ServiceNetworkConfig)> {
+        if service.network_mode.is_some() {
+            return Vec::new();
+        }
+        if service.networks.is_empty() {
+            return vec![(DEFAULT_NETWORK.to_string(), ServiceNetworkConfig::default())];
+        }
+        service
+            .networks
+            .iter()
+            .map(|(key, config)| (key.clone(), config.clone()))
+            .collect()
+    }
+
+    /// Services to run, every service after the ones it depends on. Without
+    /// `requested` services, all services enabled by `profiles` are used.
+    /// Dependencies of the services are always included.
+    pub fn selected_services(
+        &self,
+        requested: &[String],
+        profiles: &[String],
+    ) -> Result<Vec<String>, String> {
+        let mut pending: Vec<String> = if requested.is_empty() {
+            self.file
+                .services
+                .iter()
+                .filter(|(_, service)| {
+                    service.profiles.is_empty()
+                        || service
+                            .profiles
+                            .iter()
+                            .any(|profile| profiles.contains(profile))
+                })
+                .map(|(name, _)| name.clone())
+                .collect()
+        } else {
+            requested.to_vec()
+        };
+
+        let mut selected: Vec<String> = Vec::new();
+        while let Some(name) = pending.pop() {
+            if selected.contains(&name) {
+                continue;
+            }
+            let service = self
+                .file
+                .services
+                .get(&name)
+                .ok_or_else(|| format!("No such service: {}", name))?;
+            pending.extend(
+                service
+                    .depends_on
+                    .iter()
+                    .filter(|(dependency, config)| {
+                        config.required || self.file.services.contains_key(*dependency)
+                    })
+                    .map(|(dependency, _)| dependency.clone()),
+            );
+            if let Some(other) = service
+                .network_mode
+                .as_deref()
+                .and_then(|mode| mode.strip_prefix("service:"))
+            {
+                pending.push(other.to_string());
+            }
+            selected.push(name);
+        }
+
+        let nodes: Vec<(&str, Vec<&str>)> = selected
+            .iter()
+            .map(|name| (name.as_str(), self.dependencies(name)))
+            .collect();
+        let order = dependency_order(&nodes).map_err(|cycle| {
+            format!("Circular dependency between services: {}", cycle.join(", "))
+        })?;
+        Ok(order.into_iter().map(str::to_string).collect())
+    }
+
+    /// Services that have to be running before `service` is started.
+    pub(crate) fn dependencies(&self, service: &str) -> Vec<&str> {
+        let Some(config) = self.file.services.get(service) else {
+            return Vec::new();
+        };
+        let mut dependencies: Vec<&str> = config.depends_on.keys().map(String::as_str).collect();
+        if let Some(other) = config
+            .network_mode
+            .as_deref()
+            .and_then(|mode| mode.strip_prefix("service:"))
+        {
+            dependencies.push(other);
+        }
+        dependencies
+    }
+
+    /// Keys of the networks used by `services`, sorted.
+    pub fn used_networks(&self, services: &[String]) -> Vec<String> {
+        let mut keys: Vec<String> = services
+            .iter()
+            .filter_map(|name| self.file.services.get(name))
+            .flat_map(|service| self.service_networks(service))
+            .map(|(key, _)| key)
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
+    /// Keys of the named volumes mounted by `services`, sorted.
+    pub fn used_volumes(&self, services: &[String]) -> Vec<String> {
+        let mut keys: Vec<String> = services
+            .iter()
+            .filter_map(|name| self.file.services.get(name))
+            .flat_map(|service| &service.volumes)
+            .filter(|mount| mount.mount_type == MountPointType::Volume)
+            .filter_map(|mount| mount.source.clone())
+            .collect();
+        keys.sort();
+        keys.dedup();
+        keys
+    }
+
+    /// Options to create a network of the project. External networks are not created.
+    pub fn network_options(&self, key: &str) -> Option<CreateNetworkOptions<String>> {
+        let network = self.file.networks.get(key).cloned().unwrap_or_default();
+        if network.external {
+            return None;
+        }
+
+        let mut labels: HashMap<String, String> = network.labels.into_iter().collect();
+        labels.insert(COMPOSE_PROJECT_LABEL.to_string(), self.name.clone());
+        labels.insert(COMPOSE_NETWORK_LABEL.to_string(), key.to_string());
+
+        Some(CreateNetworkOptions {
+            name: self.network_name(key),
+            driver: network.driver.unwrap_or_else(|| "bridge".to_string()),
+            internal: network.internal,
+            attachable: network.attachable,
+            options: network.driver_opts.into_iter().collect(),
+            labels,
+            ..Default::default()
+        })
+    }
+
+    /// Options to create a volume of the project. External volumes are not created.
+    pub fn volume_options(&self, key: &str) -> Option<CreateVolumeOptions<String>> {
+        let volume = self.file.volumes.get(key).cloned().unwrap_or_default();
+        if volume.external {
+            return None;
+        }
+
+        let mut labels: HashMap<String, String> = volume.labels.into_iter().collect();
+        labels.insert(COMPOSE_PROJECT_LABEL.to_string(), self.name.clone());
+        labels.insert(COMPOSE_VOLUME_LABEL.to_string(), key.to_string());
+
+        Some(CreateVolumeOptions {
+            name: self.volume_name(key),
+            driver: volume.driver.unwrap_or_else(|| "local".to_string()),
+            driver_opts: volume.driver_opts.into_iter().collect(),
+            labels,
+        })
+    }
+
+    /// Hash of the service definition, stored in a label to tell whether a
+    /// container has to be recreated.
+    pub fn config_hash(&self, service: &str) -> String {
+        let definition = self
+            .file
+            .services
+            .get(service)
+            .and_then(|config| serde_json::to_string(config).ok())
+            .unwrap_or_default();
+        format!("{:x}", Sha256::digest(definition.as_bytes()))
+    }
+
+    /// Create config of the container of a service, with the standard compose labels.
+    /// `read_file` reads the `env_file`s of the service.
+    pub(crate) fn container_config<F>(
+        &self,
+        service_name: &str,
+        read_file: F,
+    ) -> Result<ReplacementConfig, String>
+    where
+        F: Fn(&Path) -> Result<String, String>,
+    {
+        let service = self
+            .file
+            .services
+            .get(service_name)
+            .ok_or_else(|| format!("No such service: {}", service_name))?;
+        let image = service
+            .image
+            .clone()
+            .ok_or_else(|| format!("Service {} has no image", service_name))?;
+
+        let mut env: Vec<(String, String)> = Vec::new();
+        for file in &service.env_file {
+            let content = read_file(&self.resolve_path(file))?;
+            env.extend(parse_dotenv(&content));
+        }
+        for (key, value) in &service.environment {
+            // Entries without a value are taken from the environment of the app
+            let value = match value {
+                Some(value) => value.clone(),
+                None => match self.environment.get(key) {
+                    Some(value) => value.clone(),
+                    None => continue,
+                },
+            };
+            env.push((key.clone(), value));
+        }
+        let mut deduplicated: Vec<EnvVar> = Vec::new();
+        for (key, value) in env {
+            deduplicated.retain(|var| var.key != key);
+            deduplicated.push(EnvVar { key, value });
+        }
+
+        let mounts = service
+            .volumes
+            .iter()
+            .cloned()
+            .map(|mut mount| {
+                mount.source = match (&mount.mount_type, mount.source.take()) {
+                    (MountPointType::Bind, Some(source)) => {
+                        Some(self.resolve_path(&source).to_string_lossy().to_string())
+                    }
+                    (MountPointType::Volume, Some(source)) => Some(self.volume_name(&source)),
+                    (_, source) => source,
+                };
+                mount
+            })
+            .collect();
+
+        let networks = self.service_networks(service);
+        let attachments: Vec<NetworkAttachment> = networks
+            .iter()
+            .map(|(key, config)| {
+                let mut aliases = vec![service_name.to_string()];
+                aliases.extend(config.aliases.iter().cloned());
+                NetworkAttachment {
+                    name: self.network_name(key),
+                    aliases,
+                }
+            })
+            .collect();
+
+        let mut labels: HashMap<String, String> = service.labels.clone().into_iter().collect();
+        labels.extend([
+            (COMPOSE_PROJECT_LABEL.to_string(), self.name.clone()),
+            (COMPOSE_SERVICE_LABEL.to_string(), service_name.to_string()),
+            (COMPOSE_CONTAINER_NUMBER_LABEL.to_string(), "1".to_string()),
+            (COMPOSE_ONEOFF_LABEL.to_string(), "False".to_string()),
+            (
+                COMPOSE_WORKING_DIR_LABEL.to_string(),
+                self.working_dir.clone(),
+            ),
+            (
+                COMPOSE_CONFIG_FILES_LABEL.to_string(),
+                self.config_files.join(","),
+            ),
+            (
+                COMPOSE_CONFIG_HASH_LABEL.to_string(),
+                self.config_hash(service_name),
+            ),
+        ]);
+        if !service.depends_on.is_empty() {
+            let depends_on: Vec<String> = service
+                .depends_on
+                .iter()
+                .map(|(name, config)| format!("{}:{}:{}", name, config.condition, config.restart))
+                .collect();
+            labels.insert(COMPOSE_DEPENDS_ON_LABEL.to_string(), depends_on.join(","));
+        }
+
+        let spec = ContainerSpec {
+            image,
+            name: Some(self.container_name(service_name)),
+            command: service.command.clone(),
+            entrypoint: service.entrypoint.clone(),
+            working_dir: service.working_dir.clone(),
+            user: service.user.clone(),
+            hostname: service.hostname.clone(),
+            env: deduplicated,
+            ports: service.ports.clone(),
+            mounts,
+            networks: attachments.clone(),
+            restart_policy: service
+                .restart
+                .as_deref()
+                .map(parse_restart_policy)
+                .transpose()?,
+            labels,
+            resources: ResourceLimits {
+                cpus: service.cpus,
+                cpu_shares: service.cpu_shares,
+                memory: service.mem_limit,
+                memory_reservation: service.mem_reservation,
+                memory_swap: service.memswap_limit,
+                pids_limit: service.pids_limit,
+                ..Default::default()
+            },
+            tty: service.tty,
+            open_stdin: service.stdin_open,
+            auto_remove: false,
+        };
+        spec.validate()
+            .map_err(|e| format!("Invalid service {}: {}", service_name, e))?;
+
+        let mut config = spec.to_config();
+        config.domainname = service.domainname.clone();
+        config.stop_signal = service.stop_signal.clone();
+        config.stop_timeout = service.stop_grace_period;
+        config.healthcheck = service
+            .healthcheck
+            .as_ref()
+            .map(|healthcheck| HealthConfig {
+                test: match healthcheck.disable {
+                    true => Some(vec!["NONE".to_string()]),
+                    false => healthcheck.test.clone(),
+                },
+                interval: healthcheck.interval,
+                timeout: healthcheck.timeout,
+                retries: healthcheck.retries,
+                start_period: healthcheck.start_period,
+                start_interval: None,
+            });
+        if !service.expose.is_empty() {
+            let exposed_ports = config.exposed_ports.get_or_insert_with(HashMap::new);
+            for port in &service.expose {
+                let key = match port.contains('/') {
+                    true => port.clone(),
+                    false => format!("{}/tcp", port),
+                };
+                exposed_ports.insert(key, HashMap::new());
+            }
+        }
+
+        let host_config = config.host_config.get_or_insert_with(Default::default);
+        host_config.privileged = service.privileged.then_some(true);
+        host_config.init = service.init.then_some(true);
+        host_config.readonly_rootfs = service.read_only.then_some(true);
+        host_config.cap_add = (!service.cap_add.is_empty()).then(|| service.cap_add.clone());
+        host_config.cap_drop = (!service.cap_drop.is_empty()).then(|| service.cap_drop.clone());
+        host_config.dns = (!service.dns.is_empty()).then(|| service.dns.clone());
+        host_config.extra_hosts =
+            (!service.extra_hosts.is_empty()).then(|| service.extra_hosts.clone());
+        if !service.tmpfs.is_empty() {
+            host_config.tmpfs = Some(
+                service
+                    .tmpfs
+                    .iter()
+                    .map(|tmpfs| match tmpfs.split_once(':') {
+                        Some((path, options)) => (path.to_string(), options.to_string()),
+                        None => (tmpfs.clone(), String::new()),
+                    })
+                    .collect(),
+            );
+        }
+        if let Some(mode) = &service.network_mode {
+            host_config.network_mode = Some(match mode.strip_prefix("service:") {
+                Some(other) => format!("container:{}", self.container_name(other)),
+                None => mode.clone(),
+            });
+        }
+
+        let ipam_config = |config: &ServiceNetworkConfig| {
+            config
+                .ipv4_address
+                .as_ref()
+                .map(|address| EndpointIpamConfig {
+                    ipv4_address: Some(address.clone()),
+                    ..Default::default()
+                })
+        };
+        if let (Some((_, primary)), Some(networking_config)) =
+            (networks.first(), config.networking_config.as_mut())
+        {
+            for endpoint in networking_config.endpoints_config.values_mut() {
+                endpoint.ipam_config = ipam_config(primary);
+            }
+        }
+
+        let extra_networks = networks
+            .iter()
+            .zip(&attachments)
+            .skip(1)
+            .map(|((_, network), attachment)| {
+                (
+                    attachment.name.clone(),
+                    EndpointSettings {
+                        aliases: Some(attachment.aliases.clone()),
+                        ipam_config: ipam_config(network),
+                        ..Default::default()
+                    },
+                )
+            })
+            .collect();
+
+        Ok(ReplacementConfig {
+            config,
+            extra_networks,
+        })
+    }
+
+    /// Resolve a host path against the working directory. `~` is the home directory.
+    fn resolve_path(&self, path: &str) -> PathBuf {
+        let path = match path.strip_prefix('~') {
+            Some(rest) if rest.is_empty() || rest.starts_with('/') => dirs::home_dir()
+                .unwrap_or_default()
+                .join(rest.trim_start_matches('/')),
+            _ => Path::new(&self.working_dir).join(path),
+        };
+
+        let mut resolved = PathBuf::new();
+        for component in path.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    resolved.pop();
+                }
+                component => resolved.push(component),
+            }
+        }
+        resolved
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::compose::ComposeSource;
+
+    fn project() -> ComposeProjectConfig {
+        let source = ComposeSource {
+            path: "/srv/shop/compose.yaml".to_string(),
+            content: r#"
+services:
+  web:
+    image: nginx
+    env_file: .env.web
+    environment:
+      MODE: prod
+      TOKEN:
+    volumes:
+      - ./html:/usr/share/nginx/html:ro
+      - cache:/var/cache/nginx
+    networks:
+      front:
+        aliases: [www]
+      back:
+        ipv4_address: 172.20.0.5
+    depends_on:
+      api:
+        condition: service_healthy
+    expose: ["9000"]
+  api:
+    image: shop/api
+    network_mode: service:db
+  db:
+    image: postgres
+    profiles: [db]
+  tools:
+    image: busybox
+    profiles: [debug]
+networks:
+  front:
+  back:
+    external: true
+volumes:
+  cache:
+    name: nginx-cache
+"#
+            .to_string(),
+        };
+        let environment = HashMap::from([("TOKEN".to_string(), "secret".to_string())]);
+        ComposeProjectConfig::load(&[source], "/srv/shop", environment).unwrap()
+    }
+
+    #[test]
+    fn test_resource_names() {
+        let project = project();
+
+        assert_eq!(project.network_name("front"), "shop_front");
+        assert_eq!(project.network_name("back"), "back");
+        assert_eq!(project.network_name("default"), "shop_default");
+        assert_eq!(project.volume_name("cache"), "nginx-cache");
+        assert_eq!(project.container_name("web"), "shop-web-1");
+        assert!(project.network_options("back").is_none());
+        assert_eq!(
+            project.used_networks(&["web".to_string()]),
+            vec!["back", "front"]
+        );
+    }
+
+    #[test]
+    fn test_selected_services() {
+        let project = project();
+
+        assert_eq!(
+            project.selected_services(&[], &[]).unwrap(),
+            vec!["db", "api", "web"]
+        );
+        assert_eq!(
+            project
+                .selected_services(&[], &["debug".to_string()])
+                .unwrap(),
+            vec!["db", "api", "tools", "web"]
+        );
+        assert_eq!(
+            project
+                .selected_services(&["api".to_string()], &[])
+                .unwrap(),
+            vec!["db", "api"]
+        );
+        assert!(project
+            .selected_services(&["nope".to_string()], &[])
+            .is_err());
+    }
+
+    #[test]
+    fn test_container_config() {
+        let project = project();
+        let replacement = project
+            .container_config("web", |path| {
+                assert_eq!(path, Path::new("/srv/shop/.env.web"));
+                Ok("MODE=dev\nLEVEL=info\n".to_string())
+            })
+            .unwrap();
+        let config = replacement.config;
+
+        let mut env = config.env.clone().unwrap();
+        env.sort();
+        assert_eq!(env, vec!["LEVEL=info", "MODE=prod", "TOKEN=secret"]);
+
+        let labels = config.labels.clone().unwrap();
+        assert_eq!(labels[COMPOSE_PROJECT_LABEL], "shop");
+        assert_eq!(labels[COMPOSE_SERVICE_LABEL], "web");
+        assert_eq!(
+            labels[COMPOSE_DEPENDS_ON_LABEL],
+            "api:service_healthy:false"
+        );
+        assert_eq!(
+            labels[COMPOSE_CONFIG_HASH_LABEL],
+            project.config_hash("web")
+        );
+
+        let host_config = config.host_config.clone().unwrap();
+        let mounts = host_config.mounts.unwrap();
+        assert_eq!(mounts[0].source.as_deref(), Some("/srv/shop/html"));
+        assert_eq!(mounts[1].source.as_deref(), Some("nginx-cache"));
+        assert_eq!(host_config.network_mode.as_deref(), Some("back"));
+        assert!(config.exposed_ports.unwrap().contains_key("9000/tcp"));
+
+        let endpoints = config.networking_config.unwrap().endpoints_config;
+        let ipam = endpoints["back"].ipam_config.clone().unwrap();
+        assert_eq!(ipam.ipv4_address.as_deref(), Some("172.20.0.5"));
+        assert_eq!(replacement.extra_networks[0].0, "shop_front");
+        assert_eq!(
+            replacement.extra_networks[0].1.aliases,
+            Some(vec!["web".to_string(), "www".to_string()])
+        );
+
+        let api = project
+            .container_config("api", |_| Ok(String::new()))
+            .unwrap();
+        assert_eq!(
+            api.config.host_config.unwrap().network_mode.as_deref(),
+            Some("container:shop-db-1")
+        );
+    }
+}
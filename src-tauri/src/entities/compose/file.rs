@@ -0,0 +1,713 @@
+use crate::entities::containers::{
+    parse_bytes, parse_port, parse_volume, split_command_line, MountPointType, MountSpec,
+    PortBindingSpec, PortTypeEnum,
+};
+use serde::de::Error;
+use serde::{Deserialize, Deserializer, Serialize};
+use serde_norway::Value;
+use std::collections::BTreeMap;
+
+/// Contents of a compose project, after interpolation and merging of override files.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComposeFile {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    pub services: BTreeMap<String, ServiceConfig>,
+
+    #[serde(deserialize_with = "de_optional_map")]
+    pub networks: BTreeMap<String, NetworkConfig>,
+
+    #[serde(deserialize_with = "de_optional_map")]
+    pub volumes: BTreeMap<String, VolumeConfig>,
+}
+
+/// A service of a compose file. Short and long syntaxes are read into the same fields.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServiceConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub container_name: Option<String>,
+
+    /// A string command is split like a shell would.
+    #[serde(
+        deserialize_with = "de_command",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub command: Option<Vec<String>>,
+
+    #[serde(
+        deserialize_with = "de_command",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub entrypoint: Option<Vec<String>>,
+
+    /// Variables without a value are taken from the environment of the app.
+    #[serde(deserialize_with = "de_environment")]
+    pub environment: BTreeMap<String, Option<String>>,
+
+    #[serde(deserialize_with = "de_string_list")]
+    pub env_file: Vec<String>,
+
+    #[serde(deserialize_with = "de_ports")]
+    pub ports: Vec<PortBindingSpec>,
+
+    #[serde(deserialize_with = "de_string_list")]
+    pub expose: Vec<String>,
+
+    /// Sources of bind mounts are relative to the project directory and
+    /// sources of volumes are keys of the top-level `volumes`.
+    #[serde(deserialize_with = "de_volumes")]
+    pub volumes: Vec<MountSpec>,
+
+    #[serde(deserialize_with = "de_string_list")]
+    pub tmpfs: Vec<String>,
+
+    #[serde(deserialize_with = "de_service_networks")]
+    pub networks: BTreeMap<String, ServiceNetworkConfig>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_mode: Option<String>,
+
+    #[serde(deserialize_with = "de_depends_on")]
+    pub depends_on: BTreeMap<String, DependsOnConfig>,
+
+    /// `no`, `always`, `unless-stopped` or `on-failure[:max_retries]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart: Option<String>,
+
+    #[serde(deserialize_with = "de_labels")]
+    pub labels: BTreeMap<String, String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domainname: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+
+    pub tty: bool,
+    pub stdin_open: bool,
+    pub privileged: bool,
+    pub init: bool,
+    pub read_only: bool,
+
+    #[serde(deserialize_with = "de_string_list")]
+    pub cap_add: Vec<String>,
+
+    #[serde(deserialize_with = "de_string_list")]
+    pub cap_drop: Vec<String>,
+
+    #[serde(deserialize_with = "de_string_list")]
+    pub dns: Vec<String>,
+
+    /// Written as `host:ip`.
+    #[serde(deserialize_with = "de_extra_hosts")]
+    pub extra_hosts: Vec<String>,
+
+    #[serde(deserialize_with = "de_cpus", skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_shares: Option<i64>,
+
+    /// Memory limit in bytes.
+    #[serde(deserialize_with = "de_bytes", skip_serializing_if = "Option::is_none")]
+    pub mem_limit: Option<i64>,
+
+    #[serde(deserialize_with = "de_bytes", skip_serializing_if = "Option::is_none")]
+    pub mem_reservation: Option<i64>,
+
+    #[serde(deserialize_with = "de_bytes", skip_serializing_if = "Option::is_none")]
+    pub memswap_limit: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pids_limit: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_signal: Option<String>,
+
+    /// Time to wait for the container to stop, in seconds.
+    #[serde(
+        deserialize_with = "de_duration_seconds",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub stop_grace_period: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub healthcheck: Option<HealthcheckConfig>,
+
+    /// The service is only started when one of its profiles is enabled.
+    #[serde(deserialize_with = "de_string_list")]
+    pub profiles: Vec<String>,
+
+    /// `missing` (default), `always` or `never`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pull_policy: Option<String>,
+}
+
+/// Settings of a service on one of its networks.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ServiceNetworkConfig {
+    #[serde(deserialize_with = "de_string_list")]
+    pub aliases: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ipv4_address: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DependsOnConfig {
+    /// `service_started`, `service_healthy` or `service_completed_successfully`.
+    pub condition: String,
+
+    /// Restart the service when the dependency is restarted.
+    pub restart: bool,
+
+    /// Fail when the dependency is not part of the project.
+    pub required: bool,
+}
+
+impl Default for DependsOnConfig {
+    fn default() -> Self {
+        DependsOnConfig {
+            condition: "service_started".to_string(),
+            restart: false,
+            required: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct HealthcheckConfig {
+    /// `["CMD", ...]`, `["CMD-SHELL", "..."]` or `["NONE"]`. A string is run with a shell.
+    #[serde(
+        deserialize_with = "de_healthcheck_test",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub test: Option<Vec<String>>,
+
+    /// Durations are in nanoseconds, like the engine expects them.
+    #[serde(
+        deserialize_with = "de_duration_nanos",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub interval: Option<i64>,
+
+    #[serde(
+        deserialize_with = "de_duration_nanos",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub timeout: Option<i64>,
+
+    #[serde(
+        deserialize_with = "de_duration_nanos",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub start_period: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<i64>,
+
+    pub disable: bool,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NetworkConfig {
+    /// Name of the network on the engine, `{project}_{key}` if not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub driver: Option<String>,
+
+    #[serde(deserialize_with = "de_labels")]
+    pub driver_opts: BTreeMap<String, String>,
+
+    /// The network is managed outside of the project and has to exist.
+    pub external: bool,
+
+    pub internal: bool,
+    pub attachable: bool,
+
+    #[serde(deserialize_with = "de_labels")]
+    pub labels: BTreeMap<String, String>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct VolumeConfig {
+    /// Name of the volume on the engine, `{project}_{key}` if not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub driver: Option<String>,
+
+    #[serde(deserialize_with = "de_labels")]
+    pub driver_opts: BTreeMap<String, String>,
+
+    /// The volume is managed outside of the project and has to exist.
+    pub external: bool,
+
+    #[serde(deserialize_with = "de_labels")]
+    pub labels: BTreeMap<String, String>,
+}
+
+/// Text of a string, number or boolean value.
+pub(crate) fn scalar_string(value: &Value) -> Option<String> {
+    match value {
+        Value::String(value) => Some(value.clone()),
+        Value::Number(number) => Some(number.to_string()),
+        Value::Bool(value) => Some(value.to_string()),
+        _ => None,
+    }
+}
+
+fn expect_scalar<E: Error>(value: &Value, what: &str) -> Result<String, E> {
+    scalar_string(value).ok_or_else(|| E::custom(format!("{} must be a string", what)))
+}
+
+fn de_optional_map<'de, D, T>(deserializer: D) -> Result<BTreeMap<String, T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de> + Default,
+{
+    let entries: Option<BTreeMap<String, Option<T>>> = Option::deserialize(deserializer)?;
+    Ok(entries
+        .unwrap_or_default()
+        .into_iter()
+        .map(|(key, value)| (key, value.unwrap_or_default()))
+        .collect())
+}
+
+fn de_command<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<Vec<String>>, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(None),
+        Value::String(command) => split_command_line(&command)
+            .map(Some)
+            .map_err(D::Error::custom),
+        Value::Sequence(words) => words
+            .iter()
+            .map(|word| expect_scalar(word, "Command arguments"))
+            .collect::<Result<_, _>>()
+            .map(Some),
+        _ => Err(D::Error::custom("Expected a string or a list")),
+    }
+}
+
+fn de_string_list<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(Vec::new()),
+        Value::Sequence(values) => values
+            .iter()
+            .map(|value| expect_scalar(value, "List items"))
+            .collect(),
+        value => expect_scalar(&value, "Value").map(|value| vec![value]),
+    }
+}
+
+/// Read a `KEY: value` mapping or a list of `KEY=value` strings.
+fn list_or_dict<E: Error>(value: Value) -> Result<BTreeMap<String, Option<String>>, E> {
+    match value {
+        Value::Null => Ok(BTreeMap::new()),
+        Value::Mapping(entries) => entries
+            .iter()
+            .map(|(key, value)| {
+                let key = expect_scalar(key, "Keys")?;
+                let value = match value {
+                    Value::Null => None,
+                    value => Some(expect_scalar(value, &key)?),
+                };
+                Ok((key, value))
+            })
+            .collect(),
+        Value::Sequence(entries) => entries
+            .iter()
+            .map(|entry| {
+                let entry = expect_scalar(entry, "List items")?;
+                Ok(match entry.split_once('=') {
+                    Some((key, value)) => (key.to_string(), Some(value.to_string())),
+                    None => (entry, None),
+                })
+            })
+            .collect(),
+        _ => Err(E::custom("Expected a mapping or a list")),
+    }
+}
+
+fn de_environment<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<String, Option<String>>, D::Error> {
+    list_or_dict(Value::deserialize(deserializer)?)
+}
+
+fn de_labels<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<String, String>, D::Error> {
+    Ok(list_or_dict(Value::deserialize(deserializer)?)?
+        .into_iter()
+        .map(|(key, value)| (key, value.unwrap_or_default()))
+        .collect())
+}
+
+fn de_extra_hosts<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Mapping(entries) => entries
+            .iter()
+            .map(|(host, ip)| {
+                Ok(format!(
+                    "{}:{}",
+                    expect_scalar::<D::Error>(host, "Host names")?,
+                    expect_scalar::<D::Error>(ip, "Addresses")?
+                ))
+            })
+            .collect(),
+        value => de_string_list(value).map_err(D::Error::custom),
+    }
+}
+
+fn de_ports<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<PortBindingSpec>, D::Error> {
+    let Value::Sequence(entries) = Value::deserialize(deserializer)? else {
+        return Err(D::Error::custom("Ports must be a list"));
+    };
+
+    let mut ports = Vec::new();
+    for entry in entries {
+        match entry {
+            Value::Mapping(_) => {
+                #[derive(Deserialize)]
+                struct LongPort {
+                    target: u16,
+                    published: Option<Value>,
+                    host_ip: Option<String>,
+                    protocol: Option<String>,
+                }
+
+                let port: LongPort = serde_norway::from_value(entry).map_err(D::Error::custom)?;
+                let host_port = match port.published.as_ref().and_then(scalar_string) {
+                    Some(published) => Some(published.parse::<u16>().map_err(|_| {
+                        D::Error::custom(format!("Invalid published port: {}", published))
+                    })?),
+                    None => None,
+                };
+                let protocol = match port.protocol.as_deref() {
+                    None | Some("tcp") => None,
+                    Some("udp") => Some(PortTypeEnum::Udp),
+                    Some("sctp") => Some(PortTypeEnum::Sctp),
+                    Some(other) => {
+                        return Err(D::Error::custom(format!("Invalid protocol: {}", other)))
+                    }
+                };
+                ports.push(PortBindingSpec {
+                    container_port: port.target,
+                    protocol,
+                    host_ip: port.host_ip,
+                    host_port,
+                });
+            }
+            entry => {
+                let port = expect_scalar::<D::Error>(&entry, "Ports")?;
+                ports.extend(parse_port(&port).map_err(D::Error::custom)?);
+            }
+        }
+    }
+    Ok(ports)
+}
+
+fn de_volumes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<MountSpec>, D::Error> {
+    let Value::Sequence(entries) = Value::deserialize(deserializer)? else {
+        return Err(D::Error::custom("Volumes must be a list"));
+    };
+
+    entries
+        .into_iter()
+        .map(|entry| match entry {
+            Value::Mapping(_) => {
+                #[derive(Deserialize)]
+                struct LongVolume {
+                    #[serde(rename = "type")]
+                    mount_type: String,
+                    source: Option<String>,
+                    target: String,
+                    #[serde(default)]
+                    read_only: bool,
+                    tmpfs: Option<LongTmpfs>,
+                }
+                #[derive(Deserialize)]
+                struct LongTmpfs {
+                    size: Option<Value>,
+                }
+
+                let volume: LongVolume =
+                    serde_norway::from_value(entry).map_err(D::Error::custom)?;
+                let mount_type = match volume.mount_type.as_str() {
+                    "bind" => MountPointType::Bind,
+                    "volume" => MountPointType::Volume,
+                    "tmpfs" => MountPointType::Tmpfs,
+                    other => {
+                        return Err(D::Error::custom(format!(
+                            "Unsupported volume type: {}",
+                            other
+                        )))
+                    }
+                };
+                let tmpfs_size = match volume.tmpfs.and_then(|tmpfs| tmpfs.size) {
+                    Some(size) => Some(
+                        parse_bytes(&expect_scalar::<D::Error>(&size, "Sizes")?)
+                            .map_err(D::Error::custom)?,
+                    ),
+                    None => None,
+                };
+                Ok(MountSpec {
+                    mount_type,
+                    source: volume.source,
+                    target: volume.target,
+                    read_only: volume.read_only,
+                    tmpfs_size,
+                })
+            }
+            entry => {
+                let volume = expect_scalar::<D::Error>(&entry, "Volumes")?;
+                // Options such as SELinux labels have no equivalent in a mount
+                parse_volume(&volume)
+                    .map(|(mount, _)| mount)
+                    .map_err(D::Error::custom)
+            }
+        })
+        .collect()
+}
+
+fn de_service_networks<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<String, ServiceNetworkConfig>, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(BTreeMap::new()),
+        Value::Sequence(names) => names
+            .iter()
+            .map(|name| Ok((expect_scalar(name, "Network names")?, Default::default())))
+            .collect(),
+        value @ Value::Mapping(_) => {
+            let networks: BTreeMap<String, Option<ServiceNetworkConfig>> =
+                serde_norway::from_value(value).map_err(D::Error::custom)?;
+            Ok(networks
+                .into_iter()
+                .map(|(name, network)| (name, network.unwrap_or_default()))
+                .collect())
+        }
+        _ => Err(D::Error::custom("Networks must be a list or a mapping")),
+    }
+}
+
+fn de_depends_on<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<BTreeMap<String, DependsOnConfig>, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(BTreeMap::new()),
+        Value::Sequence(names) => names
+            .iter()
+            .map(|name| Ok((expect_scalar(name, "Service names")?, Default::default())))
+            .collect(),
+        value @ Value::Mapping(_) => {
+            let dependencies: BTreeMap<String, Option<DependsOnConfig>> =
+                serde_norway::from_value(value).map_err(D::Error::custom)?;
+            Ok(dependencies
+                .into_iter()
+                .map(|(name, dependency)| (name, dependency.unwrap_or_default()))
+                .collect())
+        }
+        _ => Err(D::Error::custom("depends_on must be a list or a mapping")),
+    }
+}
+
+fn de_healthcheck_test<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<Vec<String>>, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(None),
+        Value::String(command) => Ok(Some(vec!["CMD-SHELL".to_string(), command])),
+        value => de_string_list(value).map(Some).map_err(D::Error::custom),
+    }
+}
+
+fn de_cpus<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<f64>, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(None),
+        value => {
+            let cpus = expect_scalar::<D::Error>(&value, "cpus")?;
+            cpus.parse()
+                .map(Some)
+                .map_err(|_| D::Error::custom(format!("Invalid number of CPUs: {}", cpus)))
+        }
+    }
+}
+
+fn de_bytes<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<i64>, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(None),
+        Value::Number(bytes) => bytes
+            .as_i64()
+            .map(Some)
+            .ok_or_else(|| D::Error::custom(format!("Invalid size: {}", bytes))),
+        value => {
+            let size = expect_scalar::<D::Error>(&value, "Sizes")?;
+            // `-1` means unlimited swap
+            if size == "-1" {
+                return Ok(Some(-1));
+            }
+            parse_bytes(&size).map(Some).map_err(D::Error::custom)
+        }
+    }
+}
+
+fn de_duration_nanos<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Option<i64>, D::Error> {
+    match Value::deserialize(deserializer)? {
+        Value::Null => Ok(None),
+        value => {
+            let duration = expect_scalar::<D::Error>(&value, "Durations")?;
+            parse_duration(&duration)
+                .map(Some)
+                .map_err(D::Error::custom)
+        }
+    }
+}
+
+fn de_duration_seconds<'de, D: Deserializer<'de>>(
+    deserializer: D,
+) -> Result<Option<i64>, D::Error> {
+    Ok(de_duration_nanos(deserializer)?.map(|nanos| nanos / 1_000_000_000))
+}
+
+/// Parse a duration like `1m30s`, `500ms` or `2h` into nanoseconds.
+/// A plain number is read as seconds.
+pub(crate) fn parse_duration(value: &str) -> Result<i64, String> {
+    let value = value.trim();
+    if let Ok(seconds) = value.parse::<f64>() {
+        return Ok((seconds * 1e9) as i64);
+    }
+
+    let mut total = 0f64;
+    let mut rest = value;
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !c.is_ascii_digit() && c != '.')
+            .unwrap_or(rest.len());
+        let unit_end = rest[number_end..]
+            .find(|c: char| c.is_ascii_digit() || c == '.')
+            .map_or(rest.len(), |end| number_end + end);
+        let number: f64 = rest[..number_end]
+            .parse()
+            .map_err(|_| format!("Invalid duration: {}", value))?;
+        let nanos = match &rest[number_end..unit_end] {
+            "ns" => 1.0,
+            "us" | "µs" => 1e3,
+            "ms" => 1e6,
+            "s" => 1e9,
+            "m" => 60e9,
+            "h" => 3600e9,
+            _ => return Err(format!("Invalid duration: {}", value)),
+        };
+        total += number * nanos;
+        rest = &rest[unit_end..];
+    }
+
+    Ok(total as i64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_service_syntaxes() {
+        let file: ComposeFile = serde_norway::from_str(
+            r#"
+services:
+  web:
+    image: nginx
+    command: nginx -g 'daemon off;'
+    environment:
+      - MODE=prod
+      - TOKEN
+    ports:
+      - "8080:80"
+      - target: 443
+        published: "8443"
+        protocol: tcp
+    volumes:
+      - ./html:/usr/share/nginx/html:ro
+      - type: tmpfs
+        target: /cache
+        tmpfs:
+          size: 64m
+    networks:
+      front:
+        aliases: [www]
+      back:
+    depends_on:
+      api:
+        condition: service_healthy
+    mem_limit: 512m
+    stop_grace_period: 1m30s
+    healthcheck:
+      test: curl -f http://localhost
+      interval: 10s
+networks:
+  front:
+  back:
+    internal: true
+"#,
+        )
+        .unwrap();
+
+        let web = &file.services["web"];
+        assert_eq!(
+            web.command.as_deref(),
+            Some(
+                &[
+                    "nginx".to_string(),
+                    "-g".to_string(),
+                    "daemon off;".to_string()
+                ][..]
+            )
+        );
+        assert_eq!(web.environment["MODE"].as_deref(), Some("prod"));
+        assert_eq!(web.environment["TOKEN"], None);
+        assert_eq!(web.ports.len(), 2);
+        assert_eq!(web.ports[1].host_port, Some(8443));
+        assert_eq!(web.volumes[0].mount_type, MountPointType::Bind);
+        assert!(web.volumes[0].read_only);
+        assert_eq!(web.volumes[1].tmpfs_size, Some(64 << 20));
+        assert_eq!(web.networks["front"].aliases, vec!["www".to_string()]);
+        assert!(web.networks.contains_key("back"));
+        assert_eq!(web.depends_on["api"].condition, "service_healthy");
+        assert_eq!(web.mem_limit, Some(512 << 20));
+        assert_eq!(web.stop_grace_period, Some(90));
+        let healthcheck = web.healthcheck.as_ref().unwrap();
+        assert_eq!(healthcheck.test.as_ref().unwrap()[0], "CMD-SHELL");
+        assert_eq!(healthcheck.interval, Some(10_000_000_000));
+        assert!(file.networks["back"].internal);
+        assert!(!file.networks["front"].external);
+    }
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("1m30s"), Ok(90_000_000_000));
+        assert_eq!(parse_duration("500ms"), Ok(500_000_000));
+        assert_eq!(parse_duration("2"), Ok(2_000_000_000));
+        assert!(parse_duration("5 minutes").is_err());
+    }
+}
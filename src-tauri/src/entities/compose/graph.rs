@@ -0,0 +1,580 @@
+use crate::entities::compose::{
+    ServiceDependency, COMPOSE_DEPENDS_ON_LABEL, COMPOSE_ONEOFF_LABEL, COMPOSE_PROJECT_LABEL,
+    COMPOSE_SERVICE_LABEL,
+};
+use crate::entities::containers::{ContainerState, MountPointType};
+use crate::entities::Container;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::fmt::Write;
+
+/// Networks every container can be on, which do not say anything about how
+/// containers relate. Containers on the default bridge cannot reach each other by name.
+const IGNORED_NETWORKS: [&str; 3] = ["bridge", "host", "none"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphNodeKind {
+    /// A service of a compose project, standing for all of its containers.
+    Service,
+
+    /// A container that is not part of a compose project.
+    Container,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum GraphEdgeKind {
+    /// `depends_on` of a compose service, from the dependent to its dependency.
+    DependsOn,
+
+    /// Legacy `--link`, from the container that links to the linked one.
+    Link,
+
+    /// Both ends are attached to the network in `label`.
+    Network,
+
+    /// Both ends mount the volume in `label`.
+    Volume,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GraphNode {
+    /// `service:{project}/{service}` or `container:{id}`.
+    pub id: String,
+
+    pub kind: GraphNodeKind,
+    pub label: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+
+    /// Names of the containers of a service, or the name of a container.
+    pub containers: Vec<String>,
+
+    /// Number of running containers.
+    pub running: usize,
+
+    /// Referenced as a dependency or link target, but no such container exists.
+    pub missing: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct GraphEdge {
+    pub source: String,
+    pub target: String,
+    pub kind: GraphEdgeKind,
+
+    /// Condition of a dependency, alias of a link, or name of a network or volume.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
+
+    /// The target does not exist.
+    pub missing: bool,
+
+    /// Part of a cycle of dependencies or links.
+    pub in_cycle: bool,
+}
+
+/// How containers and compose services depend on each other.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DependencyGraph {
+    /// Sorted by id.
+    pub nodes: Vec<GraphNode>,
+
+    pub edges: Vec<GraphEdge>,
+
+    /// Ids of the nodes of every cycle of dependencies or links.
+    pub cycles: Vec<Vec<String>>,
+
+    /// The graph in Graphviz DOT format.
+    pub dot: String,
+}
+
+impl DependencyGraph {
+    /// Build the graph from the labels, networks and mounts of containers.
+    /// Containers of a compose service are merged into one node for the service.
+    pub fn from_containers(containers: &[Container]) -> Self {
+        let mut nodes: BTreeMap<String, GraphNode> = BTreeMap::new();
+        let mut edges: BTreeSet<GraphEdge> = BTreeSet::new();
+        let mut owners: Vec<(String, &Container)> = Vec::new();
+        let mut by_name: HashMap<String, String> = HashMap::new();
+
+        for container in containers {
+            let labels = container.labels.clone().unwrap_or_default();
+            let name = primary_name(container);
+            let is_oneoff = labels
+                .get(COMPOSE_ONEOFF_LABEL)
+                .is_some_and(|oneoff| oneoff.eq_ignore_ascii_case("true"));
+            let service = match (
+                labels.get(COMPOSE_PROJECT_LABEL),
+                labels.get(COMPOSE_SERVICE_LABEL),
+            ) {
+                (Some(project), Some(service)) if !is_oneoff => {
+                    Some((project.clone(), service.clone()))
+                }
+                _ => None,
+            };
+
+            let id = match &service {
+                Some((project, service)) => service_id(project, service),
+                None => format!("container:{}", container.id.as_deref().unwrap_or_default()),
+            };
+            let node = nodes.entry(id.clone()).or_insert_with(|| GraphNode {
+                id: id.clone(),
+                kind: match service {
+                    Some(_) => GraphNodeKind::Service,
+                    None => GraphNodeKind::Container,
+                },
+                label: service
+                    .as_ref()
+                    .map(|(_, service)| service.clone())
+                    .unwrap_or_else(|| name.clone()),
+                project: service.as_ref().map(|(project, _)| project.clone()),
+                containers: Vec::new(),
+                running: 0,
+                missing: false,
+            });
+            node.containers.push(name.clone());
+            if container.state == Some(ContainerState::Running) {
+                node.running += 1;
+            }
+
+            by_name.insert(name, id.clone());
+            if let Some(container_id) = &container.id {
+                by_name.insert(container_id.clone(), id.clone());
+            }
+            owners.push((id, container));
+        }
+
+        let mut missing: BTreeMap<String, GraphNode> = BTreeMap::new();
+        let mut missing_node = |id: String, kind, label: &str, project: Option<String>| {
+            missing.entry(id.clone()).or_insert_with(|| GraphNode {
+                id,
+                kind,
+                label: label.to_string(),
+                project,
+                containers: Vec::new(),
+                running: 0,
+                missing: true,
+            });
+        };
+
+        let mut networks: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        let mut volumes: BTreeMap<String, BTreeSet<String>> = BTreeMap::new();
+        for (id, container) in &owners {
+            let labels = container.labels.clone().unwrap_or_default();
+
+            let node = &nodes[id];
+            if let (Some(project), Some(label)) =
+                (&node.project, labels.get(COMPOSE_DEPENDS_ON_LABEL))
+            {
+                for dependency in ServiceDependency::parse_label(label) {
+                    let target = service_id(project, &dependency.service);
+                    let is_missing = !nodes.contains_key(&target);
+                    if is_missing {
+                        missing_node(
+                            target.clone(),
+                            GraphNodeKind::Service,
+                            &dependency.service,
+                            Some(project.clone()),
+                        );
+                    }
+                    edges.insert(GraphEdge {
+                        source: id.clone(),
+                        target,
+                        kind: GraphEdgeKind::DependsOn,
+                        label: Some(dependency.condition),
+                        missing: is_missing,
+                        in_cycle: false,
+                    });
+                }
+            }
+
+            // Linked containers get an extra name: `/{linking container}/{alias}`
+            for name in container.names.iter().flatten() {
+                let Some((parent, alias)) = name.trim_start_matches('/').split_once('/') else {
+                    continue;
+                };
+                if let Some(source) = by_name.get(parent) {
+                    edges.insert(GraphEdge {
+                        source: source.clone(),
+                        target: id.clone(),
+                        kind: GraphEdgeKind::Link,
+                        label: Some(alias.to_string()),
+                        missing: false,
+                        in_cycle: false,
+                    });
+                }
+            }
+
+            let endpoints = container
+                .network_settings
+                .as_ref()
+                .and_then(|settings| settings.networks.clone())
+                .unwrap_or_default();
+            for (network, endpoint) in endpoints {
+                for link in endpoint.links.iter().flatten() {
+                    let (target_name, alias) = link.split_once(':').unwrap_or((link, link));
+                    let target_name = target_name.trim_start_matches('/');
+                    let alias = alias.rsplit('/').next().unwrap_or(alias);
+                    let target = match by_name.get(target_name) {
+                        Some(target) => target.clone(),
+                        None => {
+                            let target = format!("container:{}", target_name);
+                            missing_node(
+                                target.clone(),
+                                GraphNodeKind::Container,
+                                target_name,
+                                None,
+                            );
+                            target
+                        }
+                    };
+                    edges.insert(GraphEdge {
+                        source: id.clone(),
+                        missing: !nodes.contains_key(&target),
+                        target,
+                        kind: GraphEdgeKind::Link,
+                        label: Some(alias.to_string()),
+                        in_cycle: false,
+                    });
+                }
+                if !IGNORED_NETWORKS.contains(&network.as_str()) {
+                    networks.entry(network).or_default().insert(id.clone());
+                }
+            }
+
+            for mount in container.mounts.iter().flatten() {
+                if mount.mount_type != Some(MountPointType::Volume) {
+                    continue;
+                }
+                if let Some(name) = &mount.name {
+                    volumes.entry(name.clone()).or_default().insert(id.clone());
+                }
+            }
+        }
+        nodes.extend(missing);
+
+        for (kind, members) in [
+            (GraphEdgeKind::Network, networks),
+            (GraphEdgeKind::Volume, volumes),
+        ] {
+            for (name, ids) in members {
+                let ids: Vec<&String> = ids.iter().collect();
+                for (index, source) in ids.iter().enumerate() {
+                    for target in &ids[index + 1..] {
+                        edges.insert(GraphEdge {
+                            source: source.to_string(),
+                            target: target.to_string(),
+                            kind,
+                            label: Some(name.clone()),
+                            missing: false,
+                            in_cycle: false,
+                        });
+                    }
+                }
+            }
+        }
+
+        let mut edges: Vec<GraphEdge> = edges.into_iter().collect();
+        let cycles = find_cycles(&nodes, &edges);
+        for edge in &mut edges {
+            edge.in_cycle = matches!(edge.kind, GraphEdgeKind::DependsOn | GraphEdgeKind::Link)
+                && cycles
+                    .iter()
+                    .any(|cycle| cycle.contains(&edge.source) && cycle.contains(&edge.target));
+        }
+
+        let mut graph = DependencyGraph {
+            nodes: nodes.into_values().collect(),
+            edges,
+            cycles,
+            dot: String::new(),
+        };
+        graph.dot = graph.to_dot();
+        graph
+    }
+
+    /// Render the graph in Graphviz DOT format. Services are boxes, containers
+    /// ellipses, and missing nodes and cycles are drawn in red.
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph dependencies {\n    rankdir=LR;\n");
+
+        for node in &self.nodes {
+            let mut attributes = vec![format!("label={}", quote(&node.label))];
+            attributes.push(match node.kind {
+                GraphNodeKind::Service => "shape=box".to_string(),
+                GraphNodeKind::Container => "shape=ellipse".to_string(),
+            });
+            if node.missing {
+                attributes.push("style=dashed".to_string());
+                attributes.push("color=red".to_string());
+            }
+            let _ = writeln!(dot, "    {} [{}];", quote(&node.id), attributes.join(", "));
+        }
+
+        for edge in &self.edges {
+            let mut attributes = Vec::new();
+            if let Some(label) = &edge.label {
+                attributes.push(format!("label={}", quote(label)));
+            }
+            match edge.kind {
+                GraphEdgeKind::DependsOn => {}
+                GraphEdgeKind::Link => attributes.push("style=bold".to_string()),
+                GraphEdgeKind::Network => {
+                    attributes.push("dir=none".to_string());
+                    attributes.push("style=dotted".to_string());
+                }
+                GraphEdgeKind::Volume => {
+                    attributes.push("dir=none".to_string());
+                    attributes.push("style=dashed".to_string());
+                }
+            }
+            if edge.missing || edge.in_cycle {
+                attributes.push("color=red".to_string());
+            }
+            let _ = writeln!(
+                dot,
+                "    {} -> {} [{}];",
+                quote(&edge.source),
+                quote(&edge.target),
+                attributes.join(", ")
+            );
+        }
+
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn service_id(project: &str, service: &str) -> String {
+    format!("service:{}/{}", project, service)
+}
+
+/// Name of a container without the leading slash. Link aliases are extra names
+/// of the form `/other/alias` and are skipped.
+fn primary_name(container: &Container) -> String {
+    let names = container.names.clone().unwrap_or_default();
+    names
+        .iter()
+        .map(|name| name.trim_start_matches('/'))
+        .find(|name| !name.contains('/'))
+        .or_else(|| names.first().map(|name| name.trim_start_matches('/')))
+        .unwrap_or_default()
+        .to_string()
+}
+
+fn quote(value: &str) -> String {
+    format!("\"{}\"", value.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+/// Strongly connected components with more than one node, or a node that
+/// depends on itself, over `depends_on` and link edges (Tarjan's algorithm).
+fn find_cycles(nodes: &BTreeMap<String, GraphNode>, edges: &[GraphEdge]) -> Vec<Vec<String>> {
+    struct Search<'a> {
+        successors: HashMap<&'a str, Vec<&'a str>>,
+        index: HashMap<&'a str, usize>,
+        low_link: HashMap<&'a str, usize>,
+        stack: Vec<&'a str>,
+        cycles: Vec<Vec<String>>,
+    }
+
+    impl<'a> Search<'a> {
+        fn visit(&mut self, node: &'a str) {
+            let index = self.index.len();
+            self.index.insert(node, index);
+            self.low_link.insert(node, index);
+            self.stack.push(node);
+
+            for successor in self.successors.get(node).cloned().unwrap_or_default() {
+                if !self.index.contains_key(successor) {
+                    self.visit(successor);
+                    let low_link = self.low_link[node].min(self.low_link[successor]);
+                    self.low_link.insert(node, low_link);
+                } else if self.stack.contains(&successor) {
+                    let low_link = self.low_link[node].min(self.index[successor]);
+                    self.low_link.insert(node, low_link);
+                }
+            }
+
+            if self.low_link[node] == self.index[node] {
+                let mut component = Vec::new();
+                while let Some(member) = self.stack.pop() {
+                    component.push(member.to_string());
+                    if member == node {
+                        break;
+                    }
+                }
+                let is_self_loop = self
+                    .successors
+                    .get(node)
+                    .is_some_and(|successors| successors.contains(&node));
+                if component.len() > 1 || is_self_loop {
+                    component.sort();
+                    self.cycles.push(component);
+                }
+            }
+        }
+    }
+
+    let mut search = Search {
+        successors: HashMap::new(),
+        index: HashMap::new(),
+        low_link: HashMap::new(),
+        stack: Vec::new(),
+        cycles: Vec::new(),
+    };
+    for edge in edges {
+        if matches!(edge.kind, GraphEdgeKind::DependsOn | GraphEdgeKind::Link) {
+            search
+                .successors
+                .entry(edge.source.as_str())
+                .or_default()
+                .push(edge.target.as_str());
+        }
+    }
+    for node in nodes.keys() {
+        if !search.index.contains_key(node.as_str()) {
+            search.visit(node);
+        }
+    }
+
+    search.cycles.sort();
+    search.cycles
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::containers::{ContainerNetworkSettings, EndpointSettings, MountPoint};
+
+    fn container(
+        id: &str,
+        names: &[&str],
+        labels: &[(&str, &str)],
+        networks: &[&str],
+    ) -> Container {
+        Container {
+            id: Some(id.to_string()),
+            names: Some(names.iter().map(|name| name.to_string()).collect()),
+            labels: Some(
+                labels
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), value.to_string()))
+                    .collect(),
+            ),
+            state: Some(ContainerState::Running),
+            network_settings: Some(ContainerNetworkSettings {
+                networks: Some(
+                    networks
+                        .iter()
+                        .map(|network| (network.to_string(), EndpointSettings::default()))
+                        .collect(),
+                ),
+            }),
+            ..Default::default()
+        }
+    }
+
+    fn service(id: &str, service: &str, depends_on: &str) -> Container {
+        container(
+            id,
+            &[&format!("/shop-{}-1", service)],
+            &[
+                (COMPOSE_PROJECT_LABEL, "shop"),
+                (COMPOSE_SERVICE_LABEL, service),
+                (COMPOSE_DEPENDS_ON_LABEL, depends_on),
+            ],
+            &["shop_default"],
+        )
+    }
+
+    fn edge<'a>(
+        graph: &'a DependencyGraph,
+        kind: GraphEdgeKind,
+        source: &str,
+    ) -> Vec<&'a GraphEdge> {
+        graph
+            .edges
+            .iter()
+            .filter(|edge| edge.kind == kind && edge.source == source)
+            .collect()
+    }
+
+    #[test]
+    fn test_graph_edges() {
+        let mut cache = container("c", &["/cache", "/legacy/redis"], &[], &["bridge"]);
+        cache.mounts = Some(vec![MountPoint {
+            mount_type: Some(MountPointType::Volume),
+            name: Some("data".to_string()),
+            ..Default::default()
+        }]);
+        let mut legacy = container("l", &["/legacy"], &[], &["bridge"]);
+        legacy.mounts = cache.mounts.clone();
+
+        let graph = DependencyGraph::from_containers(&[
+            service("w", "web", "api:service_started:false"),
+            service("a", "api", "db:service_healthy:false"),
+            cache,
+            legacy,
+        ]);
+
+        let ids: Vec<&str> = graph.nodes.iter().map(|node| node.id.as_str()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                "container:c",
+                "container:l",
+                "service:shop/api",
+                "service:shop/db",
+                "service:shop/web"
+            ]
+        );
+        assert!(graph.nodes[3].missing);
+
+        let depends_on = edge(&graph, GraphEdgeKind::DependsOn, "service:shop/api");
+        assert_eq!(depends_on[0].target, "service:shop/db");
+        assert!(depends_on[0].missing);
+
+        let links = edge(&graph, GraphEdgeKind::Link, "container:l");
+        assert_eq!(links[0].target, "container:c");
+        assert_eq!(links[0].label.as_deref(), Some("redis"));
+
+        let networks = edge(&graph, GraphEdgeKind::Network, "service:shop/api");
+        assert_eq!(networks[0].target, "service:shop/web");
+        assert_eq!(networks[0].label.as_deref(), Some("shop_default"));
+        assert!(edge(&graph, GraphEdgeKind::Network, "container:c").is_empty());
+
+        let volumes = edge(&graph, GraphEdgeKind::Volume, "container:c");
+        assert_eq!(volumes[0].target, "container:l");
+        assert!(graph.cycles.is_empty());
+    }
+
+    #[test]
+    fn test_graph_cycles_and_dot() {
+        let graph = DependencyGraph::from_containers(&[
+            service("w", "web", "api:service_started:false"),
+            service("a", "api", "web:service_started:false"),
+            service("d", "db", ""),
+        ]);
+
+        assert_eq!(
+            graph.cycles,
+            vec![vec![
+                "service:shop/api".to_string(),
+                "service:shop/web".to_string()
+            ]]
+        );
+        let in_cycle = graph.edges.iter().filter(|edge| edge.in_cycle).count();
+        assert_eq!(in_cycle, 2);
+
+        assert!(graph.dot.starts_with("digraph dependencies {"));
+        assert!(graph.dot.contains(
+            "\"service:shop/web\" -> \"service:shop/api\" [label=\"service_started\", color=red];"
+        ));
+        assert!(graph
+            .dot
+            .contains("\"service:shop/db\" [label=\"db\", shape=box];"));
+    }
+}
@@ -0,0 +1,1069 @@
+use crate::entities::compose::{dependency_order, ComposeFile};
+use crate::entities::containers::{is_valid_container_name, parse_restart_policy, MountPointType};
+use serde::{Deserialize, Serialize};
+use serde_norway::{Mapping, Value};
+use std::collections::HashMap;
+
+/// Service keys that are read. Others are reported as warnings and ignored.
+const SERVICE_KEYS: [&str; 39] = [
+    "image",
+    "container_name",
+    "command",
+    "entrypoint",
+    "environment",
+    "env_file",
+    "ports",
+    "expose",
+    "volumes",
+    "tmpfs",
+    "networks",
+    "network_mode",
+    "depends_on",
+    "restart",
+    "labels",
+    "hostname",
+    "domainname",
+    "user",
+    "working_dir",
+    "tty",
+    "stdin_open",
+    "privileged",
+    "init",
+    "read_only",
+    "cap_add",
+    "cap_drop",
+    "dns",
+    "extra_hosts",
+    "cpus",
+    "cpu_shares",
+    "mem_limit",
+    "mem_reservation",
+    "memswap_limit",
+    "pids_limit",
+    "stop_signal",
+    "stop_grace_period",
+    "healthcheck",
+    "profiles",
+    "pull_policy",
+];
+
+/// Top-level keys that are read. `version` is obsolete and ignored.
+const TOP_LEVEL_KEYS: [&str; 5] = ["name", "version", "services", "networks", "volumes"];
+
+/// Sequences an override file replaces instead of extending.
+const REPLACED_SEQUENCES: [&str; 3] = ["command", "entrypoint", "test"];
+
+const DEPENDENCY_CONDITIONS: [&str; 3] = [
+    "service_started",
+    "service_healthy",
+    "service_completed_successfully",
+];
+
+/// A compose file as read from disk.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ComposeSource {
+    pub path: String,
+    pub content: String,
+}
+
+/// A problem found while loading a compose file, with the line it is on when known.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComposeIssue {
+    pub file: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub column: Option<usize>,
+
+    pub message: String,
+}
+
+impl ::std::fmt::Display for ComposeIssue {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match (self.line, self.column) {
+            (Some(line), Some(column)) => {
+                write!(f, "{}:{}:{}: {}", self.file, line, column, self.message)
+            }
+            (Some(line), None) => write!(f, "{}:{}: {}", self.file, line, self.message),
+            _ => write!(f, "{}: {}", self.file, self.message),
+        }
+    }
+}
+
+impl ComposeIssue {
+    fn from_yaml_error(file: &str, error: &serde_norway::Error) -> Self {
+        let location = error.location();
+        ComposeIssue {
+            file: file.to_string(),
+            line: location.as_ref().map(|location| location.line()),
+            column: location.as_ref().map(|location| location.column()),
+            message: strip_location(&error.to_string()),
+        }
+    }
+}
+
+/// A compose project loaded from one or more files, later files overriding earlier ones.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposeProjectConfig {
+    pub name: String,
+
+    /// Directory relative paths are resolved against.
+    pub working_dir: String,
+
+    pub config_files: Vec<String>,
+    pub file: ComposeFile,
+    pub warnings: Vec<ComposeIssue>,
+
+    /// Variables used for interpolation, also the values of environment
+    /// entries that have none.
+    #[serde(skip)]
+    pub(crate) environment: HashMap<String, String>,
+}
+
+/// Outcome of checking compose files without running them.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposeValidation {
+    pub valid: bool,
+    pub errors: Vec<ComposeIssue>,
+    pub warnings: Vec<ComposeIssue>,
+
+    /// The resolved project, when the files are valid.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub project: Option<ComposeProjectConfig>,
+}
+
+impl From<Result<ComposeProjectConfig, Vec<ComposeIssue>>> for ComposeValidation {
+    fn from(result: Result<ComposeProjectConfig, Vec<ComposeIssue>>) -> Self {
+        match result {
+            Ok(project) => ComposeValidation {
+                valid: true,
+                errors: Vec::new(),
+                warnings: project.warnings.clone(),
+                project: Some(project),
+            },
+            Err(errors) => ComposeValidation {
+                valid: false,
+                errors,
+                warnings: Vec::new(),
+                project: None,
+            },
+        }
+    }
+}
+
+impl ComposeProjectConfig {
+    /// Interpolate, merge and validate compose files. `environment` holds the
+    /// variables of the `.env` file overridden by the ones of the app.
+    /// All problems found are returned at once.
+    pub fn load(
+        sources: &[ComposeSource],
+        working_dir: &str,
+        environment: HashMap<String, String>,
+    ) -> Result<Self, Vec<ComposeIssue>> {
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+        let mut merged = Value::Mapping(Mapping::new());
+
+        for source in sources {
+            let mut document = match serde_norway::from_str::<Value>(&source.content) {
+                Ok(document @ Value::Mapping(_)) => document,
+                Ok(Value::Null) => continue,
+                Ok(_) => {
+                    errors.push(ComposeIssue {
+                        file: source.path.clone(),
+                        line: Some(1),
+                        column: None,
+                        message: "A compose file must be a mapping".to_string(),
+                    });
+                    continue;
+                }
+                Err(e) => {
+                    errors.push(ComposeIssue::from_yaml_error(&source.path, &e));
+                    continue;
+                }
+            };
+
+            let mut problems = Vec::new();
+            let mut unset = Vec::new();
+            interpolate_value(
+                &mut document,
+                &environment,
+                &mut Vec::new(),
+                &mut problems,
+                &mut unset,
+            );
+            for (path, message) in problems {
+                errors.push(source.issue_at(&path, message));
+            }
+            for (path, name) in unset {
+                warnings.push(source.issue_at(
+                    &path,
+                    format!(
+                        "The {} variable is not set, defaulting to a blank string",
+                        name
+                    ),
+                ));
+            }
+
+            warnings.extend(unsupported_keys(source, &document));
+            normalize_dicts(&mut document);
+            merge(&mut merged, document, &mut Vec::new());
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        // Deserialized from text so errors carry the path of the value
+        let text = serde_norway::to_string(&merged).map_err(|e| {
+            vec![ComposeIssue {
+                file: sources.last().map(|s| s.path.clone()).unwrap_or_default(),
+                line: None,
+                column: None,
+                message: e.to_string(),
+            }]
+        })?;
+        let file: ComposeFile = serde_norway::from_str(&text).map_err(|e| {
+            let message = strip_location(&e.to_string());
+            let path = message
+                .split_once(": ")
+                .map(|(path, _)| path)
+                .filter(|path| !path.contains(' '))
+                .map(parse_path)
+                .unwrap_or_default();
+            vec![locate(sources, &path, message)]
+        })?;
+
+        let errors: Vec<ComposeIssue> = validate(&file, &merged)
+            .into_iter()
+            .map(|(path, message)| locate(sources, &path, message))
+            .collect();
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let name = file
+            .name
+            .clone()
+            .or_else(|| environment.get("COMPOSE_PROJECT_NAME").cloned())
+            .unwrap_or_else(|| {
+                std::path::Path::new(working_dir)
+                    .file_name()
+                    .map(|name| name.to_string_lossy().to_string())
+                    .unwrap_or_default()
+            });
+        let name = normalize_project_name(&name);
+        if name.is_empty() {
+            return Err(vec![ComposeIssue {
+                file: sources.first().map(|s| s.path.clone()).unwrap_or_default(),
+                line: None,
+                column: None,
+                message: "Project name must contain a letter or a digit".to_string(),
+            }]);
+        }
+
+        Ok(ComposeProjectConfig {
+            name,
+            working_dir: working_dir.to_string(),
+            config_files: sources.iter().map(|source| source.path.clone()).collect(),
+            file,
+            warnings,
+            environment,
+        })
+    }
+}
+
+impl ComposeSource {
+    fn issue_at(&self, path: &[String], message: String) -> ComposeIssue {
+        ComposeIssue {
+            file: self.path.clone(),
+            line: find_line(&self.content, path).map(|(line, _)| line),
+            column: None,
+            message,
+        }
+    }
+}
+
+/// Read a `.env` file. Values may be quoted and lines may start with `export`.
+pub fn parse_dotenv(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .filter_map(|line| {
+            let line = line.strip_prefix("export ").unwrap_or(line);
+            let (key, value) = line.split_once('=')?;
+            let value = value.trim();
+            let value = match value.chars().next() {
+                Some(quote @ ('"' | '\'')) => value
+                    .strip_prefix(quote)
+                    .and_then(|value| value.rsplit_once(quote))
+                    .map(|(value, _)| value.to_string())
+                    .unwrap_or_else(|| value.to_string()),
+                // Unquoted values end at a comment
+                _ => value
+                    .split_once(" #")
+                    .map_or(value, |(value, _)| value)
+                    .trim_end()
+                    .to_string(),
+            };
+            Some((key.trim().to_string(), value))
+        })
+        .collect()
+}
+
+/// Lowercase the name and drop the characters compose does not allow.
+fn normalize_project_name(name: &str) -> String {
+    let name: String = name
+        .to_lowercase()
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric() || *c == '_' || *c == '-')
+        .collect();
+    name.trim_start_matches(['_', '-']).to_string()
+}
+
+/// Replace `$VAR`, `${VAR}` and `${VAR:-default}` style references in `text`.
+/// `$$` is a literal `$`. Names of variables that are not set are added to `unset`.
+pub(crate) fn interpolate(
+    text: &str,
+    environment: &HashMap<String, String>,
+    unset: &mut Vec<String>,
+) -> Result<String, String> {
+    let mut result = String::with_capacity(text.len());
+    let mut chars = text.char_indices().peekable();
+
+    while let Some((_, c)) = chars.next() {
+        if c != '$' {
+            result.push(c);
+            continue;
+        }
+        match chars.peek().copied() {
+            Some((_, '$')) => {
+                chars.next();
+                result.push('$');
+            }
+            Some((start, '{')) => {
+                chars.next();
+                let mut depth = 1;
+                let mut end = None;
+                for (index, c) in chars.by_ref() {
+                    match c {
+                        '{' => depth += 1,
+                        '}' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                end = Some(index);
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                }
+                let end = end.ok_or_else(|| format!("Unclosed variable reference in {}", text))?;
+                result.push_str(&substitute(&text[start + 1..end], environment, unset)?);
+            }
+            Some((start, c)) if c.is_ascii_alphabetic() || c == '_' => {
+                let mut end = text.len();
+                while let Some((index, c)) = chars.peek().copied() {
+                    if !(c.is_ascii_alphanumeric() || c == '_') {
+                        end = index;
+                        break;
+                    }
+                    chars.next();
+                }
+                result.push_str(&substitute(&text[start..end], environment, unset)?);
+            }
+            _ => result.push('$'),
+        }
+    }
+
+    Ok(result)
+}
+
+/// Resolve the inside of `${...}`.
+fn substitute(
+    expression: &str,
+    environment: &HashMap<String, String>,
+    unset: &mut Vec<String>,
+) -> Result<String, String> {
+    let name_end = expression
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+        .unwrap_or(expression.len());
+    let (name, operation) = expression.split_at(name_end);
+    if name.is_empty() {
+        return Err(format!("Invalid variable reference: ${{{}}}", expression));
+    }
+
+    let value = environment.get(name);
+    let is_empty = value.is_none_or(|value| value.is_empty());
+    let (operator, operand) = match operation.strip_prefix(':') {
+        Some(rest) => (
+            format!(":{}", rest.get(..1).unwrap_or_default()),
+            rest.get(1..),
+        ),
+        None => (
+            operation.get(..1).unwrap_or_default().to_string(),
+            operation.get(1..),
+        ),
+    };
+    let mut argument = || interpolate(operand.unwrap_or_default(), environment, unset);
+
+    match operator.as_str() {
+        "" => match value {
+            Some(value) => Ok(value.clone()),
+            None => {
+                if !unset.iter().any(|unset| unset == name) {
+                    unset.push(name.to_string());
+                }
+                Ok(String::new())
+            }
+        },
+        ":-" if is_empty => argument(),
+        "-" if value.is_none() => argument(),
+        ":-" | "-" => Ok(value.cloned().unwrap_or_default()),
+        ":?" | "?" if (operator == ":?" && is_empty) || value.is_none() => {
+            let message = argument()?;
+            Err(match message.is_empty() {
+                true => format!("Required variable {} is missing a value", name),
+                false => format!("Required variable {} is missing a value: {}", name, message),
+            })
+        }
+        ":?" | "?" => Ok(value.cloned().unwrap_or_default()),
+        ":+" if !is_empty => argument(),
+        "+" if value.is_some() => argument(),
+        ":+" | "+" => Ok(String::new()),
+        _ => Err(format!("Invalid variable reference: ${{{}}}", expression)),
+    }
+}
+
+/// Interpolate every string in `value`. Problems are collected with the path they occur at.
+fn interpolate_value(
+    value: &mut Value,
+    environment: &HashMap<String, String>,
+    path: &mut Vec<String>,
+    problems: &mut Vec<(Vec<String>, String)>,
+    unset: &mut Vec<(Vec<String>, String)>,
+) {
+    match value {
+        Value::String(text) if text.contains('$') => {
+            let mut names = Vec::new();
+            match interpolate(text, environment, &mut names) {
+                Ok(interpolated) => *text = interpolated,
+                Err(e) => problems.push((path.clone(), e)),
+            }
+            for name in names {
+                if !unset.iter().any(|(_, unset)| *unset == name) {
+                    unset.push((path.clone(), name));
+                }
+            }
+        }
+        Value::Sequence(values) => {
+            for (index, value) in values.iter_mut().enumerate() {
+                path.push(format!("[{}]", index));
+                interpolate_value(value, environment, path, problems, unset);
+                path.pop();
+            }
+        }
+        Value::Mapping(entries) => {
+            for (key, value) in entries.iter_mut() {
+                path.push(key_string(key));
+                interpolate_value(value, environment, path, problems, unset);
+                path.pop();
+            }
+        }
+        _ => {}
+    }
+}
+
+fn key_string(key: &Value) -> String {
+    crate::entities::compose::file::scalar_string(key).unwrap_or_default()
+}
+
+/// Report keys that are not read, except extension fields starting with `x-`.
+fn unsupported_keys(source: &ComposeSource, document: &Value) -> Vec<ComposeIssue> {
+    let mut warnings = Vec::new();
+    let Value::Mapping(entries) = document else {
+        return warnings;
+    };
+
+    for key in entries.keys().map(key_string) {
+        if !key.starts_with("x-") && !TOP_LEVEL_KEYS.contains(&key.as_str()) {
+            let message = format!("{} is not supported and was ignored", key);
+            warnings.push(source.issue_at(&[key], message));
+        }
+    }
+    if let Some(Value::Mapping(services)) = entries.get("services") {
+        for (service, config) in services {
+            let service = key_string(service);
+            let Value::Mapping(config) = config else {
+                continue;
+            };
+            for key in config.keys().map(key_string) {
+                if !key.starts_with("x-") && !SERVICE_KEYS.contains(&key.as_str()) {
+                    warnings.push(source.issue_at(
+                        &["services".to_string(), service.clone(), key.clone()],
+                        format!(
+                            "{} of service {} is not supported and was ignored",
+                            key, service
+                        ),
+                    ));
+                }
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Turn `KEY=value` lists of services into mappings, so files using
+/// different syntaxes can be merged.
+fn normalize_dicts(document: &mut Value) {
+    let Some(Value::Mapping(services)) = document.get_mut("services") else {
+        return;
+    };
+    for config in services.values_mut() {
+        for key in ["environment", "labels"] {
+            let Some(value) = config.get_mut(key) else {
+                continue;
+            };
+            let Value::Sequence(entries) = value else {
+                continue;
+            };
+            let mapping: Mapping = entries
+                .iter()
+                .filter_map(|entry| entry.as_str())
+                .map(|entry| match entry.split_once('=') {
+                    Some((key, value)) => (Value::from(key), Value::from(value)),
+                    None => (Value::from(entry), Value::Null),
+                })
+                .collect();
+            *value = Value::Mapping(mapping);
+        }
+    }
+}
+
+/// Merge an override file into `base`. Mappings are merged key by key, lists
+/// are extended, and everything else is replaced. Volumes of a service replace
+/// the ones with the same target.
+fn merge(base: &mut Value, over: Value, path: &mut Vec<String>) {
+    match (base, over) {
+        (Value::Mapping(base), Value::Mapping(over)) => {
+            for (key, value) in over {
+                match base.get_mut(&key) {
+                    Some(existing) => {
+                        path.push(key_string(&key));
+                        merge(existing, value, path);
+                        path.pop();
+                    }
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Value::Sequence(base), Value::Sequence(over))
+            if !REPLACED_SEQUENCES
+                .contains(&path.last().map(String::as_str).unwrap_or_default()) =>
+        {
+            let is_service_volumes =
+                path.len() == 3 && path[0] == "services" && path[2] == "volumes";
+            for value in over {
+                if is_service_volumes {
+                    let target = volume_target(&value);
+                    base.retain(|existing| target.is_none() || volume_target(existing) != target);
+                    base.push(value);
+                } else if !base.contains(&value) {
+                    base.push(value);
+                }
+            }
+        }
+        (base, over) => *base = over,
+    }
+}
+
+/// Target of a volume in short (`source:target[:mode]`) or long syntax.
+fn volume_target(volume: &Value) -> Option<String> {
+    match volume {
+        Value::String(volume) => {
+            let parts: Vec<&str> = volume.split(':').collect();
+            match parts.as_slice() {
+                [target] => Some(target.to_string()),
+                // Windows drive letter in the source, e.g. `C:\data:/data`
+                [drive, _, target, ..] if drive.len() == 1 => Some(target.to_string()),
+                [_, target, ..] => Some(target.to_string()),
+                [] => None,
+            }
+        }
+        Value::Mapping(_) => volume
+            .get("target")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        _ => None,
+    }
+}
+
+/// Checks that need the whole project. Returns the path each problem is at.
+fn validate(file: &ComposeFile, merged: &Value) -> Vec<(Vec<String>, String)> {
+    let mut errors = Vec::new();
+    let at = |parts: &[&str]| {
+        parts
+            .iter()
+            .map(|part| part.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    if file.services.is_empty() {
+        errors.push((at(&["services"]), "No services are defined".to_string()));
+    }
+
+    for (name, service) in &file.services {
+        let is_valid_name = name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'));
+        if name.is_empty() || !is_valid_name {
+            errors.push((
+                at(&["services", name]),
+                format!("Invalid service name: {}", name),
+            ));
+        }
+
+        if service.image.is_none() {
+            let has_build = merged
+                .get("services")
+                .and_then(|services| services.get(name.as_str()))
+                .and_then(|service| service.get("build"))
+                .is_some();
+            errors.push((
+                at(&["services", name]),
+                match has_build {
+                    true => format!(
+                        "Service {} has no image. Building images from compose files is not supported",
+                        name
+                    ),
+                    false => format!("Service {} has no image", name),
+                },
+            ));
+        }
+
+        if let Some(container_name) = &service.container_name {
+            if !is_valid_container_name(container_name) {
+                errors.push((
+                    at(&["services", name, "container_name"]),
+                    format!(
+                        "Invalid container name: {}. Only [a-zA-Z0-9][a-zA-Z0-9_.-] are allowed",
+                        container_name
+                    ),
+                ));
+            }
+        }
+
+        if let Some(restart) = &service.restart {
+            if let Err(e) = parse_restart_policy(restart) {
+                errors.push((at(&["services", name, "restart"]), e));
+            }
+        }
+
+        for (dependency, config) in &service.depends_on {
+            let path = at(&["services", name, "depends_on", dependency]);
+            if config.required && !file.services.contains_key(dependency) {
+                errors.push((
+                    path.clone(),
+                    format!(
+                        "Service {} depends on undefined service {}",
+                        name, dependency
+                    ),
+                ));
+            }
+            if !DEPENDENCY_CONDITIONS.contains(&config.condition.as_str()) {
+                errors.push((
+                    path,
+                    format!("Invalid dependency condition: {}", config.condition),
+                ));
+            }
+        }
+
+        if service.network_mode.is_some() && !service.networks.is_empty() {
+            errors.push((
+                at(&["services", name, "network_mode"]),
+                format!(
+                    "Service {} cannot have both network_mode and networks",
+                    name
+                ),
+            ));
+        }
+        if let Some(other) = service
+            .network_mode
+            .as_deref()
+            .and_then(|mode| mode.strip_prefix("service:"))
+        {
+            if !file.services.contains_key(other) {
+                errors.push((
+                    at(&["services", name, "network_mode"]),
+                    format!(
+                        "Service {} uses the network of undefined service {}",
+                        name, other
+                    ),
+                ));
+            }
+        }
+        for network in service.networks.keys() {
+            if network != "default" && !file.networks.contains_key(network) {
+                errors.push((
+                    at(&["services", name, "networks", network]),
+                    format!("Service {} uses undefined network {}", name, network),
+                ));
+            }
+        }
+
+        for (index, volume) in service.volumes.iter().enumerate() {
+            let Some(source) = volume.source.as_deref() else {
+                continue;
+            };
+            if volume.mount_type == MountPointType::Volume && !file.volumes.contains_key(source) {
+                errors.push((
+                    at(&["services", name, "volumes", &format!("[{}]", index)]),
+                    format!("Service {} uses undefined volume {}", name, source),
+                ));
+            }
+        }
+    }
+
+    let nodes: Vec<(&str, Vec<&str>)> = file
+        .services
+        .iter()
+        .map(|(name, service)| {
+            (
+                name.as_str(),
+                service.depends_on.keys().map(String::as_str).collect(),
+            )
+        })
+        .collect();
+    if let Err(cycle) = dependency_order(&nodes) {
+        errors.push((
+            at(&["services"]),
+            format!("Circular dependency between services: {}", cycle.join(", ")),
+        ));
+    }
+
+    errors
+}
+
+/// Remove the ` at line X column Y` serde_norway appends to its messages.
+fn strip_location(message: &str) -> String {
+    match message.rfind(" at line ") {
+        Some(index) => message[..index].to_string(),
+        None => message.to_string(),
+    }
+}
+
+/// Split a serde path like `services.web.ports[1]` into its segments.
+fn parse_path(path: &str) -> Vec<String> {
+    let mut segments = Vec::new();
+    for part in path.split('.') {
+        match part.find('[') {
+            Some(index) => {
+                if index > 0 {
+                    segments.push(part[..index].to_string());
+                }
+                segments.extend(part[index..].split_inclusive(']').map(str::to_string));
+            }
+            None => segments.push(part.to_string()),
+        }
+    }
+    segments
+}
+
+/// Attach a problem to the line of the file that defines `path` last. Falls back
+/// to the closest parent that could be found.
+fn locate(sources: &[ComposeSource], path: &[String], message: String) -> ComposeIssue {
+    let mut best: Option<(&ComposeSource, usize, usize)> = None;
+    for source in sources {
+        if let Some((line, depth)) = find_line(&source.content, path) {
+            if best.is_none_or(|(_, _, best_depth)| depth >= best_depth) {
+                best = Some((source, line, depth));
+            }
+        }
+    }
+
+    ComposeIssue {
+        file: best
+            .map(|(source, _, _)| source.path.clone())
+            .or_else(|| sources.last().map(|source| source.path.clone()))
+            .unwrap_or_default(),
+        line: best.map(|(_, line, _)| line),
+        column: None,
+        message,
+    }
+}
+
+/// A line of a block-style YAML document. List items are split into a `-`
+/// entry and an entry for what follows the dash.
+struct YamlLine<'a> {
+    line: usize,
+    indent: usize,
+    content: &'a str,
+}
+
+fn yaml_lines(text: &str) -> Vec<YamlLine<'_>> {
+    let mut lines = Vec::new();
+    for (index, raw) in text.lines().enumerate() {
+        let trimmed = raw.trim_start();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let mut indent = raw.len() - trimmed.len();
+        let mut content = trimmed;
+        loop {
+            if content == "-" || content.starts_with("- ") {
+                lines.push(YamlLine {
+                    line: index + 1,
+                    indent,
+                    content: "-",
+                });
+                let rest = content[1..].trim_start();
+                if rest.is_empty() {
+                    break;
+                }
+                indent += content.len() - rest.len();
+                content = rest;
+            } else {
+                lines.push(YamlLine {
+                    line: index + 1,
+                    indent,
+                    content,
+                });
+                break;
+            }
+        }
+    }
+    lines
+}
+
+/// Key of a `key: value` line, without quotes.
+fn line_key(content: &str) -> Option<&str> {
+    let end = content
+        .char_indices()
+        .find(|(index, c)| {
+            *c == ':' && matches!(content[index + 1..].chars().next(), None | Some(' '))
+        })
+        .map(|(index, _)| index)?;
+    Some(content[..end].trim().trim_matches(['"', '\'']))
+}
+
+/// Find the line of the value at `path` in a block-style YAML document.
+/// Returns the line of the deepest segment found and how many segments matched.
+fn find_line(text: &str, path: &[String]) -> Option<(usize, usize)> {
+    let lines = yaml_lines(text);
+    let (mut start, mut end) = (0, lines.len());
+    let mut found = None;
+
+    for (depth, segment) in path.iter().enumerate() {
+        let Some(indent) = lines[start..end].iter().map(|line| line.indent).min() else {
+            break;
+        };
+        let index = segment
+            .strip_prefix('[')
+            .and_then(|index| index.strip_suffix(']'))
+            .and_then(|index| index.parse::<usize>().ok());
+
+        let position = match index {
+            Some(index) => lines[start..end]
+                .iter()
+                .enumerate()
+                .filter(|(_, line)| line.indent == indent && line.content == "-")
+                .nth(index)
+                .map(|(position, _)| position),
+            // Keys of mappings, or entries of lists like `depends_on: [db]`
+            None => lines[start..end]
+                .iter()
+                .position(|line| {
+                    line.indent == indent && line_key(line.content) == Some(segment.as_str())
+                })
+                .or_else(|| {
+                    lines[start..end].windows(2).position(|pair| {
+                        pair[0].indent == indent
+                            && pair[0].content == "-"
+                            && pair[1].line == pair[0].line
+                            && pair[1].content.trim_matches(['"', '\'']) == segment
+                    })
+                }),
+        };
+        let Some(position) = position.map(|position| start + position) else {
+            break;
+        };
+        found = Some((lines[position].line, depth + 1));
+
+        // Lists may be written at the same indentation as their key
+        let block_end = lines[position + 1..end]
+            .iter()
+            .position(|line| {
+                line.indent < indent
+                    || (line.indent == indent && (index.is_some() || line.content != "-"))
+            })
+            .map_or(end, |offset| position + 1 + offset);
+        start = position + 1;
+        end = block_end;
+    }
+
+    found
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn source(path: &str, content: &str) -> ComposeSource {
+        ComposeSource {
+            path: path.to_string(),
+            content: content.to_string(),
+        }
+    }
+
+    fn environment(vars: &[(&str, &str)]) -> HashMap<String, String> {
+        vars.iter()
+            .map(|(key, value)| (key.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_interpolate() {
+        let env = environment(&[("TAG", "1.27"), ("EMPTY", "")]);
+        let mut unset = Vec::new();
+
+        assert_eq!(
+            interpolate("nginx:${TAG}", &env, &mut unset).unwrap(),
+            "nginx:1.27"
+        );
+        assert_eq!(
+            interpolate("$TAG-alpine", &env, &mut unset).unwrap(),
+            "1.27-alpine"
+        );
+        assert_eq!(
+            interpolate("${EMPTY:-${TAG}}", &env, &mut unset).unwrap(),
+            "1.27"
+        );
+        assert_eq!(interpolate("${EMPTY-x}", &env, &mut unset).unwrap(), "");
+        assert_eq!(interpolate("${TAG:+set}", &env, &mut unset).unwrap(), "set");
+        assert_eq!(
+            interpolate("cost: $$5", &env, &mut unset).unwrap(),
+            "cost: $5"
+        );
+        assert!(unset.is_empty());
+
+        assert_eq!(interpolate("${MISSING}", &env, &mut unset).unwrap(), "");
+        assert_eq!(unset, vec!["MISSING".to_string()]);
+        assert_eq!(
+            interpolate("${MISSING:?set it}", &env, &mut unset).unwrap_err(),
+            "Required variable MISSING is missing a value: set it"
+        );
+    }
+
+    #[test]
+    fn test_load_with_override() {
+        let base = source(
+            "compose.yaml",
+            r#"
+services:
+  web:
+    image: "nginx:${TAG:-latest}"
+    environment:
+      - MODE=dev
+    ports:
+      - "8080:80"
+    volumes:
+      - ./html:/usr/share/nginx/html
+    depends_on: [db]
+  db:
+    image: postgres:16
+    volumes:
+      - data:/var/lib/postgresql/data
+volumes:
+  data:
+"#,
+        );
+        let over = source(
+            "compose.override.yaml",
+            r#"
+services:
+  web:
+    environment:
+      MODE: prod
+    ports:
+      - "8443:443"
+    volumes:
+      - ./public:/usr/share/nginx/html:ro
+"#,
+        );
+
+        let project = ComposeProjectConfig::load(
+            &[base, over],
+            "/home/me/Shop",
+            environment(&[("TAG", "1.27")]),
+        )
+        .unwrap();
+
+        assert_eq!(project.name, "shop");
+        let web = &project.file.services["web"];
+        assert_eq!(web.image.as_deref(), Some("nginx:1.27"));
+        assert_eq!(web.environment["MODE"].as_deref(), Some("prod"));
+        assert_eq!(web.ports.len(), 2);
+        assert_eq!(web.volumes.len(), 1);
+        assert_eq!(web.volumes[0].source.as_deref(), Some("./public"));
+        assert!(project.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_errors_point_at_lines() {
+        let file = source(
+            "compose.yaml",
+            "services:\n  web:\n    image: nginx\n    tty: maybe\n",
+        );
+        let errors = ComposeProjectConfig::load(&[file], "/srv/app", HashMap::new()).unwrap_err();
+        assert_eq!(errors[0].line, Some(4));
+        assert!(errors[0].message.contains("services.web.tty"));
+
+        let file = source(
+            "compose.yaml",
+            r#"services:
+  web:
+    image: nginx
+    depends_on:
+      - api
+    networks:
+      - front
+    build: .
+  db:
+    build: ./db
+"#,
+        );
+        let errors = ComposeProjectConfig::load(&[file], "/srv/app", HashMap::new()).unwrap_err();
+        let lines: Vec<Option<usize>> = errors.iter().map(|error| error.line).collect();
+        assert_eq!(lines, vec![Some(9), Some(5), Some(7)]);
+        assert!(errors[0].message.contains("Building images"));
+
+        let file = source("compose.yaml", "services:\n  web:\n    image: [nginx\n");
+        let errors = ComposeProjectConfig::load(&[file], "/srv/app", HashMap::new()).unwrap_err();
+        assert!(errors[0].line.is_some());
+    }
+
+    #[test]
+    fn test_unsupported_keys_are_warnings() {
+        let file = source(
+            "compose.yaml",
+            "version: '3'\nx-common: &common\n  restart: always\nservices:\n  web:\n    image: nginx\n    deploy:\n      replicas: 2\n",
+        );
+        let project = ComposeProjectConfig::load(&[file], "/srv/app", HashMap::new()).unwrap();
+
+        assert_eq!(project.warnings.len(), 1);
+        assert_eq!(project.warnings[0].line, Some(7));
+    }
+
+    #[test]
+    fn test_parse_dotenv() {
+        let env = parse_dotenv("# comment\nexport TAG=1.27\nNAME=\"my app\"\nPORT=8080 # web\n");
+        assert_eq!(env["TAG"], "1.27");
+        assert_eq!(env["NAME"], "my app");
+        assert_eq!(env["PORT"], "8080");
+    }
+}
@@ -0,0 +1,12 @@
+mod create;
+mod file;
+mod graph;
+mod load;
+mod project;
+mod run;
+
+pub use file::*;
+pub use graph::*;
+pub use load::*;
+pub use project::*;
+pub use run::*;
@@ -0,0 +1,369 @@
+use crate::entities::containers::ContainerState;
+use crate::entities::Container;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+
+pub const COMPOSE_PROJECT_LABEL: &str = "com.docker.compose.project";
+pub const COMPOSE_SERVICE_LABEL: &str = "com.docker.compose.service";
+pub const COMPOSE_WORKING_DIR_LABEL: &str = "com.docker.compose.project.working_dir";
+pub const COMPOSE_CONFIG_FILES_LABEL: &str = "com.docker.compose.project.config_files";
+pub const COMPOSE_DEPENDS_ON_LABEL: &str = "com.docker.compose.depends_on";
+pub const COMPOSE_CONTAINER_NUMBER_LABEL: &str = "com.docker.compose.container-number";
+pub const COMPOSE_ONEOFF_LABEL: &str = "com.docker.compose.oneoff";
+pub const COMPOSE_CONFIG_HASH_LABEL: &str = "com.docker.compose.config-hash";
+pub const COMPOSE_NETWORK_LABEL: &str = "com.docker.compose.network";
+pub const COMPOSE_VOLUME_LABEL: &str = "com.docker.compose.volume";
+
+/// A dependency of a service, as written in the `depends_on` label:
+/// `service:condition:restart`, separated by commas.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ServiceDependency {
+    pub service: String,
+
+    /// `service_started`, `service_healthy` or `service_completed_successfully`.
+    pub condition: String,
+
+    /// Restart the service when the dependency is restarted.
+    pub restart: bool,
+}
+
+impl ServiceDependency {
+    pub fn parse_label(label: &str) -> Vec<ServiceDependency> {
+        label
+            .split(',')
+            .map(str::trim)
+            .filter(|dependency| !dependency.is_empty())
+            .map(|dependency| {
+                let mut parts = dependency.split(':');
+                ServiceDependency {
+                    service: parts.next().unwrap_or_default().to_string(),
+                    condition: parts
+                        .next()
+                        .filter(|condition| !condition.is_empty())
+                        .unwrap_or("service_started")
+                        .to_string(),
+                    restart: parts.next() == Some("true"),
+                }
+            })
+            .collect()
+    }
+}
+
+/// A container that belongs to a service of a compose project.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectContainer {
+    pub id: String,
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub state: Option<ContainerState>,
+
+    /// Human-readable status, e.g. `Up 2 hours`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+
+    /// Replica number of the container within its service.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub number: Option<u32>,
+}
+
+/// A service of a compose project and the containers it runs.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProjectService {
+    pub name: String,
+    pub depends_on: Vec<ServiceDependency>,
+    pub containers: Vec<ProjectContainer>,
+}
+
+/// A compose project, put together from the labels of its containers.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ComposeProject {
+    pub name: String,
+
+    /// Directory the project was started from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+
+    pub config_files: Vec<String>,
+
+    /// Services sorted by name.
+    pub services: Vec<ProjectService>,
+
+    /// Number of running containers.
+    pub running: usize,
+
+    pub total: usize,
+}
+
+impl ComposeProject {
+    /// Group containers by the project they belong to. Containers without a project
+    /// label and one-off containers created by `docker compose run` are left out.
+    pub fn from_containers(containers: Vec<Container>) -> Vec<ComposeProject> {
+        let mut projects: BTreeMap<String, ComposeProject> = BTreeMap::new();
+
+        for container in containers {
+            let labels = container.labels.clone().unwrap_or_default();
+            let Some(project_name) = labels.get(COMPOSE_PROJECT_LABEL) else {
+                continue;
+            };
+            if labels
+                .get(COMPOSE_ONEOFF_LABEL)
+                .is_some_and(|oneoff| oneoff.eq_ignore_ascii_case("true"))
+            {
+                continue;
+            }
+
+            let project = projects
+                .entry(project_name.clone())
+                .or_insert_with(|| ComposeProject {
+                    name: project_name.clone(),
+                    working_dir: None,
+                    config_files: Vec::new(),
+                    services: Vec::new(),
+                    running: 0,
+                    total: 0,
+                });
+            if project.working_dir.is_none() {
+                project.working_dir = labels.get(COMPOSE_WORKING_DIR_LABEL).cloned();
+            }
+            if project.config_files.is_empty() {
+                project.config_files = labels
+                    .get(COMPOSE_CONFIG_FILES_LABEL)
+                    .map(|files| {
+                        files
+                            .split(',')
+                            .filter(|file| !file.is_empty())
+                            .map(str::to_string)
+                            .collect()
+                    })
+                    .unwrap_or_default();
+            }
+
+            let service_name = labels
+                .get(COMPOSE_SERVICE_LABEL)
+                .cloned()
+                .unwrap_or_default();
+            let index = match project
+                .services
+                .iter()
+                .position(|service| service.name == service_name)
+            {
+                Some(index) => index,
+                None => {
+                    project.services.push(ProjectService {
+                        name: service_name,
+                        depends_on: Vec::new(),
+                        containers: Vec::new(),
+                    });
+                    project.services.len() - 1
+                }
+            };
+            let service = &mut project.services[index];
+            if service.depends_on.is_empty() {
+                service.depends_on = labels
+                    .get(COMPOSE_DEPENDS_ON_LABEL)
+                    .map(|label| ServiceDependency::parse_label(label))
+                    .unwrap_or_default();
+            }
+
+            if container.state == Some(ContainerState::Running) {
+                project.running += 1;
+            }
+            project.total += 1;
+            service.containers.push(ProjectContainer {
+                id: container.id.clone().unwrap_or_default(),
+                name: container
+                    .names
+                    .as_ref()
+                    .and_then(|names| names.first())
+                    .map(|name| name.trim_start_matches('/').to_string())
+                    .unwrap_or_default(),
+                state: container.state,
+                status: container.status.clone(),
+                number: labels
+                    .get(COMPOSE_CONTAINER_NUMBER_LABEL)
+                    .and_then(|number| number.parse().ok()),
+            });
+        }
+
+        projects
+            .into_values()
+            .map(|mut project| {
+                project.services.sort_by(|a, b| a.name.cmp(&b.name));
+                for service in &mut project.services {
+                    service.containers.sort_by_key(|container| container.number);
+                }
+                project
+            })
+            .collect()
+    }
+
+    /// Services in the order they are started in, every service after the ones it
+    /// depends on. Dependencies on services without containers are ignored.
+    pub fn start_order(&self) -> Result<Vec<&ProjectService>, String> {
+        let nodes: Vec<(&str, Vec<&str>)> = self
+            .services
+            .iter()
+            .map(|service| {
+                let dependencies = service
+                    .depends_on
+                    .iter()
+                    .map(|dependency| dependency.service.as_str())
+                    .collect();
+                (service.name.as_str(), dependencies)
+            })
+            .collect();
+
+        let order = dependency_order(&nodes).map_err(|cycle| {
+            format!("Circular dependency between services: {}", cycle.join(", "))
+        })?;
+        Ok(order
+            .into_iter()
+            .filter_map(|name| self.services.iter().find(|service| service.name == name))
+            .collect())
+    }
+}
+
+/// Order `(name, dependencies)` nodes so every node comes after its dependencies.
+/// Ties are broken by name, and dependencies on unknown nodes are ignored. When
+/// there is a cycle, the sorted names of the nodes that could not be ordered are returned.
+pub(crate) fn dependency_order<'a>(
+    nodes: &[(&'a str, Vec<&'a str>)],
+) -> Result<Vec<&'a str>, Vec<&'a str>> {
+    let mut names: Vec<&str> = nodes.iter().map(|(name, _)| *name).collect();
+    names.sort();
+    let mut remaining: HashMap<&str, Vec<&str>> = nodes
+        .iter()
+        .map(|(name, dependencies)| {
+            let dependencies = dependencies
+                .iter()
+                .copied()
+                .filter(|dependency| names.contains(dependency))
+                .collect();
+            (*name, dependencies)
+        })
+        .collect();
+
+    let mut order = Vec::with_capacity(names.len());
+    while order.len() < names.len() {
+        let ready = names.iter().copied().find(|name| {
+            remaining
+                .get(name)
+                .is_some_and(|dependencies| dependencies.is_empty())
+        });
+        let Some(name) = ready else {
+            let mut cycle: Vec<&str> = remaining.keys().copied().collect();
+            cycle.sort();
+            return Err(cycle);
+        };
+
+        remaining.remove(name);
+        for dependencies in remaining.values_mut() {
+            dependencies.retain(|dependency| *dependency != name);
+        }
+        order.push(name);
+    }
+
+    Ok(order)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn container(id: &str, service: &str, depends_on: &str, state: ContainerState) -> Container {
+        let mut labels = HashMap::from([
+            (COMPOSE_PROJECT_LABEL.to_string(), "shop".to_string()),
+            (COMPOSE_SERVICE_LABEL.to_string(), service.to_string()),
+            (
+                COMPOSE_WORKING_DIR_LABEL.to_string(),
+                "/home/me/shop".to_string(),
+            ),
+            (
+                COMPOSE_CONFIG_FILES_LABEL.to_string(),
+                "/home/me/shop/compose.yml,/home/me/shop/compose.override.yml".to_string(),
+            ),
+            (COMPOSE_CONTAINER_NUMBER_LABEL.to_string(), "1".to_string()),
+        ]);
+        if !depends_on.is_empty() {
+            labels.insert(COMPOSE_DEPENDS_ON_LABEL.to_string(), depends_on.to_string());
+        }
+
+        Container {
+            id: Some(id.to_string()),
+            names: Some(vec![format!("/shop-{}-1", service)]),
+            labels: Some(labels),
+            state: Some(state),
+            ..Default::default()
+        }
+    }
+
+    fn project() -> ComposeProject {
+        let mut containers = vec![
+            container(
+                "a",
+                "web",
+                "api:service_started:false",
+                ContainerState::Running,
+            ),
+            container(
+                "b",
+                "api",
+                "db:service_healthy:true,cache:service_started:false",
+                ContainerState::Running,
+            ),
+            container("c", "db", "", ContainerState::Exited),
+        ];
+        let mut unrelated = container("d", "other", "", ContainerState::Running);
+        unrelated.labels = None;
+        containers.push(unrelated);
+
+        let mut projects = ComposeProject::from_containers(containers);
+        assert_eq!(projects.len(), 1);
+        projects.remove(0)
+    }
+
+    #[test]
+    fn test_projects_from_labels() {
+        let project = project();
+
+        assert_eq!(project.name, "shop");
+        assert_eq!(project.working_dir.as_deref(), Some("/home/me/shop"));
+        assert_eq!(project.config_files.len(), 2);
+        assert_eq!(project.running, 2);
+        assert_eq!(project.total, 3);
+        assert_eq!(project.services[0].name, "api");
+        assert_eq!(project.services[0].containers[0].name, "shop-api-1");
+        assert_eq!(
+            project.services[0].depends_on[0],
+            ServiceDependency {
+                service: "db".to_string(),
+                condition: "service_healthy".to_string(),
+                restart: true,
+            }
+        );
+    }
+
+    #[test]
+    fn test_start_order() {
+        let project = project();
+        let order: Vec<&str> = project
+            .start_order()
+            .unwrap()
+            .iter()
+            .map(|service| service.name.as_str())
+            .collect();
+
+        // `cache` has no containers and is ignored
+        assert_eq!(order, vec!["db", "api", "web"]);
+    }
+
+    #[test]
+    fn test_start_order_with_cycle() {
+        let mut project = project();
+        project.services[1].depends_on = ServiceDependency::parse_label("web");
+
+        let error = project.start_order().unwrap_err();
+        assert_eq!(error, "Circular dependency between services: api, db, web");
+    }
+}
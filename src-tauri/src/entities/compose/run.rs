@@ -0,0 +1,63 @@
+use serde::{Deserialize, Serialize};
+
+/// Options of `compose up`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComposeUpOptions {
+    /// Services to start with their dependencies. All services when empty.
+    pub services: Vec<String>,
+
+    /// Profiles whose services are started as well.
+    pub profiles: Vec<String>,
+
+    /// Recreate containers even if their configuration did not change.
+    pub force_recreate: bool,
+
+    /// Pull images even if they exist locally.
+    pub pull: bool,
+}
+
+/// Options of `compose down`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComposeDownOptions {
+    /// Also remove the named volumes declared by the project.
+    pub volumes: bool,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComposeProgressStatus {
+    Creating,
+    Created,
+    Pulling,
+    Pulled,
+    Waiting,
+    Starting,
+    Started,
+    Stopping,
+    Stopped,
+    Removing,
+    Removed,
+    Skipped,
+    Error,
+}
+
+/// Emitted for every step of `compose up` and `compose down`. `service` is not
+/// set for networks and volumes, which are named in `resource` instead.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComposeProgress {
+    pub operation_id: String,
+    pub project: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub service: Option<String>,
+
+    /// Container, network, volume or image the step is about.
+    pub resource: String,
+
+    pub status: ComposeProgressStatus,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
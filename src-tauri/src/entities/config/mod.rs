@@ -2,15 +2,17 @@ mod i18n;
 mod theme;
 mod v1;
 mod v2;
+mod v3;
 
 pub use i18n::*;
 pub use theme::*;
 pub use v1::*;
 pub use v2::*;
+pub use v3::*;
 
 use serde::{Deserialize, Serialize};
 
-pub type AppConfig = AppConfigV2;
+pub type AppConfig = AppConfigV3;
 
 // Versioned config enum for backward compatibility
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -20,4 +22,6 @@ pub enum VersionedAppConfig {
     V1(AppConfigV1),
     #[serde(rename = "2")]
     V2(AppConfigV2),
+    #[serde(rename = "3")]
+    V3(AppConfigV3),
 }
@@ -0,0 +1,109 @@
+use crate::entities::config::AppConfigV1;
+use crate::entities::config::AppConfigV2;
+use crate::entities::config::Language;
+use crate::entities::config::StartupSettings;
+use crate::entities::config::TelemetrySettings;
+use crate::entities::config::Theme;
+use crate::entities::config::VersionedAppConfig;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct ContainerSettings {
+    /// Seconds to wait for a container to exit after the stop signal before it is killed.
+    /// Used for containers without their own `StopTimeout`. The engine default of
+    /// 10 seconds applies when not set.
+    pub default_stop_timeout: Option<i64>,
+}
+
+impl ContainerSettings {
+    pub fn validate(&self) -> Result<(), String> {
+        match self.default_stop_timeout {
+            Some(timeout) if timeout < 0 => {
+                Err("Default stop timeout cannot be negative".to_string())
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default, PartialEq, Eq)]
+#[serde(default)]
+pub struct AppConfigV3 {
+    pub theme: Theme,
+    pub language: Language,
+    pub telemetry: TelemetrySettings,
+    pub startup: StartupSettings,
+    pub sidebar_collapsed: bool,
+    pub containers: ContainerSettings,
+}
+
+impl From<AppConfigV2> for AppConfigV3 {
+    fn from(v2: AppConfigV2) -> Self {
+        Self {
+            theme: v2.theme,
+            language: v2.language,
+            telemetry: v2.telemetry,
+            startup: v2.startup,
+            sidebar_collapsed: v2.sidebar_collapsed,
+            containers: ContainerSettings::default(),
+        }
+    }
+}
+
+impl From<AppConfigV1> for AppConfigV3 {
+    fn from(v1: AppConfigV1) -> Self {
+        AppConfigV2::from(v1).into()
+    }
+}
+
+impl From<AppConfigV3> for VersionedAppConfig {
+    fn from(v3: AppConfigV3) -> Self {
+        VersionedAppConfig::V3(v3)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_v2_to_v3_migration() {
+        let v2_config = AppConfigV2 {
+            theme: Theme::Dark,
+            language: Language::Russian,
+            telemetry: TelemetrySettings::default(),
+            startup: StartupSettings::default(),
+            sidebar_collapsed: true,
+        };
+
+        let v3_config: AppConfigV3 = v2_config.into();
+
+        assert_eq!(v3_config.theme, Theme::Dark);
+        assert_eq!(v3_config.language, Language::Russian);
+        assert!(v3_config.sidebar_collapsed);
+        assert_eq!(v3_config.containers.default_stop_timeout, None);
+    }
+
+    #[test]
+    fn test_v2_config_file_is_read_as_v2() {
+        let json = r#"{"version":"2","theme":"dark","sidebar_collapsed":true}"#;
+
+        let deserialized: VersionedAppConfig = serde_json::from_str(json).unwrap();
+        match deserialized {
+            VersionedAppConfig::V2(config) => assert!(config.sidebar_collapsed),
+            _ => panic!("Expected V2 config"),
+        }
+    }
+
+    #[test]
+    fn test_container_settings_validation() {
+        let mut settings = ContainerSettings {
+            default_stop_timeout: Some(30),
+        };
+        assert!(settings.validate().is_ok());
+
+        settings.default_stop_timeout = Some(-1);
+        assert!(settings.validate().is_err());
+    }
+}
@@ -0,0 +1,221 @@
+use crate::entities::containers::FileEntryType;
+use bollard::models::ChangeType;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ChangeKind {
+    Added,
+    Modified,
+    Deleted,
+}
+
+impl From<ChangeType> for ChangeKind {
+    fn from(change_type: ChangeType) -> Self {
+        match change_type {
+            ChangeType::_0 => ChangeKind::Modified,
+            ChangeType::_1 => ChangeKind::Added,
+            ChangeType::_2 => ChangeKind::Deleted,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangeCounts {
+    pub added: u64,
+    pub modified: u64,
+    pub deleted: u64,
+}
+
+impl ChangeCounts {
+    fn add(&mut self, kind: ChangeKind) {
+        match kind {
+            ChangeKind::Added => self.added += 1,
+            ChangeKind::Modified => self.modified += 1,
+            ChangeKind::Deleted => self.deleted += 1,
+        }
+    }
+
+    fn extend(&mut self, other: &ChangeCounts) {
+        self.added += other.added;
+        self.modified += other.modified;
+        self.deleted += other.deleted;
+    }
+}
+
+/// A path in the tree of changes. Directories that only lead to changed
+/// paths have no `kind` of their own.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ChangeNode {
+    pub name: String,
+
+    /// Absolute path inside the container.
+    pub path: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<ChangeKind>,
+
+    /// Type and size from the archive API, for paths that still exist.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entry_type: Option<FileEntryType>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+
+    /// Changes of this path and everything below it.
+    pub counts: ChangeCounts,
+
+    /// Sorted by name.
+    pub children: Vec<ChangeNode>,
+}
+
+impl ChangeNode {
+    /// Group the flat list of changes reported by the engine into a tree rooted at `/`.
+    pub fn tree(changes: impl IntoIterator<Item = (String, ChangeKind)>) -> Self {
+        let mut root = TreeBuilder::default();
+        for (path, kind) in changes {
+            let node = path
+                .split('/')
+                .filter(|component| !component.is_empty())
+                .fold(&mut root, |node, component| {
+                    node.children.entry(component.to_string()).or_default()
+                });
+            node.kind = Some(kind);
+        }
+
+        root.build("/".to_string(), "/".to_string())
+    }
+
+    pub fn find_mut(&mut self, path: &str) -> Option<&mut ChangeNode> {
+        path.split('/')
+            .filter(|component| !component.is_empty())
+            .try_fold(self, |node, component| {
+                node.children
+                    .iter_mut()
+                    .find(|child| child.name == component)
+            })
+    }
+
+    /// Paths of changed entries without changed children that still exist,
+    /// in tree order. These are the ones worth asking the archive API about.
+    pub fn existing_leaves(&self) -> Vec<String> {
+        let mut leaves = Vec::new();
+        self.collect_existing_leaves(&mut leaves);
+        leaves
+    }
+
+    fn collect_existing_leaves(&self, leaves: &mut Vec<String>) {
+        if self.children.is_empty() {
+            if matches!(self.kind, Some(ChangeKind::Added | ChangeKind::Modified)) {
+                leaves.push(self.path.clone());
+            }
+            return;
+        }
+        for child in &self.children {
+            child.collect_existing_leaves(leaves);
+        }
+    }
+}
+
+#[derive(Default)]
+struct TreeBuilder {
+    kind: Option<ChangeKind>,
+    children: BTreeMap<String, TreeBuilder>,
+}
+
+impl TreeBuilder {
+    fn build(self, name: String, path: String) -> ChangeNode {
+        let mut counts = ChangeCounts::default();
+        if let Some(kind) = self.kind {
+            counts.add(kind);
+        }
+
+        let children: Vec<ChangeNode> = self
+            .children
+            .into_iter()
+            .map(|(name, child)| {
+                let child_path = if path == "/" {
+                    format!("/{}", name)
+                } else {
+                    format!("{}/{}", path, name)
+                };
+                child.build(name, child_path)
+            })
+            .collect();
+        for child in &children {
+            counts.extend(&child.counts);
+        }
+
+        ChangeNode {
+            name,
+            path,
+            kind: self.kind,
+            entry_type: None,
+            size: None,
+            counts,
+            children,
+        }
+    }
+}
+
+/// Filesystem changes of a container relative to its image.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerChanges {
+    pub container_id: String,
+    pub root: ChangeNode,
+
+    /// Whether the archive API was asked about every changed path. Sizes are
+    /// only looked up for a limited number of paths.
+    pub sizes_complete: bool,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_tree() {
+        let root = ChangeNode::tree(vec![
+            ("/etc".to_string(), ChangeKind::Modified),
+            ("/etc/nginx/conf.d/app.conf".to_string(), ChangeKind::Added),
+            ("/etc/hosts".to_string(), ChangeKind::Modified),
+            ("/tmp/cache".to_string(), ChangeKind::Deleted),
+        ]);
+
+        assert_eq!(
+            root.counts,
+            ChangeCounts {
+                added: 1,
+                modified: 2,
+                deleted: 1
+            }
+        );
+        let names: Vec<&str> = root.children.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(names, ["etc", "tmp"]);
+
+        let etc = &root.children[0];
+        assert_eq!(etc.kind, Some(ChangeKind::Modified));
+        assert_eq!(etc.counts.added, 1);
+        assert_eq!(etc.children[0].path, "/etc/hosts");
+
+        let tmp = &root.children[1];
+        assert_eq!(tmp.kind, None);
+        assert_eq!(tmp.counts.deleted, 1);
+
+        assert_eq!(
+            root.existing_leaves(),
+            ["/etc/hosts", "/etc/nginx/conf.d/app.conf"]
+        );
+    }
+
+    #[test]
+    fn test_find_mut() {
+        let mut root = ChangeNode::tree(vec![("/var/log/app.log".to_string(), ChangeKind::Added)]);
+        root.find_mut("/var/log/app.log").unwrap().size = Some(42);
+
+        assert_eq!(root.children[0].children[0].children[0].size, Some(42));
+        assert!(root.find_mut("/var/lib").is_none());
+        assert_eq!(root.find_mut("/").unwrap().path, "/");
+    }
+}
@@ -0,0 +1,149 @@
+use crate::entities::images::{image_reference, validate_image_reference};
+use serde::{Deserialize, Serialize};
+
+/// Dockerfile instructions the engine accepts in the `changes` of a commit.
+const COMMIT_INSTRUCTIONS: [&str; 9] = [
+    "CMD",
+    "ENTRYPOINT",
+    "ENV",
+    "EXPOSE",
+    "LABEL",
+    "ONBUILD",
+    "USER",
+    "VOLUME",
+    "WORKDIR",
+];
+
+/// Options used to create an image from a container.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CommitContainerOptions {
+    /// Repository of the new image, e.g. `myapp` or `ghcr.io/org/myapp`.
+    /// The image is left untagged when not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repository: Option<String>,
+
+    /// Tag of the new image, `latest` when not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tag: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+
+    /// Pause the container while its filesystem is committed.
+    pub pause: bool,
+
+    /// Dockerfile instructions applied to the image config, e.g. `ENV DEBUG=1`,
+    /// `CMD ["nginx", "-g", "daemon off;"]` or `EXPOSE 8080`.
+    pub changes: Vec<String>,
+}
+
+impl Default for CommitContainerOptions {
+    fn default() -> Self {
+        CommitContainerOptions {
+            repository: None,
+            tag: None,
+            author: None,
+            message: None,
+            pause: true,
+            changes: Vec::new(),
+        }
+    }
+}
+
+impl CommitContainerOptions {
+    /// Validate the options before sending them to the engine.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors = validate_image_reference(self.repository.as_deref(), self.tag.as_deref());
+        errors.extend(validate_changes(&self.changes));
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+
+    /// `repository:tag` the image is created as, if any.
+    pub fn reference(&self) -> Option<String> {
+        image_reference(self.repository.as_deref(), self.tag.as_deref())
+    }
+}
+
+/// Check the instructions passed as `changes` when an image is committed or imported.
+pub(crate) fn validate_changes(changes: &[String]) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    for change in changes {
+        let instruction = change
+            .split_whitespace()
+            .next()
+            .unwrap_or_default()
+            .to_uppercase();
+        if !COMMIT_INSTRUCTIONS.contains(&instruction.as_str()) {
+            errors.push(format!(
+                "Unsupported change: {}. Only {} are allowed",
+                change,
+                COMMIT_INSTRUCTIONS.join(", ")
+            ));
+        } else if change.split_whitespace().nth(1).is_none() {
+            errors.push(format!("{} requires arguments", instruction));
+        }
+    }
+
+    errors
+}
+
+/// Image created by a commit.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CommitResult {
+    /// ID of the new image. May be missing for untagged images
+    /// when the engine does not report it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_id: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reference: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_commit_options_validation() {
+        let options = CommitContainerOptions {
+            repository: Some("localhost:5000/team/app".to_string()),
+            tag: Some("v1.2".to_string()),
+            changes: vec!["ENV DEBUG=1".to_string(), "expose 8080".to_string()],
+            ..Default::default()
+        };
+        assert!(options.validate().is_ok());
+        assert_eq!(
+            options.reference().as_deref(),
+            Some("localhost:5000/team/app:v1.2")
+        );
+
+        let options = CommitContainerOptions {
+            repository: Some("MyApp".to_string()),
+            tag: Some("-bad".to_string()),
+            changes: vec!["RUN apt-get update".to_string(), "CMD".to_string()],
+            ..Default::default()
+        };
+        let errors = options.validate().unwrap_err();
+        assert_eq!(errors.split("; ").count(), 4);
+    }
+
+    #[test]
+    fn test_tag_requires_repository() {
+        let options = CommitContainerOptions {
+            tag: Some("latest".to_string()),
+            ..Default::default()
+        };
+        assert!(options.validate().is_err());
+        assert!(CommitContainerOptions::default().validate().is_ok());
+    }
+}
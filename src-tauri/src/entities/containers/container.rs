@@ -70,6 +70,21 @@ impl From<String> for ContainerState {
     }
 }
 
+impl From<bollard::models::ContainerStateStatusEnum> for ContainerState {
+    fn from(status: bollard::models::ContainerStateStatusEnum) -> Self {
+        match status {
+            bollard::models::ContainerStateStatusEnum::EMPTY => ContainerState::Empty,
+            bollard::models::ContainerStateStatusEnum::CREATED => ContainerState::Created,
+            bollard::models::ContainerStateStatusEnum::RUNNING => ContainerState::Running,
+            bollard::models::ContainerStateStatusEnum::PAUSED => ContainerState::Paused,
+            bollard::models::ContainerStateStatusEnum::RESTARTING => ContainerState::Restarting,
+            bollard::models::ContainerStateStatusEnum::REMOVING => ContainerState::Removing,
+            bollard::models::ContainerStateStatusEnum::EXITED => ContainerState::Exited,
+            bollard::models::ContainerStateStatusEnum::DEAD => ContainerState::Dead,
+        }
+    }
+}
+
 // impl From<bollard::models::ContainerSummaryStateEnum> for ContainerState {
 //     fn from(state: bollard::models::ContainerSummaryStateEnum) -> Self {
 //         match state {
@@ -85,7 +100,7 @@ impl From<String> for ContainerState {
 //     }
 // }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Default, Serialize, Deserialize, Clone)]
 pub struct Container {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub id: Option<String>,
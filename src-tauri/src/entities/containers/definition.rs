@@ -0,0 +1,857 @@
+use crate::entities::containers::{ReplacementConfig, ResourceLimits};
+use bollard::models::{EndpointSettings, HostConfig, Mount, MountTypeEnum, RestartPolicyNameEnum};
+use serde::{Deserialize, Serialize};
+use serde_norway::{Mapping, Value};
+
+/// Network modes that replace the network stack instead of attaching to networks.
+const EXCLUSIVE_NETWORK_MODES: [&str; 2] = ["host", "none"];
+
+/// A `docker run` command and a compose service that create a container like an existing one.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerDefinition {
+    pub container_id: String,
+    pub name: String,
+
+    /// `docker run` command split over several lines, ready to paste into a shell.
+    pub run_command: String,
+
+    /// docker-compose.yml with the service and the networks and volumes it uses,
+    /// which are declared as external since they already exist.
+    pub compose_yaml: String,
+
+    /// Settings of the container that could not be expressed in one of the formats.
+    pub warnings: Vec<String>,
+}
+
+impl ContainerDefinition {
+    /// Render the definition of a container from the config it would be cloned with,
+    /// so settings that are image defaults or engine assigned are already left out.
+    pub(crate) fn from_config(
+        container_id: &str,
+        name: &str,
+        service_name: &str,
+        replacement: &ReplacementConfig,
+    ) -> Result<Self, String> {
+        let settings = Settings::new(replacement);
+        let mut warnings = settings.warnings();
+
+        let run_command = settings.run_command(name);
+        let compose_yaml = settings.compose_yaml(name, service_name, &mut warnings)?;
+
+        Ok(ContainerDefinition {
+            container_id: container_id.to_string(),
+            name: name.to_string(),
+            run_command,
+            compose_yaml,
+            warnings,
+        })
+    }
+}
+
+/// Settings of a container in the shape both formats need.
+struct Settings<'a> {
+    replacement: &'a ReplacementConfig,
+    host_config: HostConfig,
+    ports: Vec<String>,
+    exposed: Vec<String>,
+    env: Vec<(String, Option<String>)>,
+    labels: Vec<(&'a String, &'a String)>,
+    anonymous_volumes: Vec<&'a String>,
+    network_mode: Option<String>,
+    networks: Vec<(&'a str, Vec<String>)>,
+    restart: Option<String>,
+    resources: ResourceLimits,
+}
+
+impl<'a> Settings<'a> {
+    fn new(replacement: &'a ReplacementConfig) -> Self {
+        let config = &replacement.config;
+        let host_config = config.host_config.clone().unwrap_or_default();
+
+        let mut bindings: Vec<(&String, _)> = host_config.port_bindings.iter().flatten().collect();
+        bindings.sort_by_key(|(key, _)| port_sort_key(key));
+        let mut ports = Vec::new();
+        for (key, bindings) in &bindings {
+            for binding in bindings.iter().flatten() {
+                let port = format_port(
+                    key,
+                    binding.host_ip.as_deref(),
+                    binding.host_port.as_deref(),
+                );
+                if !ports.contains(&port) {
+                    ports.push(port);
+                }
+            }
+        }
+
+        let mut exposed: Vec<&String> = config
+            .exposed_ports
+            .iter()
+            .flatten()
+            .map(|(key, _)| key)
+            .filter(|key| !bindings.iter().any(|(bound, _)| bound == key))
+            .collect();
+        exposed.sort_by_key(|key| port_sort_key(key));
+
+        let env = config
+            .env
+            .iter()
+            .flatten()
+            .map(|var| match var.split_once('=') {
+                Some((key, value)) => (key.to_string(), Some(value.to_string())),
+                None => (var.clone(), None),
+            })
+            .collect();
+
+        let mut labels: Vec<_> = config.labels.iter().flatten().collect();
+        labels.sort();
+
+        let mut anonymous_volumes: Vec<&String> = config
+            .volumes
+            .iter()
+            .flatten()
+            .map(|(path, _)| path)
+            .collect();
+        anonymous_volumes.sort();
+
+        // Attached networks are in the networking config, anything else is a mode
+        let mode = host_config.network_mode.clone().unwrap_or_default();
+        let network_mode = (EXCLUSIVE_NETWORK_MODES.contains(&mode.as_str())
+            || mode.starts_with("container:"))
+        .then_some(mode);
+        let mut networks: Vec<(&str, Vec<String>)> = Vec::new();
+        if network_mode.is_none() {
+            let primary = config
+                .networking_config
+                .iter()
+                .flat_map(|networking| networking.endpoints_config.iter());
+            let endpoints = primary.chain(
+                replacement
+                    .extra_networks
+                    .iter()
+                    .map(|(name, endpoint)| (name, endpoint)),
+            );
+            networks = endpoints
+                .map(|(name, endpoint): (&String, &EndpointSettings)| {
+                    (name.as_str(), endpoint.aliases.clone().unwrap_or_default())
+                })
+                .collect();
+        }
+
+        let restart = host_config
+            .restart_policy
+            .as_ref()
+            .and_then(|policy| match policy.name? {
+                RestartPolicyNameEnum::EMPTY | RestartPolicyNameEnum::NO => None,
+                RestartPolicyNameEnum::ON_FAILURE => Some(match policy.maximum_retry_count {
+                    Some(count) if count > 0 => format!("on-failure:{}", count),
+                    _ => "on-failure".to_string(),
+                }),
+                name => Some(name.to_string()),
+            });
+
+        Settings {
+            replacement,
+            resources: ResourceLimits::from(&host_config),
+            host_config,
+            ports,
+            exposed: exposed.into_iter().map(|key| strip_tcp(key)).collect(),
+            env,
+            labels,
+            anonymous_volumes,
+            network_mode,
+            networks,
+            restart,
+        }
+    }
+
+    /// Settings neither format carries over.
+    fn warnings(&self) -> Vec<String> {
+        let config = &self.replacement.config;
+        let mut warnings = Vec::new();
+        if config.healthcheck.is_some() {
+            warnings.push("The healthcheck of the container is not included".to_string());
+        }
+        if self
+            .host_config
+            .devices
+            .as_ref()
+            .is_some_and(|d| !d.is_empty())
+        {
+            warnings.push("Device mappings are not included".to_string());
+        }
+        if let Some(driver) = self
+            .host_config
+            .log_config
+            .as_ref()
+            .and_then(|log_config| log_config.typ.as_deref())
+            .filter(|driver| !driver.is_empty() && *driver != "json-file")
+        {
+            warnings.push(format!("The {} log driver is not included", driver));
+        }
+        warnings
+    }
+
+    fn run_command(&self, name: &str) -> String {
+        let config = &self.replacement.config;
+        let host_config = &self.host_config;
+        let mut flags: Vec<String> = Vec::new();
+        let mut flag = |flag: &str, value: &str| flags.push(format!("{} {}", flag, quote(value)));
+
+        flag("--name", name);
+        if let Some(hostname) = &config.hostname {
+            flag("--hostname", hostname);
+        }
+        if let Some(domainname) = &config.domainname {
+            flag("--domainname", domainname);
+        }
+        if let Some(user) = &config.user {
+            flag("--user", user);
+        }
+        if let Some(working_dir) = &config.working_dir {
+            flag("--workdir", working_dir);
+        }
+        for (key, value) in &self.env {
+            match value {
+                Some(value) => flag("-e", &format!("{}={}", key, value)),
+                None => flag("-e", key),
+            }
+        }
+        for port in &self.ports {
+            flag("-p", port);
+        }
+        for port in &self.exposed {
+            flag("--expose", port);
+        }
+        for bind in host_config.binds.iter().flatten() {
+            flag("-v", bind);
+        }
+        for volume in &self.anonymous_volumes {
+            flag("-v", volume);
+        }
+        for mount in host_config.mounts.iter().flatten() {
+            flag("--mount", &format_mount(mount));
+        }
+        for (path, options) in sorted(host_config.tmpfs.as_ref()) {
+            match options.is_empty() {
+                true => flag("--tmpfs", path),
+                false => flag("--tmpfs", &format!("{}:{}", path, options)),
+            }
+        }
+
+        if let Some(mode) = &self.network_mode {
+            flag("--network", mode);
+        }
+        if let Some(((primary, aliases), extra)) = self.networks.split_first() {
+            if *primary != "bridge" || !extra.is_empty() {
+                flag("--network", primary);
+            }
+            for alias in aliases {
+                flag("--network-alias", alias);
+            }
+            // Additional networks take their aliases in the advanced syntax
+            for (network, aliases) in extra {
+                let mut value = format!("name={}", network);
+                for alias in aliases {
+                    value.push_str(&format!(",alias={}", alias));
+                }
+                flag("--network", &value);
+            }
+        }
+
+        if let Some(restart) = &self.restart {
+            flag("--restart", restart);
+        }
+        for (key, value) in &self.labels {
+            flag("--label", &format!("{}={}", key, value));
+        }
+
+        let resources = &self.resources;
+        if let Some(cpus) = resources.cpus {
+            flag("--cpus", &cpus.to_string());
+        }
+        if let Some(shares) = resources.cpu_shares {
+            flag("--cpu-shares", &shares.to_string());
+        }
+        if let Some(period) = resources.cpu_period {
+            flag("--cpu-period", &period.to_string());
+        }
+        if let Some(quota) = resources.cpu_quota {
+            flag("--cpu-quota", &quota.to_string());
+        }
+        if let Some(cpuset) = &resources.cpuset_cpus {
+            flag("--cpuset-cpus", cpuset);
+        }
+        if let Some(memory) = resources.memory {
+            flag("--memory", &format_bytes(memory));
+        }
+        if let Some(reservation) = resources.memory_reservation {
+            flag("--memory-reservation", &format_bytes(reservation));
+        }
+        if let Some(swap) = resources.memory_swap {
+            flag("--memory-swap", &format_bytes(swap));
+        }
+        if let Some(limit) = resources.pids_limit {
+            flag("--pids-limit", &limit.to_string());
+        }
+
+        for cap in host_config.cap_add.iter().flatten() {
+            flag("--cap-add", cap);
+        }
+        for cap in host_config.cap_drop.iter().flatten() {
+            flag("--cap-drop", cap);
+        }
+        for host in host_config.extra_hosts.iter().flatten() {
+            flag("--add-host", host);
+        }
+        for dns in host_config.dns.iter().flatten() {
+            flag("--dns", dns);
+        }
+        if let Some(signal) = &config.stop_signal {
+            flag("--stop-signal", signal);
+        }
+        if let Some(timeout) = config.stop_timeout {
+            flag("--stop-timeout", &timeout.to_string());
+        }
+
+        // Only the first word of the entrypoint can be given as a flag,
+        // the rest goes in front of the command
+        let mut args: Vec<String> = Vec::new();
+        if let Some(entrypoint) = &config.entrypoint {
+            let mut words = entrypoint.iter();
+            flag(
+                "--entrypoint",
+                words.next().map(String::as_str).unwrap_or(""),
+            );
+            args.extend(words.cloned());
+        }
+        args.extend(config.cmd.iter().flatten().cloned());
+
+        let mut switches = vec!["-d"];
+        if config.tty == Some(true) {
+            switches.push("-t");
+        }
+        if config.open_stdin == Some(true) {
+            switches.push("-i");
+        }
+        if host_config.auto_remove == Some(true) {
+            switches.push("--rm");
+        }
+        if host_config.privileged == Some(true) {
+            switches.push("--privileged");
+        }
+        if host_config.init == Some(true) {
+            switches.push("--init");
+        }
+        if host_config.readonly_rootfs == Some(true) {
+            switches.push("--read-only");
+        }
+        if host_config.publish_all_ports == Some(true) {
+            switches.push("-P");
+        }
+
+        let mut image = quote(config.image.as_deref().unwrap_or_default());
+        for arg in &args {
+            image.push(' ');
+            image.push_str(&quote(arg));
+        }
+
+        let mut lines = vec![format!("docker run {}", switches.join(" "))];
+        lines.extend(flags);
+        lines.push(image);
+        lines.join(" \\\n  ")
+    }
+
+    fn compose_yaml(
+        &self,
+        name: &str,
+        service_name: &str,
+        warnings: &mut Vec<String>,
+    ) -> Result<String, String> {
+        let config = &self.replacement.config;
+        let host_config = &self.host_config;
+        let mut service = Mapping::new();
+        let mut set = |key: &str, value: Value| {
+            service.insert(Value::from(key), value);
+        };
+        let strings = |values: &[String]| {
+            Value::Sequence(values.iter().map(|v| Value::from(v.as_str())).collect())
+        };
+
+        set(
+            "image",
+            Value::from(config.image.clone().unwrap_or_default()),
+        );
+        set("container_name", Value::from(name));
+        if let Some(hostname) = &config.hostname {
+            set("hostname", Value::from(hostname.as_str()));
+        }
+        if let Some(domainname) = &config.domainname {
+            set("domainname", Value::from(domainname.as_str()));
+        }
+        if let Some(user) = &config.user {
+            set("user", Value::from(user.as_str()));
+        }
+        if let Some(working_dir) = &config.working_dir {
+            set("working_dir", Value::from(working_dir.as_str()));
+        }
+        if let Some(entrypoint) = &config.entrypoint {
+            set("entrypoint", strings(entrypoint));
+        }
+        if let Some(cmd) = &config.cmd {
+            set("command", strings(cmd));
+        }
+        if !self.env.is_empty() {
+            let env = self
+                .env
+                .iter()
+                .map(|(key, value)| {
+                    let value = value.as_deref().map(Value::from).unwrap_or(Value::Null);
+                    (Value::from(key.as_str()), value)
+                })
+                .collect();
+            set("environment", Value::Mapping(env));
+        }
+        if !self.ports.is_empty() {
+            set("ports", strings(&self.ports));
+        }
+        if !self.exposed.is_empty() {
+            set("expose", strings(&self.exposed));
+        }
+
+        let mut named_volumes: Vec<String> = Vec::new();
+        let mut volumes: Vec<Value> = Vec::new();
+        for bind in host_config.binds.iter().flatten() {
+            if let Some(source) = bind.split(':').next().filter(|s| is_volume_name(s)) {
+                named_volumes.push(source.to_string());
+            }
+            volumes.push(Value::from(bind.as_str()));
+        }
+        volumes.extend(
+            self.anonymous_volumes
+                .iter()
+                .map(|v| Value::from(v.as_str())),
+        );
+        for mount in host_config.mounts.iter().flatten() {
+            if mount.typ == Some(MountTypeEnum::VOLUME) {
+                named_volumes.extend(mount.source.clone().filter(|s| !s.is_empty()));
+            }
+            volumes.push(compose_mount(mount));
+        }
+        if !volumes.is_empty() {
+            set("volumes", Value::Sequence(volumes));
+        }
+        let tmpfs: Vec<String> = sorted(host_config.tmpfs.as_ref())
+            .into_iter()
+            .map(|(path, options)| match options.is_empty() {
+                true => path.clone(),
+                false => format!("{}:{}", path, options),
+            })
+            .collect();
+        if !tmpfs.is_empty() {
+            set("tmpfs", strings(&tmpfs));
+        }
+
+        // The default bridge network cannot be listed next to other networks
+        let mut networks = Mapping::new();
+        if let Some(mode) = &self.network_mode {
+            set("network_mode", Value::from(mode.as_str()));
+        } else if let [("bridge", _)] = self.networks.as_slice() {
+            set("network_mode", Value::from("bridge"));
+        } else {
+            for (network, aliases) in &self.networks {
+                if *network == "bridge" {
+                    warnings.push(
+                        "The default bridge network is left out of the compose service".to_string(),
+                    );
+                    continue;
+                }
+                let mut endpoint = Mapping::new();
+                if !aliases.is_empty() {
+                    endpoint.insert(Value::from("aliases"), strings(aliases));
+                }
+                networks.insert(Value::from(*network), Value::Mapping(endpoint));
+            }
+            if !networks.is_empty() {
+                set("networks", Value::Mapping(networks.clone()));
+            }
+        }
+
+        if let Some(restart) = &self.restart {
+            set("restart", Value::from(restart.as_str()));
+        }
+        if !self.labels.is_empty() {
+            let labels = self
+                .labels
+                .iter()
+                .map(|(key, value)| (Value::from(key.as_str()), Value::from(value.as_str())))
+                .collect();
+            set("labels", Value::Mapping(labels));
+        }
+
+        let resources = &self.resources;
+        if let Some(cpus) = resources.cpus {
+            set("cpus", Value::from(cpus));
+        }
+        if let Some(shares) = resources.cpu_shares {
+            set("cpu_shares", Value::from(shares));
+        }
+        if let Some(period) = resources.cpu_period {
+            set("cpu_period", Value::from(period));
+        }
+        if let Some(quota) = resources.cpu_quota {
+            set("cpu_quota", Value::from(quota));
+        }
+        if let Some(cpuset) = &resources.cpuset_cpus {
+            set("cpuset", Value::from(cpuset.as_str()));
+        }
+        if let Some(memory) = resources.memory {
+            set("mem_limit", Value::from(format_bytes(memory)));
+        }
+        if let Some(reservation) = resources.memory_reservation {
+            set("mem_reservation", Value::from(format_bytes(reservation)));
+        }
+        if let Some(swap) = resources.memory_swap {
+            set("memswap_limit", Value::from(format_bytes(swap)));
+        }
+        if let Some(limit) = resources.pids_limit {
+            set("pids_limit", Value::from(limit));
+        }
+
+        if let Some(cap_add) = host_config.cap_add.as_ref().filter(|c| !c.is_empty()) {
+            set("cap_add", strings(cap_add));
+        }
+        if let Some(cap_drop) = host_config.cap_drop.as_ref().filter(|c| !c.is_empty()) {
+            set("cap_drop", strings(cap_drop));
+        }
+        if let Some(hosts) = host_config.extra_hosts.as_ref().filter(|h| !h.is_empty()) {
+            set("extra_hosts", strings(hosts));
+        }
+        if let Some(dns) = host_config.dns.as_ref().filter(|d| !d.is_empty()) {
+            set("dns", strings(dns));
+        }
+        if host_config.privileged == Some(true) {
+            set("privileged", Value::from(true));
+        }
+        if host_config.init == Some(true) {
+            set("init", Value::from(true));
+        }
+        if host_config.readonly_rootfs == Some(true) {
+            set("read_only", Value::from(true));
+        }
+        if config.tty == Some(true) {
+            set("tty", Value::from(true));
+        }
+        if config.open_stdin == Some(true) {
+            set("stdin_open", Value::from(true));
+        }
+        if let Some(signal) = &config.stop_signal {
+            set("stop_signal", Value::from(signal.as_str()));
+        }
+        if let Some(timeout) = config.stop_timeout {
+            set("stop_grace_period", Value::from(format!("{}s", timeout)));
+        }
+
+        if host_config.auto_remove == Some(true) {
+            warnings.push("Compose has no equivalent of --rm, it is left out".to_string());
+        }
+        if host_config.publish_all_ports == Some(true) {
+            warnings.push("Compose has no equivalent of -P, it is left out".to_string());
+        }
+
+        let mut file = Mapping::new();
+        let mut services = Mapping::new();
+        services.insert(Value::from(service_name), Value::Mapping(service));
+        file.insert(Value::from("services"), Value::Mapping(services));
+        if !networks.is_empty() {
+            file.insert(Value::from("networks"), external(networks.keys()));
+        }
+        named_volumes.sort();
+        named_volumes.dedup();
+        if !named_volumes.is_empty() {
+            let names: Vec<Value> = named_volumes.into_iter().map(Value::from).collect();
+            file.insert(Value::from("volumes"), external(names.iter()));
+        }
+
+        serde_norway::to_string(&file).map_err(|e| format!("Failed to render compose file: {}", e))
+    }
+}
+
+/// Declare each of `names` as an already existing resource.
+fn external<'v>(names: impl Iterator<Item = &'v Value>) -> Value {
+    let mut external = Mapping::new();
+    external.insert(Value::from("external"), Value::from(true));
+    Value::Mapping(
+        names
+            .map(|name| (name.clone(), Value::Mapping(external.clone())))
+            .collect(),
+    )
+}
+
+fn sorted(map: Option<&std::collections::HashMap<String, String>>) -> Vec<(&String, &String)> {
+    let mut entries: Vec<_> = map.into_iter().flatten().collect();
+    entries.sort();
+    entries
+}
+
+/// Order port keys like `8080/tcp` by number, then protocol.
+fn port_sort_key(key: &str) -> (u32, String) {
+    let (port, protocol) = key.split_once('/').unwrap_or((key, "tcp"));
+    let port = port.split('-').next().unwrap_or_default();
+    (port.parse().unwrap_or(u32::MAX), protocol.to_string())
+}
+
+/// `tcp` is the default protocol and is not written.
+fn strip_tcp(key: &str) -> String {
+    key.strip_suffix("/tcp").unwrap_or(key).to_string()
+}
+
+/// Write a port binding as `[ip:][host_port:]container_port[/protocol]`.
+fn format_port(key: &str, host_ip: Option<&str>, host_port: Option<&str>) -> String {
+    let container = strip_tcp(key);
+    let host_ip = host_ip.filter(|ip| !ip.is_empty() && *ip != "0.0.0.0" && *ip != "::");
+    let host_port = host_port.filter(|port| !port.is_empty());
+    let host_ip = host_ip.map(|ip| match ip.contains(':') {
+        true => format!("[{}]", ip),
+        false => ip.to_string(),
+    });
+
+    match (host_ip, host_port) {
+        (Some(ip), Some(port)) => format!("{}:{}:{}", ip, port, container),
+        (Some(ip), None) => format!("{}::{}", ip, container),
+        (None, Some(port)) => format!("{}:{}", port, container),
+        (None, None) => container,
+    }
+}
+
+/// Write a size in the largest unit it is a whole multiple of, e.g. `512m`.
+fn format_bytes(bytes: i64) -> String {
+    if bytes <= 0 {
+        return bytes.to_string();
+    }
+    for (unit, size) in [("g", 1i64 << 30), ("m", 1 << 20), ("k", 1 << 10)] {
+        if bytes % size == 0 {
+            return format!("{}{}", bytes / size, unit);
+        }
+    }
+    bytes.to_string()
+}
+
+fn mount_type(mount: &Mount) -> &'static str {
+    match mount.typ {
+        Some(MountTypeEnum::BIND) => "bind",
+        Some(MountTypeEnum::TMPFS) => "tmpfs",
+        Some(MountTypeEnum::NPIPE) => "npipe",
+        Some(MountTypeEnum::CLUSTER) => "cluster",
+        _ => "volume",
+    }
+}
+
+/// Write a mount in the syntax of `--mount`.
+fn format_mount(mount: &Mount) -> String {
+    let mut options = vec![format!("type={}", mount_type(mount))];
+    if let Some(source) = mount.source.as_ref().filter(|s| !s.is_empty()) {
+        options.push(format!("source={}", source));
+    }
+    options.push(format!(
+        "target={}",
+        mount.target.as_deref().unwrap_or_default()
+    ));
+    if mount.read_only == Some(true) {
+        options.push("readonly".to_string());
+    }
+    if let Some(size) = mount
+        .tmpfs_options
+        .as_ref()
+        .and_then(|tmpfs| tmpfs.size_bytes)
+    {
+        options.push(format!("tmpfs-size={}", size));
+    }
+    options.join(",")
+}
+
+/// Write a mount in the long volume syntax of compose.
+fn compose_mount(mount: &Mount) -> Value {
+    let mut entry = Mapping::new();
+    entry.insert(Value::from("type"), Value::from(mount_type(mount)));
+    if let Some(source) = mount.source.as_ref().filter(|s| !s.is_empty()) {
+        entry.insert(Value::from("source"), Value::from(source.as_str()));
+    }
+    entry.insert(
+        Value::from("target"),
+        Value::from(mount.target.clone().unwrap_or_default()),
+    );
+    if mount.read_only == Some(true) {
+        entry.insert(Value::from("read_only"), Value::from(true));
+    }
+    if let Some(size) = mount
+        .tmpfs_options
+        .as_ref()
+        .and_then(|tmpfs| tmpfs.size_bytes)
+    {
+        let mut tmpfs = Mapping::new();
+        tmpfs.insert(Value::from("size"), Value::from(size));
+        entry.insert(Value::from("tmpfs"), Value::Mapping(tmpfs));
+    }
+    Value::Mapping(entry)
+}
+
+/// Bind sources that are not paths are named volumes.
+fn is_volume_name(source: &str) -> bool {
+    let is_drive = source.len() >= 2 && source.as_bytes()[1] == b':';
+    !source.is_empty()
+        && !is_drive
+        && !source.starts_with(['/', '.', '~', '\\'])
+        && !source.contains('/')
+}
+
+/// Quote a word for a POSIX shell when it contains anything special.
+fn quote(word: &str) -> String {
+    let is_plain = |c: char| c.is_ascii_alphanumeric() || "_-./:=@%+,".contains(c);
+    if !word.is_empty() && word.chars().all(is_plain) {
+        return word.to_string();
+    }
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::containers::{ContainerPatch, ParsedRunCommand, ReplacementKind};
+    use bollard::models::{
+        ContainerConfig, ContainerInspectResponse, ImageConfig, NetworkSettings, PortBinding,
+        RestartPolicy,
+    };
+    use std::collections::HashMap;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn definition() -> ContainerDefinition {
+        let image = ImageConfig {
+            env: Some(strings(&["PATH=/usr/bin"])),
+            cmd: Some(strings(&["nginx", "-g", "daemon off;"])),
+            exposed_ports: Some(HashMap::from([("80/tcp".to_string(), HashMap::new())])),
+            ..Default::default()
+        };
+        let container = ContainerInspectResponse {
+            id: Some("0123456789abcdef".to_string()),
+            name: Some("/web".to_string()),
+            config: Some(ContainerConfig {
+                image: Some("nginx:1.27".to_string()),
+                env: Some(strings(&["PATH=/usr/bin", "GREETING=it's me"])),
+                cmd: Some(strings(&["nginx", "-g", "daemon off;"])),
+                exposed_ports: Some(HashMap::from([("80/tcp".to_string(), HashMap::new())])),
+                labels: Some(HashMap::from([(
+                    "com.docker.compose.service".to_string(),
+                    "web".to_string(),
+                )])),
+                ..Default::default()
+            }),
+            host_config: Some(HostConfig {
+                network_mode: Some("site".to_string()),
+                binds: Some(strings(&["www:/usr/share/nginx/html:ro"])),
+                port_bindings: Some(HashMap::from([(
+                    "80/tcp".to_string(),
+                    Some(vec![PortBinding {
+                        host_ip: Some("127.0.0.1".to_string()),
+                        host_port: Some("8080".to_string()),
+                    }]),
+                )])),
+                restart_policy: Some(RestartPolicy {
+                    name: Some(RestartPolicyNameEnum::UNLESS_STOPPED),
+                    maximum_retry_count: Some(0),
+                }),
+                memory: Some(512 << 20),
+                nano_cpus: Some(1_500_000_000),
+                ..Default::default()
+            }),
+            network_settings: Some(NetworkSettings {
+                networks: Some(HashMap::from([(
+                    "site".to_string(),
+                    EndpointSettings {
+                        aliases: Some(strings(&["frontend"])),
+                        ..Default::default()
+                    },
+                )])),
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let replacement = ReplacementConfig::from_inspect(
+            &container,
+            Some(&image),
+            ReplacementKind::Definition,
+            &ContainerPatch::default(),
+        );
+
+        ContainerDefinition::from_config("0123456789abcdef", "web", "web", &replacement).unwrap()
+    }
+
+    #[test]
+    fn test_run_command_parses_back() {
+        let definition = definition();
+        assert!(definition.run_command.contains("-e 'GREETING=it'\\''s me'"));
+        assert!(!definition.run_command.contains("PATH="));
+        assert!(!definition.run_command.contains("--label"));
+
+        let parsed =
+            ParsedRunCommand::parse(&definition.run_command, |_| Ok(String::new())).unwrap();
+        let spec = parsed.spec;
+        assert!(parsed.detach);
+        assert!(parsed.unsupported_flags.is_empty());
+        assert_eq!(spec.image, "nginx:1.27");
+        assert_eq!(spec.name.as_deref(), Some("web"));
+        assert_eq!(spec.command, None);
+        assert_eq!(spec.env[0].value, "it's me");
+        assert_eq!(spec.ports[0].host_port, Some(8080));
+        assert_eq!(spec.ports[0].host_ip.as_deref(), Some("127.0.0.1"));
+        assert_eq!(spec.mounts[0].source.as_deref(), Some("www"));
+        assert!(spec.mounts[0].read_only);
+        assert_eq!(spec.networks[0].name, "site");
+        assert_eq!(spec.networks[0].aliases, strings(&["frontend"]));
+        assert_eq!(spec.resources.memory, Some(512 << 20));
+        assert_eq!(spec.resources.cpus, Some(1.5));
+    }
+
+    #[test]
+    fn test_compose_service() {
+        let definition = definition();
+        let file: Value = serde_norway::from_str(&definition.compose_yaml).unwrap();
+        let service = &file["services"]["web"];
+
+        assert_eq!(service["image"], Value::from("nginx:1.27"));
+        assert_eq!(service["environment"]["GREETING"], Value::from("it's me"));
+        assert_eq!(service["ports"][0], Value::from("127.0.0.1:8080:80"));
+        assert_eq!(
+            service["volumes"][0],
+            Value::from("www:/usr/share/nginx/html:ro")
+        );
+        assert_eq!(
+            service["networks"]["site"]["aliases"][0],
+            Value::from("frontend")
+        );
+        assert_eq!(service["restart"], Value::from("unless-stopped"));
+        assert_eq!(service["mem_limit"], Value::from("512m"));
+        assert!(service.get("command").is_none());
+        assert!(service.get("labels").is_none());
+        assert_eq!(file["networks"]["site"]["external"], Value::from(true));
+        assert_eq!(file["volumes"]["www"]["external"], Value::from(true));
+        assert!(definition.warnings.is_empty());
+    }
+
+    #[test]
+    fn test_format_helpers() {
+        assert_eq!(format_port("53/udp", Some("::1"), None), "[::1]::53/udp");
+        assert_eq!(format_port("80/tcp", Some("0.0.0.0"), Some("")), "80");
+        assert_eq!(format_bytes(1 << 30), "1g");
+        assert_eq!(format_bytes(1500), "1500");
+        assert_eq!(format_bytes(-1), "-1");
+        assert_eq!(quote("daemon off;"), "'daemon off;'");
+        assert_eq!(quote(""), "''");
+        assert!(is_volume_name("cache"));
+        assert!(!is_volume_name("./data"));
+        assert!(!is_volume_name("C:\\data"));
+    }
+}
@@ -0,0 +1,570 @@
+use crate::entities::containers::{
+    ContainerState, EndpointSettings, MountPoint, PortBindingSpec, PortTypeEnum, ResourceLimits,
+    RestartPolicy,
+};
+use bollard::models::{ContainerInspectResponse, PortMap};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Placeholder shown instead of the value of a sensitive environment variable.
+pub const MASKED_VALUE: &str = "********";
+
+/// Parts of variable names that usually hold credentials.
+const SENSITIVE_ENV_MARKERS: [&str; 9] = [
+    "PASSWORD",
+    "PASSWD",
+    "PASS",
+    "SECRET",
+    "TOKEN",
+    "CREDENTIAL",
+    "PRIVATE",
+    "APIKEY",
+    "AUTH",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum HealthStatus {
+    #[serde(rename = "")]
+    Empty,
+
+    #[serde(rename = "none")]
+    None,
+
+    #[serde(rename = "starting")]
+    Starting,
+
+    #[serde(rename = "healthy")]
+    Healthy,
+
+    #[serde(rename = "unhealthy")]
+    Unhealthy,
+}
+
+impl From<bollard::models::HealthStatusEnum> for HealthStatus {
+    fn from(status: bollard::models::HealthStatusEnum) -> Self {
+        match status {
+            bollard::models::HealthStatusEnum::EMPTY => HealthStatus::Empty,
+            bollard::models::HealthStatusEnum::NONE => HealthStatus::None,
+            bollard::models::HealthStatusEnum::STARTING => HealthStatus::Starting,
+            bollard::models::HealthStatusEnum::HEALTHY => HealthStatus::Healthy,
+            bollard::models::HealthStatusEnum::UNHEALTHY => HealthStatus::Unhealthy,
+        }
+    }
+}
+
+/// Result of a single healthcheck run.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HealthcheckResult {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub end: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output: Option<String>,
+}
+
+impl From<bollard::models::HealthcheckResult> for HealthcheckResult {
+    fn from(result: bollard::models::HealthcheckResult) -> Self {
+        HealthcheckResult {
+            start: result.start,
+            end: result.end,
+            exit_code: result.exit_code,
+            output: result.output,
+        }
+    }
+}
+
+/// Health of a container that has a healthcheck.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Health {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<HealthStatus>,
+
+    /// Number of consecutive failures.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub failing_streak: Option<i64>,
+
+    /// The last few healthcheck results, oldest first.
+    pub log: Vec<HealthcheckResult>,
+}
+
+impl From<bollard::models::Health> for Health {
+    fn from(health: bollard::models::Health) -> Self {
+        Health {
+            status: health.status.map(|status| status.into()),
+            failing_streak: health.failing_streak,
+            log: health
+                .log
+                .unwrap_or_default()
+                .into_iter()
+                .map(|result| result.into())
+                .collect(),
+        }
+    }
+}
+
+/// Runtime state of the container.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContainerStateDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<ContainerState>,
+
+    pub running: bool,
+    pub paused: bool,
+    pub restarting: bool,
+
+    /// Whether the kernel killed a process of the container because it ran out of memory.
+    pub oom_killed: bool,
+    pub dead: bool,
+
+    /// Process ID of the main process, `0` when the container is not running.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<i64>,
+
+    /// Exit code of the last run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub health: Option<Health>,
+}
+
+impl From<bollard::models::ContainerState> for ContainerStateDetails {
+    fn from(state: bollard::models::ContainerState) -> Self {
+        ContainerStateDetails {
+            status: state.status.map(|status| status.into()),
+            running: state.running.unwrap_or(false),
+            paused: state.paused.unwrap_or(false),
+            restarting: state.restarting.unwrap_or(false),
+            oom_killed: state.oom_killed.unwrap_or(false),
+            dead: state.dead.unwrap_or(false),
+            pid: state.pid,
+            exit_code: state.exit_code,
+            error: state.error.filter(|error| !error.is_empty()),
+            started_at: state.started_at,
+            finished_at: state.finished_at,
+            health: state.health.map(|health| health.into()),
+        }
+    }
+}
+
+/// Healthcheck configuration. Durations are in nanoseconds.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct HealthcheckConfig {
+    /// `["NONE"]`, `["CMD", args...]` or `["CMD-SHELL", command]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub test: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub timeout: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub retries: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_period: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_interval: Option<i64>,
+}
+
+impl From<bollard::models::HealthConfig> for HealthcheckConfig {
+    fn from(health_config: bollard::models::HealthConfig) -> Self {
+        HealthcheckConfig {
+            test: health_config.test,
+            interval: health_config.interval,
+            timeout: health_config.timeout,
+            retries: health_config.retries,
+            start_period: health_config.start_period,
+            start_interval: health_config.start_interval,
+        }
+    }
+}
+
+/// An environment variable of an inspected container.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ContainerEnvVar {
+    pub key: String,
+    pub value: String,
+
+    /// Whether `value` was replaced because the variable looks like it holds a secret.
+    pub masked: bool,
+}
+
+impl ContainerEnvVar {
+    /// Parse a `KEY=value` entry as returned by the engine.
+    pub fn parse(entry: &str) -> Self {
+        let (key, value) = entry.split_once('=').unwrap_or((entry, ""));
+        ContainerEnvVar {
+            key: key.to_string(),
+            value: value.to_string(),
+            masked: false,
+        }
+    }
+
+    /// Whether the variable probably holds a password, token or key,
+    /// either by its name or because it is a URL with credentials.
+    pub fn is_sensitive(&self) -> bool {
+        let key = self.key.to_uppercase();
+        let sensitive_key = SENSITIVE_ENV_MARKERS
+            .iter()
+            .any(|marker| key.contains(marker))
+            || key == "KEY"
+            || key.ends_with("_KEY");
+
+        let credentials_in_url = self
+            .value
+            .split_once("://")
+            .and_then(|(_, rest)| rest.split('/').next())
+            .is_some_and(|authority| {
+                authority
+                    .rsplit_once('@')
+                    .is_some_and(|(user_info, _)| user_info.contains(':'))
+            });
+
+        sensitive_key || credentials_in_url
+    }
+}
+
+/// Configuration the container was created with.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContainerConfigDetails {
+    /// Image reference the container was created from, e.g. `nginx:1.27`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub domainname: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+
+    pub env: Vec<ContainerEnvVar>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cmd: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entrypoint: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+
+    /// Exposed ports in the `80/tcp` form.
+    pub exposed_ports: Vec<String>,
+
+    /// Anonymous volumes declared by the image or at creation.
+    pub volumes: Vec<String>,
+
+    pub labels: HashMap<String, String>,
+    pub tty: bool,
+    pub open_stdin: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_signal: Option<String>,
+
+    /// Seconds to wait for the container to stop before it is killed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_timeout: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub healthcheck: Option<HealthcheckConfig>,
+}
+
+impl From<bollard::models::ContainerConfig> for ContainerConfigDetails {
+    fn from(config: bollard::models::ContainerConfig) -> Self {
+        let mut exposed_ports: Vec<String> = config
+            .exposed_ports
+            .unwrap_or_default()
+            .into_keys()
+            .collect();
+        exposed_ports.sort();
+        let mut volumes: Vec<String> = config.volumes.unwrap_or_default().into_keys().collect();
+        volumes.sort();
+
+        ContainerConfigDetails {
+            image: config.image,
+            hostname: config.hostname,
+            domainname: config.domainname.filter(|domain| !domain.is_empty()),
+            user: config.user.filter(|user| !user.is_empty()),
+            env: config
+                .env
+                .unwrap_or_default()
+                .iter()
+                .map(|entry| ContainerEnvVar::parse(entry))
+                .collect(),
+            cmd: config.cmd,
+            entrypoint: config.entrypoint,
+            working_dir: config.working_dir.filter(|dir| !dir.is_empty()),
+            exposed_ports,
+            volumes,
+            labels: config.labels.unwrap_or_default(),
+            tty: config.tty.unwrap_or(false),
+            open_stdin: config.open_stdin.unwrap_or(false),
+            stop_signal: config.stop_signal,
+            stop_timeout: config.stop_timeout,
+            healthcheck: config.healthcheck.map(|healthcheck| healthcheck.into()),
+        }
+    }
+}
+
+/// Logging driver and its options.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct LogConfig {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub driver: Option<String>,
+
+    pub options: HashMap<String, String>,
+}
+
+impl From<bollard::models::HostConfigLogConfig> for LogConfig {
+    fn from(log_config: bollard::models::HostConfigLogConfig) -> Self {
+        LogConfig {
+            driver: log_config.typ,
+            options: log_config.config.unwrap_or_default(),
+        }
+    }
+}
+
+/// Host specific settings of the container.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ContainerHostConfigDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart_policy: Option<RestartPolicy>,
+
+    pub resources: ResourceLimits,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub log_config: Option<LogConfig>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub network_mode: Option<String>,
+
+    pub port_bindings: Vec<PortBindingSpec>,
+
+    /// Bind mounts in the `source:target[:options]` form.
+    pub binds: Vec<String>,
+
+    pub privileged: bool,
+    pub readonly_rootfs: bool,
+    pub auto_remove: bool,
+    pub init: bool,
+    pub cap_add: Vec<String>,
+    pub cap_drop: Vec<String>,
+    pub dns: Vec<String>,
+    pub extra_hosts: Vec<String>,
+
+    /// Size of `/dev/shm` in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub shm_size: Option<i64>,
+}
+
+impl From<bollard::models::HostConfig> for ContainerHostConfigDetails {
+    fn from(host_config: bollard::models::HostConfig) -> Self {
+        ContainerHostConfigDetails {
+            resources: ResourceLimits::from(&host_config),
+            restart_policy: host_config.restart_policy.map(|policy| policy.into()),
+            log_config: host_config.log_config.map(|log_config| log_config.into()),
+            network_mode: host_config.network_mode,
+            port_bindings: port_bindings_from_map(host_config.port_bindings),
+            binds: host_config.binds.unwrap_or_default(),
+            privileged: host_config.privileged.unwrap_or(false),
+            readonly_rootfs: host_config.readonly_rootfs.unwrap_or(false),
+            auto_remove: host_config.auto_remove.unwrap_or(false),
+            init: host_config.init.unwrap_or(false),
+            cap_add: host_config.cap_add.unwrap_or_default(),
+            cap_drop: host_config.cap_drop.unwrap_or_default(),
+            dns: host_config.dns.unwrap_or_default(),
+            extra_hosts: host_config.extra_hosts.unwrap_or_default(),
+            shm_size: host_config.shm_size,
+        }
+    }
+}
+
+/// Flatten a port map into one entry per host binding. Ports that are
+/// exposed but not published are skipped.
+pub fn port_bindings_from_map(port_map: Option<PortMap>) -> Vec<PortBindingSpec> {
+    let mut bindings = Vec::new();
+
+    for (port_key, host_bindings) in port_map.unwrap_or_default() {
+        let (port, protocol) = port_key.split_once('/').unwrap_or((&port_key, "tcp"));
+        let Ok(container_port) = port.parse::<u16>() else {
+            continue;
+        };
+        let protocol = match protocol {
+            "udp" => PortTypeEnum::Udp,
+            "sctp" => PortTypeEnum::Sctp,
+            _ => PortTypeEnum::Tcp,
+        };
+
+        for binding in host_bindings.unwrap_or_default() {
+            bindings.push(PortBindingSpec {
+                container_port,
+                protocol: Some(protocol),
+                host_ip: binding.host_ip.filter(|ip| !ip.is_empty()),
+                host_port: binding.host_port.and_then(|port| port.parse().ok()),
+            });
+        }
+    }
+
+    bindings.sort_by_key(|binding| (binding.container_port, binding.host_port));
+    bindings
+}
+
+/// Everything the engine reports about a container.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ContainerDetails {
+    pub id: String,
+
+    /// Container name without the leading slash.
+    pub name: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+
+    /// ID of the image the container was created from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_id: Option<String>,
+
+    /// Path of the command run by the container.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+
+    pub args: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub platform: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub driver: Option<String>,
+
+    pub restart_count: i64,
+    pub state: ContainerStateDetails,
+    pub config: ContainerConfigDetails,
+    pub host_config: ContainerHostConfigDetails,
+
+    /// Settings of each network the container is attached to.
+    pub networks: HashMap<String, EndpointSettings>,
+
+    pub mounts: Vec<MountPoint>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_rw: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size_root_fs: Option<i64>,
+}
+
+impl From<ContainerInspectResponse> for ContainerDetails {
+    fn from(container: ContainerInspectResponse) -> Self {
+        ContainerDetails {
+            id: container.id.unwrap_or_default(),
+            name: container
+                .name
+                .map(|name| name.trim_start_matches('/').to_string())
+                .unwrap_or_default(),
+            created: container.created,
+            image_id: container.image,
+            path: container.path,
+            args: container.args.unwrap_or_default(),
+            platform: container.platform,
+            driver: container.driver,
+            restart_count: container.restart_count.unwrap_or(0),
+            state: container
+                .state
+                .map(|state| state.into())
+                .unwrap_or_default(),
+            config: container
+                .config
+                .map(|config| config.into())
+                .unwrap_or_default(),
+            host_config: container
+                .host_config
+                .map(|host_config| host_config.into())
+                .unwrap_or_default(),
+            networks: container
+                .network_settings
+                .and_then(|network_settings| network_settings.networks)
+                .unwrap_or_default()
+                .into_iter()
+                .map(|(name, endpoint)| (name, endpoint.into()))
+                .collect(),
+            mounts: container
+                .mounts
+                .unwrap_or_default()
+                .into_iter()
+                .map(|mount| mount.into())
+                .collect(),
+            size_rw: container.size_rw,
+            size_root_fs: container.size_root_fs,
+        }
+    }
+}
+
+impl ContainerDetails {
+    /// Replace the values of environment variables that look like secrets.
+    pub fn mask_sensitive_env(&mut self) {
+        for var in self.config.env.iter_mut() {
+            if var.is_sensitive() {
+                var.value = MASKED_VALUE.to_string();
+                var.masked = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sensitive_env_detection() {
+        let sensitive = [
+            "POSTGRES_PASSWORD=postgres",
+            "GITHUB_TOKEN=ghp_123",
+            "AWS_SECRET_ACCESS_KEY=abc",
+            "STRIPE_API_KEY=sk_live",
+            "DATABASE_URL=postgres://app:hunter2@db:5432/app",
+        ];
+        for entry in sensitive {
+            assert!(ContainerEnvVar::parse(entry).is_sensitive(), "{}", entry);
+        }
+
+        let not_sensitive = [
+            "PATH=/usr/local/bin:/usr/bin",
+            "KEYCLOAK_URL=http://keycloak:8080",
+            "REDIS_URL=redis://redis:6379/0",
+            "NGINX_VERSION=1.27.0",
+        ];
+        for entry in not_sensitive {
+            assert!(!ContainerEnvVar::parse(entry).is_sensitive(), "{}", entry);
+        }
+    }
+
+    #[test]
+    fn test_env_parse_keeps_equal_signs_in_value() {
+        let var = ContainerEnvVar::parse("JAVA_OPTS=-Dfoo=bar");
+        assert_eq!(var.key, "JAVA_OPTS");
+        assert_eq!(var.value, "-Dfoo=bar");
+    }
+}
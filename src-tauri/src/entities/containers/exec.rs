@@ -0,0 +1,47 @@
+use serde::{Deserialize, Serialize};
+
+/// Options used when opening an interactive exec session.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecSessionOptions {
+    /// Command to run. Defaults to `bash`, falling back to `sh` when bash is not installed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<Vec<String>>,
+
+    /// User (and optionally group) to run the command as, e.g. `root` or `1000:1000`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+
+    /// Additional environment variables in the `KEY=value` form.
+    pub env: Vec<String>,
+
+    /// Initial terminal width in columns.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cols: Option<u16>,
+
+    /// Initial terminal height in rows.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rows: Option<u16>,
+}
+
+/// Chunk of terminal output emitted to the frontend.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecOutput {
+    pub session_id: String,
+    pub data: String,
+}
+
+/// Emitted once the process of an exec session exits or its stream fails.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecSessionClosed {
+    pub session_id: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub exit_code: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
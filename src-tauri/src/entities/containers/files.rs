@@ -0,0 +1,117 @@
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FileEntryType {
+    File,
+    Directory,
+    Symlink,
+    Hardlink,
+    Other,
+}
+
+impl From<tar::EntryType> for FileEntryType {
+    fn from(entry_type: tar::EntryType) -> Self {
+        match entry_type {
+            tar::EntryType::Regular | tar::EntryType::Continuous => FileEntryType::File,
+            tar::EntryType::Directory => FileEntryType::Directory,
+            tar::EntryType::Symlink => FileEntryType::Symlink,
+            tar::EntryType::Link => FileEntryType::Hardlink,
+            _ => FileEntryType::Other,
+        }
+    }
+}
+
+/// A file or directory inside a container, as reported by the archive API or `stat`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FileEntry {
+    pub name: String,
+    /// Absolute path inside the container.
+    pub path: String,
+    pub entry_type: FileEntryType,
+    pub size: u64,
+    /// Permission bits, e.g. `0o755`.
+    pub mode: u32,
+    /// Modification time as a unix timestamp.
+    pub mtime: u64,
+    pub uid: u64,
+    pub gid: u64,
+
+    /// Target of a symlink or hardlink.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub link_target: Option<String>,
+}
+
+/// Beginning of a file inside a container, for display in the file browser.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FilePreview {
+    pub path: String,
+    /// Full size of the file, which may be larger than the previewed content.
+    pub size: u64,
+    /// Text content, empty for binary files.
+    pub content: String,
+    pub truncated: bool,
+    pub binary: bool,
+}
+
+/// Options shared by `copy_to_container` and `copy_from_container`.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CopyOptions {
+    /// Replace the destination if it already exists. Copies fail otherwise.
+    pub overwrite: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transfer_id: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CopyDirection {
+    ToContainer,
+    FromContainer,
+}
+
+/// Emitted while a copy is running and once more with `done` set when it ends.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CopyProgress {
+    pub transfer_id: String,
+    pub container_id: String,
+    pub direction: CopyDirection,
+    pub bytes_transferred: u64,
+
+    /// Size of the whole transfer when it is known up front.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total_bytes: Option<u64>,
+
+    pub done: bool,
+}
+
+impl CopyProgress {
+    pub fn new(
+        transfer_id: &str,
+        container_id: &str,
+        direction: CopyDirection,
+        total_bytes: Option<u64>,
+    ) -> Self {
+        CopyProgress {
+            transfer_id: transfer_id.to_string(),
+            container_id: container_id.to_string(),
+            direction,
+            bytes_transferred: 0,
+            total_bytes,
+            done: false,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CopyResult {
+    pub transfer_id: String,
+    pub bytes_transferred: u64,
+
+    /// Whether file owners were kept. Copying to the host as a regular user
+    /// cannot hand files over to other users, so they end up owned by the caller.
+    /// Copies into a container are always owned by its root.
+    pub ownership_preserved: bool,
+}
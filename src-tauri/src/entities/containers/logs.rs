@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+/// Output stream a log line was written to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LogStream {
+    #[serde(rename = "stdout")]
+    Stdout,
+
+    #[serde(rename = "stderr")]
+    Stderr,
+
+    /// Output of containers started with a TTY, where stdout and stderr are merged by the engine.
+    #[serde(rename = "console")]
+    Console,
+}
+
+/// A single log line together with the stream it came from.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct LogLine {
+    pub stream: LogStream,
+    pub message: String,
+}
+
+/// Options used when opening a log subscription.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LogSubscriptionOptions {
+    /// Only return this number of lines from the end of the logs. The whole history is returned if not set.
+    pub tail: Option<u64>,
+
+    /// Only return logs since this time, as a UNIX timestamp.
+    pub since: Option<i64>,
+
+    /// Only return logs before this time, as a UNIX timestamp.
+    pub until: Option<i64>,
+
+    /// Prefix every log line with its RFC3339 timestamp.
+    pub timestamps: bool,
+}
+
+/// Batch of log lines emitted to the frontend for a subscription.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogBatch {
+    pub subscription_id: String,
+    pub container_id: String,
+    pub lines: Vec<LogLine>,
+}
+
+/// Emitted once a log subscription ends, either because the container stopped or the stream failed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LogSubscriptionClosed {
+    pub subscription_id: String,
+    pub container_id: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
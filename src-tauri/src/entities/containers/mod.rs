@@ -1,9 +1,35 @@
+mod changes;
+mod commit;
 mod container;
+mod definition;
+mod details;
+mod exec;
+mod files;
+mod logs;
 mod mount;
 mod network;
 mod port;
+mod processes;
+mod recreate;
+mod run_command;
+mod spec;
+mod stats;
+mod update;
 
+pub use changes::*;
+pub use commit::*;
 pub use container::*;
+pub use definition::*;
+pub use details::*;
+pub use exec::*;
+pub use files::*;
+pub use logs::*;
 pub use mount::*;
 pub use network::*;
 pub use port::*;
+pub use processes::*;
+pub use recreate::*;
+pub use run_command::*;
+pub use spec::*;
+pub use stats::*;
+pub use update::*;
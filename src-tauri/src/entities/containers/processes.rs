@@ -0,0 +1,188 @@
+use serde::{Deserialize, Serialize};
+
+/// Arguments passed to `ps` when the caller does not ask for anything else.
+/// Start times are printed without spaces since the engine splits columns on whitespace.
+pub const DEFAULT_PS_ARGS: &str = "-eo pid,ppid,user,pcpu,pmem,rss,stime,args";
+
+/// Options used when listing the processes of a container.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessListOptions {
+    /// Arguments for `ps`, e.g. `aux` or `-ef`. The output must have a PID column.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ps_args: Option<String>,
+}
+
+/// Options used when opening a process list subscription.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ProcessSubscriptionOptions {
+    pub container_id: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ps_args: Option<String>,
+
+    /// How often the process list is refreshed, in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ProcessSource {
+    /// Output of `ps`, run by the engine.
+    Ps,
+
+    /// Read from `/proc` inside the container.
+    Proc,
+}
+
+/// A process running in a container. Typed fields are only set when the
+/// matching column is part of the output.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContainerProcess {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pid: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ppid: Option<u32>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_percent: Option<f64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_percent: Option<f64>,
+
+    /// Resident memory in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rss: Option<u64>,
+
+    /// Start time as printed by `ps`, e.g. `14:02` or `Oct16`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub start_time: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+
+    /// Raw values, in the order of the list titles.
+    pub values: Vec<String>,
+}
+
+impl ContainerProcess {
+    /// Map a row of `ps` output to a process using the column titles.
+    pub fn from_row(titles: &[String], values: Vec<String>) -> Self {
+        let column = |names: &[&str]| {
+            titles
+                .iter()
+                .position(|title| names.contains(&title.as_str()))
+                .and_then(|index| values.get(index))
+                .map(|value| value.trim())
+                .filter(|value| !value.is_empty() && *value != "-")
+        };
+
+        ContainerProcess {
+            pid: column(&["PID"]).and_then(|value| value.parse().ok()),
+            ppid: column(&["PPID"]).and_then(|value| value.parse().ok()),
+            user: column(&["USER", "UID", "EUSER", "RUSER", "UNAME"]).map(str::to_string),
+            cpu_percent: column(&["%CPU", "C"]).and_then(|value| value.parse().ok()),
+            memory_percent: column(&["%MEM"]).and_then(|value| value.parse().ok()),
+            rss: column(&["RSS", "RSZ"])
+                .and_then(|value| value.parse::<u64>().ok())
+                .map(|kib| kib * 1024),
+            start_time: column(&["STIME", "START", "STARTED"]).map(str::to_string),
+            command: column(&["COMMAND", "CMD", "ARGS"]).map(str::to_string),
+            values,
+        }
+    }
+}
+
+/// Processes of a container at one point in time.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessList {
+    pub container_id: String,
+    pub source: ProcessSource,
+    pub titles: Vec<String>,
+    pub processes: Vec<ContainerProcess>,
+}
+
+/// Emitted every time the process list of a subscription is refreshed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessListUpdate {
+    pub subscription_id: String,
+    pub list: ProcessList,
+}
+
+/// Emitted when a process list subscription stops because the container
+/// stopped or the list could not be read.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessSubscriptionEnded {
+    pub subscription_id: String,
+    pub container_id: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    #[test]
+    fn test_process_from_default_columns() {
+        let titles = strings(&[
+            "PID", "PPID", "USER", "%CPU", "%MEM", "RSS", "STIME", "COMMAND",
+        ]);
+        let process = ContainerProcess::from_row(
+            &titles,
+            strings(&[
+                "4242",
+                "4200",
+                "nginx",
+                "1.5",
+                "0.3",
+                "2048",
+                "14:02",
+                "nginx: worker process",
+            ]),
+        );
+
+        assert_eq!(process.pid, Some(4242));
+        assert_eq!(process.ppid, Some(4200));
+        assert_eq!(process.user.as_deref(), Some("nginx"));
+        assert_eq!(process.cpu_percent, Some(1.5));
+        assert_eq!(process.rss, Some(2048 * 1024));
+        assert_eq!(process.command.as_deref(), Some("nginx: worker process"));
+    }
+
+    #[test]
+    fn test_process_from_custom_columns() {
+        let titles = strings(&["UID", "PID", "PPID", "C", "STIME", "TTY", "TIME", "CMD"]);
+        let process = ContainerProcess::from_row(
+            &titles,
+            strings(&[
+                "root",
+                "1",
+                "0",
+                "0",
+                "Oct16",
+                "?",
+                "00:00:01",
+                "sleep infinity",
+            ]),
+        );
+
+        assert_eq!(process.user.as_deref(), Some("root"));
+        assert_eq!(process.cpu_percent, Some(0.0));
+        assert_eq!(process.memory_percent, None);
+        assert_eq!(process.rss, None);
+        assert_eq!(process.start_time.as_deref(), Some("Oct16"));
+        assert_eq!(process.values.len(), 8);
+    }
+}
@@ -0,0 +1,683 @@
+use crate::entities::containers::{
+    is_valid_container_name, validate_env, validate_image, validate_mounts, validate_ports, EnvVar,
+    MountSpec, PortBindingSpec,
+};
+use bollard::container::{Config, NetworkingConfig};
+use bollard::models::{
+    ContainerConfig, ContainerInspectResponse, EndpointSettings, ImageConfig, Mount,
+    MountPointTypeEnum, MountTypeEnum, PortBinding, PortMap,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Prefix of the labels docker compose puts on the containers of a project.
+const COMPOSE_LABEL_PREFIX: &str = "com.docker.compose.";
+
+/// Length of the short container id the engine uses as default hostname and alias.
+const SHORT_ID_LENGTH: usize = 12;
+
+/// Settings changed when a container is recreated or cloned. Everything
+/// that is not set is copied from the existing container.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContainerPatch {
+    /// New image reference. The image must be available locally.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+
+    /// Environment variables to add or overwrite.
+    pub env: Vec<EnvVar>,
+
+    /// Names of environment variables to remove.
+    pub unset_env: Vec<String>,
+
+    /// Replaces all published ports when set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ports: Option<Vec<PortBindingSpec>>,
+
+    /// Replaces all bind mounts and named volumes when set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub mounts: Option<Vec<MountSpec>>,
+}
+
+impl ContainerPatch {
+    /// Validate the patch before anything is changed.
+    pub fn validate(&self) -> Result<(), String> {
+        // Checked the same way as the settings of a new container
+        let mut errors = self
+            .image
+            .as_deref()
+            .map(validate_image)
+            .unwrap_or_default();
+        errors.extend(validate_env(&self.env));
+        errors.extend(validate_ports(self.ports.as_deref().unwrap_or_default()));
+        errors.extend(validate_mounts(self.mounts.as_deref().unwrap_or_default()));
+
+        if self.unset_env.iter().any(|key| key.trim().is_empty()) {
+            errors.push("Environment variable name cannot be empty".to_string());
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+
+    /// Apply the patch to the create config of the replacement.
+    pub fn apply(&self, config: &mut Config<String>) {
+        if let Some(image) = &self.image {
+            config.image = Some(image.clone());
+        }
+
+        if !self.env.is_empty() || !self.unset_env.is_empty() {
+            let mut env: Vec<String> = config
+                .env
+                .take()
+                .unwrap_or_default()
+                .into_iter()
+                .filter(|var| {
+                    let key = var.split('=').next().unwrap_or_default();
+                    !self.unset_env.iter().any(|unset| unset == key)
+                        && !self.env.iter().any(|set| set.key == key)
+                })
+                .collect();
+            env.extend(self.env.iter().map(|var| var.to_string()));
+            config.env = Some(env);
+        }
+
+        let host_config = config.host_config.get_or_insert_with(Default::default);
+
+        if let Some(ports) = &self.ports {
+            let mut port_bindings: PortMap = HashMap::new();
+            let exposed_ports = config.exposed_ports.get_or_insert_with(HashMap::new);
+            for port in ports {
+                let key = port.port_key();
+                exposed_ports.insert(key.clone(), HashMap::new());
+                port_bindings
+                    .entry(key)
+                    .or_insert_with(|| Some(Vec::new()))
+                    .get_or_insert_with(Vec::new)
+                    .push(PortBinding {
+                        host_ip: port.host_ip.clone(),
+                        host_port: port.host_port.map(|p| p.to_string()),
+                    });
+            }
+            host_config.port_bindings = Some(port_bindings);
+        }
+
+        if let Some(mounts) = &self.mounts {
+            host_config.binds = None;
+            host_config.mounts = Some(mounts.iter().map(Mount::from).collect());
+        }
+    }
+}
+
+/// What the replacement of a container is for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum ReplacementKind {
+    /// Takes over the name, addresses and anonymous volumes of the container.
+    Recreate,
+
+    /// Runs next to the container, so nothing that has to be unique is copied.
+    Clone,
+
+    /// Describes the container to run it elsewhere. Like a clone, but published
+    /// host ports are kept, as they are part of how the container is reached.
+    Definition,
+}
+
+/// Create config of a container that behaves like an existing one.
+#[derive(Debug, Clone)]
+pub(crate) struct ReplacementConfig {
+    pub config: Config<String>,
+
+    /// Networks other than the primary one, connected after the container is created.
+    pub extra_networks: Vec<(String, EndpointSettings)>,
+}
+
+impl ReplacementConfig {
+    /// Build the config from the inspect data of a container and apply `patch`.
+    /// Settings that equal the defaults of its image in `image` are left out, so
+    /// the replacement picks up the defaults of whatever image it is created from.
+    pub fn from_inspect(
+        container: &ContainerInspectResponse,
+        image: Option<&ImageConfig>,
+        kind: ReplacementKind,
+        patch: &ContainerPatch,
+    ) -> Self {
+        let container_id = container.id.as_deref().unwrap_or_default();
+        let short_id = &container_id[..container_id.len().min(SHORT_ID_LENGTH)];
+
+        let mut container_config = container.config.clone().unwrap_or_default();
+        if kind != ReplacementKind::Recreate {
+            container_config.mac_address = None;
+            if let Some(labels) = container_config.labels.as_mut() {
+                labels.retain(|key, _| !key.starts_with(COMPOSE_LABEL_PREFIX));
+            }
+        }
+        if let Some(image) = image {
+            strip_image_defaults(&mut container_config, image);
+        }
+        // The engine uses the short id as hostname unless one was given
+        if !short_id.is_empty() && container_config.hostname.as_deref() == Some(short_id) {
+            container_config.hostname = None;
+        }
+
+        let mut host_config = container.host_config.clone().unwrap_or_default();
+        if kind == ReplacementKind::Clone {
+            // Published ports stay published, on host ports the engine picks
+            for binding in host_config
+                .port_bindings
+                .iter_mut()
+                .flat_map(|bindings| bindings.values_mut())
+                .flatten()
+                .flatten()
+            {
+                binding.host_port = None;
+            }
+        }
+        // `default` is how the engine reports the bridge network
+        let primary_network = host_config
+            .network_mode
+            .as_deref()
+            .map(|mode| if mode == "default" { "bridge" } else { mode })
+            .unwrap_or("bridge")
+            .to_string();
+
+        let mut config = config_from_container_config(container_config);
+        config.host_config = Some(host_config);
+        patch.apply(&mut config);
+
+        if kind == ReplacementKind::Recreate {
+            if let Some(host_config) = config.host_config.as_mut() {
+                let mounts = host_config.mounts.take();
+                host_config.mounts =
+                    keep_anonymous_volumes(container, host_config.binds.as_ref(), mounts);
+            }
+        }
+
+        let mut endpoints: Vec<(String, EndpointSettings)> = container
+            .network_settings
+            .as_ref()
+            .and_then(|settings| settings.networks.clone())
+            .unwrap_or_default()
+            .into_iter()
+            .map(|(name, endpoint)| (name, endpoint_config(endpoint, short_id, kind)))
+            .collect();
+        endpoints.sort_by(|(a, _), (b, _)| a.cmp(b));
+
+        let primary = endpoints
+            .iter()
+            .position(|(name, _)| *name == primary_network)
+            .map(|index| endpoints.remove(index));
+        config.networking_config = primary.map(|(name, endpoint)| NetworkingConfig {
+            endpoints_config: HashMap::from([(name, endpoint)]),
+        });
+
+        ReplacementConfig {
+            config,
+            extra_networks: endpoints,
+        }
+    }
+}
+
+/// Remove the settings of a container config that it only has because its
+/// image sets them, leaving what was given when the container was created.
+pub(crate) fn strip_image_defaults(config: &mut ContainerConfig, image: &ImageConfig) {
+    if let (Some(env), Some(image_env)) = (config.env.as_mut(), image.env.as_ref()) {
+        env.retain(|var| !image_env.contains(var));
+    }
+    if let (Some(labels), Some(image_labels)) = (config.labels.as_mut(), image.labels.as_ref()) {
+        labels.retain(|key, value| image_labels.get(key) != Some(value));
+    }
+    if let (Some(ports), Some(image_ports)) =
+        (config.exposed_ports.as_mut(), image.exposed_ports.as_ref())
+    {
+        ports.retain(|port, _| !image_ports.contains_key(port));
+    }
+    if let (Some(volumes), Some(image_volumes)) = (config.volumes.as_mut(), image.volumes.as_ref())
+    {
+        volumes.retain(|volume, _| !image_volumes.contains_key(volume));
+    }
+
+    // A new entrypoint resets the command of the image, so the command is only
+    // a default while the entrypoint is one too
+    if config.entrypoint == image.entrypoint {
+        config.entrypoint = None;
+        if config.cmd == image.cmd {
+            config.cmd = None;
+        }
+    }
+    if config.working_dir == image.working_dir {
+        config.working_dir = None;
+    }
+    if config.user == image.user {
+        config.user = None;
+    }
+    if config.stop_signal == image.stop_signal {
+        config.stop_signal = None;
+    }
+    if config.healthcheck == image.healthcheck {
+        config.healthcheck = None;
+    }
+    if config.shell == image.shell {
+        config.shell = None;
+    }
+    if config.on_build == image.on_build {
+        config.on_build = None;
+    }
+    config.args_escaped = None;
+
+    let is_empty = |values: &Option<Vec<String>>| values.as_ref().is_some_and(Vec::is_empty);
+    if is_empty(&config.env) {
+        config.env = None;
+    }
+    if config.labels.as_ref().is_some_and(HashMap::is_empty) {
+        config.labels = None;
+    }
+    if config.exposed_ports.as_ref().is_some_and(HashMap::is_empty) {
+        config.exposed_ports = None;
+    }
+    if config.volumes.as_ref().is_some_and(HashMap::is_empty) {
+        config.volumes = None;
+    }
+}
+
+fn config_from_container_config(config: ContainerConfig) -> Config<String> {
+    Config {
+        hostname: config.hostname,
+        domainname: config.domainname.filter(|domain| !domain.is_empty()),
+        user: config.user.filter(|user| !user.is_empty()),
+        attach_stdin: config.attach_stdin,
+        attach_stdout: config.attach_stdout,
+        attach_stderr: config.attach_stderr,
+        exposed_ports: config.exposed_ports,
+        tty: config.tty,
+        open_stdin: config.open_stdin,
+        stdin_once: config.stdin_once,
+        env: config.env,
+        cmd: config.cmd,
+        healthcheck: config.healthcheck,
+        args_escaped: config.args_escaped,
+        image: config.image,
+        volumes: config.volumes,
+        working_dir: config.working_dir.filter(|dir| !dir.is_empty()),
+        entrypoint: config.entrypoint,
+        network_disabled: config.network_disabled,
+        mac_address: config.mac_address,
+        on_build: config.on_build,
+        labels: config.labels,
+        stop_signal: config.stop_signal,
+        stop_timeout: config.stop_timeout,
+        shell: config.shell,
+        host_config: None,
+        networking_config: None,
+    }
+}
+
+/// Keep the settings of an endpoint that were given by the user and drop
+/// the ones the engine assigned when the container was connected.
+fn endpoint_config(
+    endpoint: EndpointSettings,
+    short_id: &str,
+    kind: ReplacementKind,
+) -> EndpointSettings {
+    let aliases = endpoint
+        .aliases
+        .map(|aliases| {
+            aliases
+                .into_iter()
+                .filter(|alias| alias != short_id)
+                .collect::<Vec<_>>()
+        })
+        .filter(|aliases| !aliases.is_empty());
+
+    let keep_addresses = kind == ReplacementKind::Recreate;
+    EndpointSettings {
+        ipam_config: endpoint.ipam_config.filter(|_| keep_addresses),
+        links: endpoint.links,
+        mac_address: endpoint
+            .mac_address
+            .filter(|mac| keep_addresses && !mac.is_empty()),
+        aliases,
+        driver_opts: endpoint.driver_opts,
+        ..Default::default()
+    }
+}
+
+/// Add the anonymous volumes of a container as mounts, so its replacement
+/// keeps their data instead of getting new empty ones.
+fn keep_anonymous_volumes(
+    container: &ContainerInspectResponse,
+    binds: Option<&Vec<String>>,
+    mounts: Option<Vec<Mount>>,
+) -> Option<Vec<Mount>> {
+    // Binds are written as `source:target[:options]`
+    let mut targets: Vec<String> = binds
+        .into_iter()
+        .flatten()
+        .filter_map(|bind| bind.split(':').nth(1).map(str::to_string))
+        .collect();
+    targets.extend(
+        mounts
+            .iter()
+            .flatten()
+            .filter_map(|mount| mount.target.clone()),
+    );
+
+    let anonymous: Vec<Mount> = container
+        .mounts
+        .iter()
+        .flatten()
+        .filter(|mount| mount.typ == Some(MountPointTypeEnum::VOLUME))
+        .filter_map(|mount| {
+            let destination = mount.destination.clone()?;
+            if targets.contains(&destination) {
+                return None;
+            }
+            Some(Mount {
+                target: Some(destination),
+                source: mount.name.clone(),
+                typ: Some(MountTypeEnum::VOLUME),
+                read_only: mount.rw.map(|rw| !rw),
+                ..Default::default()
+            })
+        })
+        .collect();
+
+    if anonymous.is_empty() {
+        return mounts;
+    }
+    let mut mounts = mounts.unwrap_or_default();
+    mounts.extend(anonymous);
+    Some(mounts)
+}
+
+/// Options used to create a copy of a container.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CloneContainerOptions {
+    pub name: String,
+    pub patch: ContainerPatch,
+
+    /// Start the copy once it is created.
+    pub start: bool,
+}
+
+impl CloneContainerOptions {
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors = Vec::new();
+        if !is_valid_container_name(&self.name) {
+            errors.push(format!(
+                "Invalid container name: {}. Only [a-zA-Z0-9][a-zA-Z0-9_.-] are allowed",
+                self.name
+            ));
+        }
+        if let Err(error) = self.patch.validate() {
+            errors.push(error);
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+}
+
+/// Result of a recreate request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecreateContainerResult {
+    /// ID of the new container.
+    pub id: String,
+
+    /// ID of the container that was replaced and removed.
+    pub replaced_id: String,
+
+    pub warnings: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::containers::MountPointType;
+    use bollard::models::{ContainerStateStatusEnum, HostConfig, MountPoint, NetworkSettings};
+
+    const CONTAINER_ID: &str = "0123456789abcdef0123456789abcdef";
+
+    fn strings(values: &[&str]) -> Vec<String> {
+        values.iter().map(|value| value.to_string()).collect()
+    }
+
+    fn image_config() -> ImageConfig {
+        ImageConfig {
+            env: Some(strings(&["PATH=/usr/bin", "NGINX_VERSION=1.27"])),
+            cmd: Some(strings(&["nginx", "-g", "daemon off;"])),
+            entrypoint: Some(strings(&["/docker-entrypoint.sh"])),
+            exposed_ports: Some(HashMap::from([("80/tcp".to_string(), HashMap::new())])),
+            labels: Some(HashMap::from([(
+                "maintainer".to_string(),
+                "NGINX".to_string(),
+            )])),
+            stop_signal: Some("SIGQUIT".to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn container() -> ContainerInspectResponse {
+        ContainerInspectResponse {
+            id: Some(CONTAINER_ID.to_string()),
+            name: Some("/web".to_string()),
+            config: Some(ContainerConfig {
+                hostname: Some("0123456789ab".to_string()),
+                image: Some("nginx:1.27".to_string()),
+                env: Some(strings(&["PATH=/usr/bin", "NGINX_VERSION=1.27", "DEBUG=1"])),
+                cmd: Some(strings(&["nginx", "-g", "daemon off;"])),
+                entrypoint: Some(strings(&["/docker-entrypoint.sh"])),
+                exposed_ports: Some(HashMap::from([("80/tcp".to_string(), HashMap::new())])),
+                labels: Some(HashMap::from([
+                    ("maintainer".to_string(), "NGINX".to_string()),
+                    ("com.docker.compose.project".to_string(), "site".to_string()),
+                ])),
+                stop_signal: Some("SIGQUIT".to_string()),
+                ..Default::default()
+            }),
+            host_config: Some(HostConfig {
+                network_mode: Some("site_default".to_string()),
+                binds: Some(strings(&["/srv/www:/usr/share/nginx/html:ro"])),
+                port_bindings: Some(HashMap::from([(
+                    "80/tcp".to_string(),
+                    Some(vec![PortBinding {
+                        host_ip: Some("127.0.0.1".to_string()),
+                        host_port: Some("8080".to_string()),
+                    }]),
+                )])),
+                ..Default::default()
+            }),
+            mounts: Some(vec![
+                MountPoint {
+                    typ: Some(MountPointTypeEnum::BIND),
+                    source: Some("/srv/www".to_string()),
+                    destination: Some("/usr/share/nginx/html".to_string()),
+                    rw: Some(false),
+                    ..Default::default()
+                },
+                MountPoint {
+                    typ: Some(MountPointTypeEnum::VOLUME),
+                    name: Some("f00dcafe".to_string()),
+                    destination: Some("/var/cache/nginx".to_string()),
+                    rw: Some(true),
+                    ..Default::default()
+                },
+            ]),
+            network_settings: Some(NetworkSettings {
+                networks: Some(HashMap::from([
+                    (
+                        "site_default".to_string(),
+                        EndpointSettings {
+                            aliases: Some(strings(&["web", "0123456789ab"])),
+                            ip_address: Some("172.20.0.2".to_string()),
+                            network_id: Some("abc".to_string()),
+                            ..Default::default()
+                        },
+                    ),
+                    ("monitoring".to_string(), EndpointSettings::default()),
+                ])),
+                ..Default::default()
+            }),
+            state: Some(bollard::models::ContainerState {
+                status: Some(ContainerStateStatusEnum::RUNNING),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_recreate_config_keeps_user_settings_only() {
+        let replacement = ReplacementConfig::from_inspect(
+            &container(),
+            Some(&image_config()),
+            ReplacementKind::Recreate,
+            &ContainerPatch::default(),
+        );
+        let config = &replacement.config;
+
+        assert_eq!(config.env, Some(strings(&["DEBUG=1"])));
+        assert_eq!(config.hostname, None);
+        assert_eq!(config.cmd, None);
+        assert_eq!(config.entrypoint, None);
+        assert_eq!(config.exposed_ports, None);
+        assert_eq!(config.stop_signal, None);
+        assert!(config
+            .labels
+            .as_ref()
+            .unwrap()
+            .contains_key("com.docker.compose.project"));
+
+        let endpoints = &config.networking_config.as_ref().unwrap().endpoints_config;
+        let primary = &endpoints["site_default"];
+        assert_eq!(primary.aliases, Some(strings(&["web"])));
+        assert_eq!(primary.ip_address, None);
+        assert_eq!(replacement.extra_networks.len(), 1);
+        assert_eq!(replacement.extra_networks[0].0, "monitoring");
+
+        // The anonymous cache volume is carried over, the bind mount stays in binds
+        let mounts = config
+            .host_config
+            .as_ref()
+            .unwrap()
+            .mounts
+            .as_ref()
+            .unwrap();
+        assert_eq!(mounts.len(), 1);
+        assert_eq!(mounts[0].source.as_deref(), Some("f00dcafe"));
+        assert_eq!(mounts[0].target.as_deref(), Some("/var/cache/nginx"));
+    }
+
+    #[test]
+    fn test_clone_config_drops_unique_settings() {
+        let replacement = ReplacementConfig::from_inspect(
+            &container(),
+            Some(&image_config()),
+            ReplacementKind::Clone,
+            &ContainerPatch::default(),
+        );
+        let config = &replacement.config;
+
+        assert_eq!(config.labels, None);
+        let host_config = config.host_config.as_ref().unwrap();
+        assert_eq!(host_config.mounts, None);
+
+        // The port is still published, on a host port the engine picks
+        let binding = &host_config.port_bindings.as_ref().unwrap()["80/tcp"]
+            .as_ref()
+            .unwrap()[0];
+        assert_eq!(binding.host_ip.as_deref(), Some("127.0.0.1"));
+        assert_eq!(binding.host_port, None);
+    }
+
+    #[test]
+    fn test_patch_apply() {
+        let patch = ContainerPatch {
+            image: Some("nginx:1.28".to_string()),
+            env: vec![EnvVar {
+                key: "MODE".to_string(),
+                value: "prod".to_string(),
+            }],
+            unset_env: strings(&["DEBUG"]),
+            ports: Some(vec![PortBindingSpec {
+                container_port: 80,
+                protocol: None,
+                host_ip: None,
+                host_port: Some(8080),
+            }]),
+            mounts: Some(vec![MountSpec {
+                mount_type: MountPointType::Volume,
+                source: Some("www".to_string()),
+                target: "/usr/share/nginx/html".to_string(),
+                read_only: true,
+                tmpfs_size: None,
+            }]),
+        };
+        assert!(patch.validate().is_ok());
+
+        let config = ReplacementConfig::from_inspect(
+            &container(),
+            Some(&image_config()),
+            ReplacementKind::Recreate,
+            &patch,
+        )
+        .config;
+
+        assert_eq!(config.image.as_deref(), Some("nginx:1.28"));
+        assert_eq!(config.env, Some(strings(&["MODE=prod"])));
+        let host_config = config.host_config.unwrap();
+        let bindings = host_config.port_bindings.unwrap();
+        assert_eq!(
+            bindings["80/tcp"].as_ref().unwrap()[0].host_port.as_deref(),
+            Some("8080")
+        );
+
+        // The bind mount is replaced, the anonymous volume is still carried over
+        assert_eq!(host_config.binds, None);
+        let mounts = host_config.mounts.unwrap();
+        assert_eq!(mounts.len(), 2);
+        assert_eq!(mounts[0].source.as_deref(), Some("www"));
+        assert_eq!(mounts[1].source.as_deref(), Some("f00dcafe"));
+    }
+
+    #[test]
+    fn test_patch_validation() {
+        let patch = ContainerPatch {
+            env: vec![EnvVar {
+                key: "BAD KEY".to_string(),
+                value: String::new(),
+            }],
+            unset_env: strings(&[""]),
+            ..Default::default()
+        };
+        assert_eq!(
+            patch.validate().unwrap_err(),
+            "Invalid environment variable name: BAD KEY; Environment variable name cannot be empty"
+        );
+        assert!(ContainerPatch::default().validate().is_ok());
+
+        let patch = ContainerPatch {
+            image: Some(" ".to_string()),
+            mounts: Some(vec![MountSpec {
+                mount_type: MountPointType::Volume,
+                source: None,
+                target: "data".to_string(),
+                read_only: false,
+                tmpfs_size: None,
+            }]),
+            ..Default::default()
+        };
+        assert_eq!(
+            patch.validate().unwrap_err(),
+            "Image cannot be empty; Mount target must be an absolute path: data"
+        );
+    }
+}
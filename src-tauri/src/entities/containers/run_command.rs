@@ -0,0 +1,861 @@
+use crate::entities::containers::{
+    ContainerSpec, EnvVar, MountPointType, MountSpec, NetworkAttachment, PortBindingSpec,
+    PortTypeEnum, RestartPolicy, RestartPolicyName,
+};
+use serde::{Deserialize, Serialize};
+
+/// Flags of `docker run` that take no value. Every other unknown flag is
+/// assumed to take one, so its value is not mistaken for the image.
+const BOOLEAN_FLAGS: [&str; 15] = [
+    "detach",
+    "disable-content-trust",
+    "help",
+    "init",
+    "interactive",
+    "no-healthcheck",
+    "oom-kill-disable",
+    "privileged",
+    "publish-all",
+    "quiet",
+    "read-only",
+    "rm",
+    "sig-proxy",
+    "tty",
+    "use-api-socket",
+];
+
+/// Result of parsing a `docker run` command line.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct ParsedRunCommand {
+    /// Settings of the container. `docker run` always starts the container,
+    /// so the spec is meant to be created with `start` set.
+    pub spec: ContainerSpec,
+
+    /// Whether `-d` was given. Without it `docker run` attaches to the output.
+    pub detach: bool,
+
+    /// Flags that are not part of the spec and were ignored, as written.
+    pub unsupported_flags: Vec<String>,
+
+    /// Things that were understood but could not be carried over exactly.
+    pub warnings: Vec<String>,
+}
+
+impl ParsedRunCommand {
+    /// Parse a command such as `docker run -d -p 8080:80 --name web nginx`.
+    /// `read_env_file` returns the content of a file given with `--env-file`.
+    pub fn parse<F>(command: &str, mut read_env_file: F) -> Result<Self, String>
+    where
+        F: FnMut(&str) -> Result<String, String>,
+    {
+        let words = split_command_line(command)?;
+        let mut words = words.into_iter().peekable();
+
+        if words.peek().is_some_and(|word| word == "sudo") {
+            words.next();
+        }
+        let program = words.next().unwrap_or_default();
+        let program = program.rsplit(['/', '\\']).next().unwrap_or_default();
+        if !matches!(program, "docker" | "docker.exe" | "podman" | "nerdctl") {
+            return Err("Not a docker run command".to_string());
+        }
+        if words.peek().is_some_and(|word| word == "container") {
+            words.next();
+        }
+        if words.next().as_deref() != Some("run") {
+            return Err("Not a docker run command".to_string());
+        }
+
+        let mut parser = RunCommandParser::default();
+        while let Some(word) = words.next() {
+            if word == "--" {
+                parser.image = words.next();
+                break;
+            }
+
+            if let Some(flag) = word.strip_prefix("--") {
+                let (name, inline_value) = match flag.split_once('=') {
+                    Some((name, value)) => (name, Some(value.to_string())),
+                    None => (flag, None),
+                };
+                let flag = format!("--{}", name);
+                if BOOLEAN_FLAGS.contains(&name) || inline_value.is_some() {
+                    let value = inline_value.unwrap_or_else(|| "true".to_string());
+                    parser.apply(&flag, name, Some(value), word.clone());
+                } else {
+                    let value = words.next();
+                    let written = format!("{} {}", word, value.as_deref().unwrap_or_default());
+                    parser.apply(&flag, name, value, written);
+                }
+            } else if word.len() > 1 && word.starts_with('-') {
+                // Short flags can be grouped (`-it`) and take their value attached (`-p80:80`)
+                for (index, short) in word.char_indices().skip(1) {
+                    let flag = format!("-{}", short);
+                    let Some((name, takes_value)) = short_flag(short) else {
+                        parser.unsupported.push(flag);
+                        continue;
+                    };
+                    if !takes_value {
+                        parser.apply(&flag, name, Some("true".to_string()), flag.clone());
+                        continue;
+                    }
+
+                    let rest = &word[index + short.len_utf8()..];
+                    let rest = rest.strip_prefix('=').unwrap_or(rest);
+                    let value = if rest.is_empty() {
+                        words.next()
+                    } else {
+                        Some(rest.to_string())
+                    };
+                    let written = format!("{} {}", flag, value.as_deref().unwrap_or_default());
+                    parser.apply(&flag, name, value, written);
+                    break;
+                }
+            } else {
+                parser.image = Some(word);
+                break;
+            }
+        }
+        let command: Vec<String> = words.collect();
+
+        let mut env_file_vars = Vec::new();
+        for path in std::mem::take(&mut parser.env_files) {
+            match read_env_file(&path) {
+                Ok(content) => {
+                    let (vars, warnings) = parse_env_file(&content);
+                    env_file_vars.extend(vars);
+                    parser.warnings.extend(
+                        warnings
+                            .into_iter()
+                            .map(|warning| format!("{}: {}", path, warning)),
+                    );
+                }
+                Err(e) => parser
+                    .errors
+                    .push(format!("Failed to read env file {}: {}", path, e)),
+            }
+        }
+
+        parser.finish(env_file_vars, command)
+    }
+}
+
+/// State collected while the flags are read.
+#[derive(Debug, Default)]
+struct RunCommandParser {
+    spec: ContainerSpec,
+    detach: bool,
+    image: Option<String>,
+    env: Vec<EnvVar>,
+    env_files: Vec<String>,
+    network_aliases: Vec<String>,
+    unsupported: Vec<String>,
+    warnings: Vec<String>,
+    errors: Vec<String>,
+}
+
+impl RunCommandParser {
+    /// Apply a flag by its long name. `flag` is the flag as written and
+    /// `written` the flag with its value, as reported when it is not supported.
+    fn apply(&mut self, flag: &str, name: &str, value: Option<String>, written: String) {
+        let Some(value) = value else {
+            self.errors.push(format!("Flag {} needs a value", flag));
+            return;
+        };
+
+        let result = match name {
+            "detach" => parse_bool(&value).map(|detach| self.detach = detach),
+            "interactive" => parse_bool(&value).map(|open| self.spec.open_stdin = open),
+            "tty" => parse_bool(&value).map(|tty| self.spec.tty = tty),
+            "rm" => parse_bool(&value).map(|remove| self.spec.auto_remove = remove),
+            "publish" => parse_port(&value).map(|ports| self.spec.ports.extend(ports)),
+            "env" => {
+                match value.split_once('=') {
+                    Some((key, value)) => self.env.push(EnvVar {
+                        key: key.to_string(),
+                        value: value.to_string(),
+                    }),
+                    None => self.warnings.push(format!(
+                        "{} takes its value from the local environment and was left out",
+                        value
+                    )),
+                }
+                Ok(())
+            }
+            "env-file" => {
+                self.env_files.push(value.clone());
+                Ok(())
+            }
+            "volume" => parse_volume(&value).map(|(mount, ignored)| {
+                self.spec.mounts.push(mount);
+                self.warnings.extend(
+                    ignored
+                        .into_iter()
+                        .map(|option| format!("Volume option {} is not supported", option)),
+                );
+            }),
+            "mount" => parse_mount(&value).map(|(mount, ignored)| {
+                self.spec.mounts.push(mount);
+                self.warnings.extend(
+                    ignored
+                        .into_iter()
+                        .map(|option| format!("Mount option {} is not supported", option)),
+                );
+            }),
+            "tmpfs" => parse_tmpfs(&value).map(|mount| self.spec.mounts.push(mount)),
+            "network" | "net" => {
+                self.spec.networks.push(NetworkAttachment {
+                    name: value.clone(),
+                    aliases: Vec::new(),
+                });
+                Ok(())
+            }
+            "network-alias" | "net-alias" => {
+                self.network_aliases.push(value.clone());
+                Ok(())
+            }
+            "name" => {
+                self.spec.name = Some(value.clone());
+                Ok(())
+            }
+            "restart" => parse_restart_policy(&value).map(|policy| {
+                self.spec.restart_policy = Some(policy);
+            }),
+            "memory" => parse_bytes(&value).map(|bytes| self.spec.resources.memory = Some(bytes)),
+            "memory-reservation" => parse_bytes(&value)
+                .map(|bytes| self.spec.resources.memory_reservation = Some(bytes)),
+            "memory-swap" => {
+                let bytes = if value == "-1" {
+                    Ok(-1)
+                } else {
+                    parse_bytes(&value)
+                };
+                bytes.map(|bytes| self.spec.resources.memory_swap = Some(bytes))
+            }
+            "cpus" => parse_number(&value).map(|cpus| self.spec.resources.cpus = Some(cpus)),
+            "cpu-shares" => {
+                parse_number(&value).map(|shares| self.spec.resources.cpu_shares = Some(shares))
+            }
+            "cpuset-cpus" => {
+                self.spec.resources.cpuset_cpus = Some(value.clone());
+                Ok(())
+            }
+            "pids-limit" => {
+                parse_number(&value).map(|limit| self.spec.resources.pids_limit = Some(limit))
+            }
+            "label" => {
+                let (key, label) = value.split_once('=').unwrap_or((&value, ""));
+                self.spec.labels.insert(key.to_string(), label.to_string());
+                Ok(())
+            }
+            // The entrypoint is a single executable, an empty one resets the image entrypoint
+            "entrypoint" => {
+                self.spec.entrypoint = Some(if value.is_empty() {
+                    Vec::new()
+                } else {
+                    vec![value.clone()]
+                });
+                Ok(())
+            }
+            "hostname" => {
+                self.spec.hostname = Some(value.clone());
+                Ok(())
+            }
+            "user" => {
+                self.spec.user = Some(value.clone());
+                Ok(())
+            }
+            "workdir" => {
+                self.spec.working_dir = Some(value.clone());
+                Ok(())
+            }
+            _ => {
+                self.unsupported.push(written);
+                Ok(())
+            }
+        };
+
+        if let Err(e) = result {
+            self.errors.push(format!("{}: {}", flag, e));
+        }
+    }
+
+    fn finish(
+        mut self,
+        env_file_vars: Vec<EnvVar>,
+        command: Vec<String>,
+    ) -> Result<ParsedRunCommand, String> {
+        match self.image.take() {
+            Some(image) => self.spec.image = image,
+            None => self.errors.push("No image given".to_string()),
+        }
+        if !self.errors.is_empty() {
+            return Err(self.errors.join("; "));
+        }
+
+        // Variables set with -e win over the ones from env files
+        for var in env_file_vars.into_iter().chain(self.env) {
+            match self.spec.env.iter_mut().find(|set| set.key == var.key) {
+                Some(set) => set.value = var.value,
+                None => self.spec.env.push(var),
+            }
+        }
+
+        if !self.network_aliases.is_empty() {
+            match self.spec.networks.first_mut() {
+                Some(network) => network.aliases = self.network_aliases,
+                None => self
+                    .warnings
+                    .push("Network aliases need a user-defined network and were left out".into()),
+            }
+        }
+
+        if !command.is_empty() {
+            self.spec.command = Some(command);
+        }
+
+        Ok(ParsedRunCommand {
+            spec: self.spec,
+            detach: self.detach,
+            unsupported_flags: self.unsupported,
+            warnings: self.warnings,
+        })
+    }
+}
+
+/// Long name of a single letter flag and whether it takes a value.
+fn short_flag(flag: char) -> Option<(&'static str, bool)> {
+    let flag = match flag {
+        'd' => ("detach", false),
+        'i' => ("interactive", false),
+        't' => ("tty", false),
+        'P' => ("publish-all", false),
+        'q' => ("quiet", false),
+        'a' => ("attach", true),
+        'c' => ("cpu-shares", true),
+        'e' => ("env", true),
+        'h' => ("hostname", true),
+        'l' => ("label", true),
+        'm' => ("memory", true),
+        'p' => ("publish", true),
+        'u' => ("user", true),
+        'v' => ("volume", true),
+        'w' => ("workdir", true),
+        _ => return None,
+    };
+    Some(flag)
+}
+
+/// Split a command line into words the way a POSIX shell does, including
+/// quotes, escapes and lines continued with a backslash.
+pub(crate) fn split_command_line(command: &str) -> Result<Vec<String>, String> {
+    let command = command.trim();
+    // Commands copied from docs often start with a prompt
+    let command = command.strip_prefix("$ ").unwrap_or(command);
+
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some('\n') => {}
+                Some('\r') if chars.peek() == Some(&'\n') => {
+                    chars.next();
+                }
+                Some(escaped) => {
+                    word.push(escaped);
+                    in_word = true;
+                }
+                None => return Err("Command ends with an escape".to_string()),
+            },
+            '\'' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('\'') => break,
+                        Some(c) => word.push(c),
+                        None => return Err("Unterminated single quote".to_string()),
+                    }
+                }
+            }
+            '"' => {
+                in_word = true;
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some(c @ ('"' | '\\' | '$' | '`')) => word.push(c),
+                            Some('\n') => {}
+                            Some(c) => {
+                                word.push('\\');
+                                word.push(c);
+                            }
+                            None => return Err("Unterminated double quote".to_string()),
+                        },
+                        Some(c) => word.push(c),
+                        None => return Err("Unterminated double quote".to_string()),
+                    }
+                }
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    words.push(std::mem::take(&mut word));
+                    in_word = false;
+                }
+            }
+            c => {
+                word.push(c);
+                in_word = true;
+            }
+        }
+    }
+    if in_word {
+        words.push(word);
+    }
+
+    Ok(words)
+}
+
+/// Parse the content of an env file. Lines without a value are taken from the
+/// local environment by docker, they are left out and returned as warnings.
+pub(crate) fn parse_env_file(content: &str) -> (Vec<EnvVar>, Vec<String>) {
+    let mut vars = Vec::new();
+    let mut warnings = Vec::new();
+
+    for line in content.lines() {
+        let line = line.trim_start();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        match line.split_once('=') {
+            Some((key, value)) => vars.push(EnvVar {
+                key: key.to_string(),
+                value: value.to_string(),
+            }),
+            None => warnings.push(format!(
+                "{} takes its value from the local environment and was left out",
+                line.trim_end()
+            )),
+        }
+    }
+
+    (vars, warnings)
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    match value {
+        "true" | "1" => Ok(true),
+        "false" | "0" => Ok(false),
+        _ => Err(format!("Invalid boolean: {}", value)),
+    }
+}
+
+fn parse_number<T: std::str::FromStr>(value: &str) -> Result<T, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("Invalid number: {}", value))
+}
+
+/// Parse sizes such as `512m`, `1g`, `1.5GB` or `1024`. Units are powers of 1024, as in docker.
+pub(crate) fn parse_bytes(value: &str) -> Result<i64, String> {
+    let lower = value.trim().to_lowercase();
+    let number_end = lower
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(lower.len());
+    let (number, unit) = lower.split_at(number_end);
+    let number: f64 = number
+        .parse()
+        .map_err(|_| format!("Invalid size: {}", value))?;
+
+    let multiplier: i64 = match unit.trim().trim_end_matches("ib").trim_end_matches('b') {
+        "" => 1,
+        "k" => 1 << 10,
+        "m" => 1 << 20,
+        "g" => 1 << 30,
+        "t" => 1 << 40,
+        "p" => 1 << 50,
+        _ => return Err(format!("Invalid size: {}", value)),
+    };
+
+    Ok((number * multiplier as f64) as i64)
+}
+
+/// Parse `[ip:][host_port:]container_port[/protocol]`, where ports may be ranges.
+pub(crate) fn parse_port(value: &str) -> Result<Vec<PortBindingSpec>, String> {
+    let (address, protocol) = match value.rsplit_once('/') {
+        Some((address, protocol)) => (address, protocol),
+        None => (value, "tcp"),
+    };
+    let protocol = match protocol.to_lowercase().as_str() {
+        "tcp" => None,
+        "udp" => Some(PortTypeEnum::Udp),
+        "sctp" => Some(PortTypeEnum::Sctp),
+        _ => return Err(format!("Invalid protocol: {}", protocol)),
+    };
+
+    // IPv6 host addresses are written in brackets, e.g. `[::1]:8080:80`
+    let (host_ip, ports) = match address.strip_prefix('[') {
+        Some(rest) => {
+            let (ip, rest) = rest
+                .split_once("]:")
+                .ok_or_else(|| format!("Invalid port mapping: {}", value))?;
+            (Some(ip.to_string()), rest.to_string())
+        }
+        None => {
+            let parts: Vec<&str> = address.split(':').collect();
+            match parts.len() {
+                1 | 2 => (None, address.to_string()),
+                3 => (Some(parts[0].to_string()), parts[1..].join(":")),
+                _ => return Err(format!("Invalid port mapping: {}", value)),
+            }
+        }
+    };
+    let host_ip = host_ip.filter(|ip| !ip.is_empty());
+
+    let (host_ports, container_ports) = match ports.split_once(':') {
+        Some((host, container)) if !host.is_empty() => (Some(port_range(host)?), container),
+        Some((_, container)) => (None, container),
+        None => (None, ports.as_str()),
+    };
+    let container_ports = port_range(container_ports)?;
+    if let Some(host_ports) = &host_ports {
+        if host_ports.len() != container_ports.len() {
+            return Err(format!(
+                "Host and container port ranges differ in size: {}",
+                value
+            ));
+        }
+    }
+
+    Ok(container_ports
+        .iter()
+        .enumerate()
+        .map(|(index, container_port)| PortBindingSpec {
+            container_port: *container_port,
+            protocol,
+            host_ip: host_ip.clone(),
+            host_port: host_ports.as_ref().map(|ports| ports[index]),
+        })
+        .collect())
+}
+
+fn port_range(value: &str) -> Result<Vec<u16>, String> {
+    let port = |port: &str| {
+        port.parse::<u16>()
+            .map_err(|_| format!("Invalid port: {}", port))
+    };
+    match value.split_once('-') {
+        Some((start, end)) => {
+            let (start, end) = (port(start)?, port(end)?);
+            if start > end {
+                return Err(format!("Invalid port range: {}", value));
+            }
+            Ok((start..=end).collect())
+        }
+        None => Ok(vec![port(value)?]),
+    }
+}
+
+/// Parse `-v [source:]target[:options]`. Sources that look like paths are bind
+/// mounts, other sources are named volumes and a lone target is an anonymous
+/// volume. Returns the options that have no equivalent in the spec.
+pub(crate) fn parse_volume(value: &str) -> Result<(MountSpec, Vec<String>), String> {
+    let mut parts: Vec<String> = value.split(':').map(str::to_string).collect();
+    // Windows drive letters, e.g. `C:\data:/data`
+    if parts.len() > 2 && parts[0].len() == 1 && parts[1].starts_with(['\\', '/']) {
+        let drive = parts.remove(0);
+        parts[0] = format!("{}:{}", drive, parts[0]);
+    }
+
+    let (source, target, options) = match parts.as_slice() {
+        [target] => (None, target.clone(), ""),
+        [source, target] => (Some(source.clone()), target.clone(), ""),
+        [source, target, options] => (Some(source.clone()), target.clone(), options.as_str()),
+        _ => return Err(format!("Invalid volume: {}", value)),
+    };
+
+    let mut read_only = false;
+    let mut ignored = Vec::new();
+    for option in options.split(',').filter(|option| !option.is_empty()) {
+        match option {
+            "ro" => read_only = true,
+            "rw" => read_only = false,
+            option => ignored.push(option.to_string()),
+        }
+    }
+
+    let is_path = |source: &str| {
+        source.starts_with(['/', '.', '~', '\\']) || source.as_bytes().get(1) == Some(&b':')
+    };
+    let mount_type = match &source {
+        Some(source) if is_path(source) => MountPointType::Bind,
+        _ => MountPointType::Volume,
+    };
+
+    Ok((
+        MountSpec {
+            mount_type,
+            source,
+            target,
+            read_only,
+            tmpfs_size: None,
+        },
+        ignored,
+    ))
+}
+
+/// Parse `--mount type=bind,source=/src,target=/dst,readonly`.
+/// Returns the options that have no equivalent in the spec.
+fn parse_mount(value: &str) -> Result<(MountSpec, Vec<String>), String> {
+    let mut mount = MountSpec {
+        mount_type: MountPointType::Volume,
+        source: None,
+        target: String::new(),
+        read_only: false,
+        tmpfs_size: None,
+    };
+    let mut ignored = Vec::new();
+
+    for field in value.split(',').filter(|field| !field.is_empty()) {
+        let (key, option) = match field.split_once('=') {
+            Some((key, option)) => (key, Some(option)),
+            None => (field, None),
+        };
+        match (key, option) {
+            ("type", Some(mount_type)) => {
+                mount.mount_type = match mount_type {
+                    "bind" => MountPointType::Bind,
+                    "volume" => MountPointType::Volume,
+                    "tmpfs" => MountPointType::Tmpfs,
+                    _ => return Err(format!("Unsupported mount type: {}", mount_type)),
+                }
+            }
+            ("source" | "src", Some(source)) => mount.source = Some(source.to_string()),
+            ("target" | "destination" | "dst", Some(target)) => mount.target = target.to_string(),
+            ("readonly" | "ro", option) => {
+                mount.read_only = option.map(parse_bool).transpose()?.unwrap_or(true)
+            }
+            ("tmpfs-size", Some(size)) => mount.tmpfs_size = Some(parse_bytes(size)?),
+            _ => ignored.push(field.to_string()),
+        }
+    }
+
+    if mount.target.is_empty() {
+        return Err(format!("Mount has no target: {}", value));
+    }
+    Ok((mount, ignored))
+}
+
+/// Parse `--tmpfs /run[:options]`, keeping the size option.
+fn parse_tmpfs(value: &str) -> Result<MountSpec, String> {
+    let (target, options) = value.split_once(':').unwrap_or((value, ""));
+    let mut tmpfs_size = None;
+    for option in options.split(',') {
+        if let Some(size) = option.strip_prefix("size=") {
+            tmpfs_size = Some(parse_bytes(size)?);
+        }
+    }
+
+    Ok(MountSpec {
+        mount_type: MountPointType::Tmpfs,
+        source: None,
+        target: target.to_string(),
+        read_only: options.split(',').any(|option| option == "ro"),
+        tmpfs_size,
+    })
+}
+
+/// Parse `no`, `always`, `unless-stopped` or `on-failure[:max-retries]`.
+pub(crate) fn parse_restart_policy(value: &str) -> Result<RestartPolicy, String> {
+    let (name, count) = match value.split_once(':') {
+        Some((name, count)) => (name, Some(parse_number(count)?)),
+        None => (value, None),
+    };
+    let name: RestartPolicyName = name.parse()?;
+
+    let policy = RestartPolicy {
+        name,
+        maximum_retry_count: count,
+    };
+    policy.validate()?;
+    Ok(policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(command: &str) -> Result<ParsedRunCommand, String> {
+        ParsedRunCommand::parse(command, |path| Err(format!("{} not found", path)))
+    }
+
+    #[test]
+    fn test_parse_run_command() {
+        let parsed = parse(
+            "docker run -d --name web -p 8080:80 -p 127.0.0.1:8443:443/tcp \
+             -e DEBUG=1 --env MODE=prod -v /srv/www:/usr/share/nginx/html:ro \
+             -v cache:/var/cache/nginx --network site --network-alias www \
+             --restart on-failure:3 --memory 512m --cpus 1.5 -l team=web \
+             --entrypoint /docker-entrypoint.sh nginx:1.27 nginx -g 'daemon off;'",
+        )
+        .unwrap();
+        let spec = &parsed.spec;
+
+        assert!(parsed.detach);
+        assert!(parsed.unsupported_flags.is_empty());
+        assert_eq!(spec.image, "nginx:1.27");
+        assert_eq!(spec.name.as_deref(), Some("web"));
+        assert_eq!(
+            spec.command,
+            Some(vec![
+                "nginx".to_string(),
+                "-g".to_string(),
+                "daemon off;".to_string()
+            ])
+        );
+        assert_eq!(
+            spec.entrypoint,
+            Some(vec!["/docker-entrypoint.sh".to_string()])
+        );
+        assert_eq!(spec.ports.len(), 2);
+        assert_eq!(spec.ports[1].host_ip.as_deref(), Some("127.0.0.1"));
+        assert_eq!(spec.ports[1].host_port, Some(8443));
+        assert_eq!(spec.env.len(), 2);
+        assert_eq!(spec.mounts[0].mount_type, MountPointType::Bind);
+        assert!(spec.mounts[0].read_only);
+        assert_eq!(spec.mounts[1].mount_type, MountPointType::Volume);
+        assert_eq!(spec.networks[0].aliases, vec!["www".to_string()]);
+        let policy = spec.restart_policy.as_ref().unwrap();
+        assert_eq!(policy.name, RestartPolicyName::OnFailure);
+        assert_eq!(policy.maximum_retry_count, Some(3));
+        assert_eq!(spec.resources.memory, Some(512 * 1024 * 1024));
+        assert_eq!(spec.resources.cpus, Some(1.5));
+        assert_eq!(spec.labels["team"], "web");
+        assert!(spec.validate().is_ok());
+    }
+
+    #[test]
+    fn test_short_flags_and_unsupported_flags() {
+        let parsed = parse(
+            "$ sudo docker container run -it --rm -p80 -eTZ=UTC --log-driver json-file \
+             --privileged --cap-add=NET_ADMIN -P alpine:3 sh",
+        )
+        .unwrap();
+
+        assert!(parsed.spec.tty);
+        assert!(parsed.spec.open_stdin);
+        assert!(parsed.spec.auto_remove);
+        assert!(!parsed.detach);
+        assert_eq!(parsed.spec.ports[0].container_port, 80);
+        assert_eq!(parsed.spec.ports[0].host_port, None);
+        assert_eq!(parsed.spec.env[0].to_string(), "TZ=UTC");
+        assert_eq!(
+            parsed.unsupported_flags,
+            [
+                "--log-driver json-file",
+                "--privileged",
+                "--cap-add=NET_ADMIN",
+                "-P"
+            ]
+        );
+        assert_eq!(parsed.spec.image, "alpine:3");
+        assert_eq!(parsed.spec.command, Some(vec!["sh".to_string()]));
+    }
+
+    #[test]
+    fn test_env_file_precedence() {
+        let parsed =
+            ParsedRunCommand::parse("docker run -e MODE=prod --env-file app.env redis", |_| {
+                Ok("# comment\nMODE=dev\nPORT=6379\nHOME\n".to_string())
+            })
+            .unwrap();
+
+        let env: Vec<String> = parsed.spec.env.iter().map(|var| var.to_string()).collect();
+        assert_eq!(env, ["MODE=prod", "PORT=6379"]);
+        assert_eq!(parsed.warnings.len(), 1);
+
+        let error = parse("docker run --env-file missing.env redis").unwrap_err();
+        assert!(error.contains("missing.env"));
+    }
+
+    #[test]
+    fn test_parse_errors_are_collected() {
+        let error = parse("docker run -p 99999:80 --memory lots --restart sometimes").unwrap_err();
+        assert_eq!(error.split("; ").count(), 4);
+
+        assert!(parse("docker ps -a").is_err());
+        assert!(parse("docker run --name").is_err());
+    }
+
+    #[test]
+    fn test_parse_port() {
+        let ports = parse_port("[::1]:8000-8001:80-81/udp").unwrap();
+        assert_eq!(ports.len(), 2);
+        assert_eq!(ports[1].host_ip.as_deref(), Some("::1"));
+        assert_eq!(ports[1].host_port, Some(8001));
+        assert_eq!(ports[1].container_port, 81);
+        assert_eq!(ports[1].protocol, Some(PortTypeEnum::Udp));
+
+        let ports = parse_port("127.0.0.1::5432").unwrap();
+        assert_eq!(ports[0].host_port, None);
+        assert_eq!(ports[0].host_ip.as_deref(), Some("127.0.0.1"));
+
+        assert!(parse_port("8000-8002:80-81").is_err());
+    }
+
+    #[test]
+    fn test_parse_volumes_and_mounts() {
+        let (mount, ignored) = parse_volume("C:\\data:/data:ro,z").unwrap();
+        assert_eq!(mount.source.as_deref(), Some("C:\\data"));
+        assert_eq!(mount.mount_type, MountPointType::Bind);
+        assert!(mount.read_only);
+        assert_eq!(ignored, ["z"]);
+
+        let (mount, _) = parse_volume("/var/lib/data").unwrap();
+        assert_eq!(mount.source, None);
+        assert_eq!(mount.mount_type, MountPointType::Volume);
+
+        let (mount, ignored) =
+            parse_mount("type=tmpfs,dst=/cache,tmpfs-size=64m,tmpfs-mode=1770").unwrap();
+        assert_eq!(mount.mount_type, MountPointType::Tmpfs);
+        assert_eq!(mount.tmpfs_size, Some(64 * 1024 * 1024));
+        assert_eq!(ignored, ["tmpfs-mode=1770"]);
+
+        let (mount, _) = parse_mount("type=bind,src=/etc/app,target=/config,readonly").unwrap();
+        assert!(mount.read_only);
+    }
+
+    #[test]
+    fn test_split_command_line() {
+        let words = split_command_line(
+            "docker run \\\n  -e \"GREETING=say \\\"hi\\\"\" \\\r\n  alpine echo 'a b' c\\ d",
+        )
+        .unwrap();
+        assert_eq!(
+            words,
+            [
+                "docker",
+                "run",
+                "-e",
+                "GREETING=say \"hi\"",
+                "alpine",
+                "echo",
+                "a b",
+                "c d"
+            ]
+        );
+        assert!(split_command_line("docker run 'alpine").is_err());
+    }
+
+    #[test]
+    fn test_parse_bytes() {
+        assert_eq!(parse_bytes("1024").unwrap(), 1024);
+        assert_eq!(parse_bytes("1.5g").unwrap(), 1536 * 1024 * 1024);
+        assert_eq!(parse_bytes("256MB").unwrap(), 256 * 1024 * 1024);
+        assert!(parse_bytes("12x").is_err());
+    }
+}
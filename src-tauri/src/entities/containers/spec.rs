@@ -0,0 +1,889 @@
+use crate::entities::containers::{MountPointType, PortTypeEnum};
+use bollard::container::Config;
+use bollard::models::{
+    EndpointSettings as BollardEndpointSettings, HostConfig, Mount, MountTmpfsOptions,
+    MountTypeEnum, PortBinding as BollardPortBinding, PortMap,
+    RestartPolicy as BollardRestartPolicy, RestartPolicyNameEnum,
+};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Minimum memory limit accepted by the Docker engine (6 MiB).
+const MIN_MEMORY_LIMIT: i64 = 6 * 1024 * 1024;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+pub enum RestartPolicyName {
+    #[serde(rename = "no")]
+    #[default]
+    No,
+
+    #[serde(rename = "always")]
+    Always,
+
+    #[serde(rename = "unless-stopped")]
+    UnlessStopped,
+
+    #[serde(rename = "on-failure")]
+    OnFailure,
+}
+
+impl ::std::fmt::Display for RestartPolicyName {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        match *self {
+            RestartPolicyName::No => write!(f, "no"),
+            RestartPolicyName::Always => write!(f, "always"),
+            RestartPolicyName::UnlessStopped => write!(f, "unless-stopped"),
+            RestartPolicyName::OnFailure => write!(f, "on-failure"),
+        }
+    }
+}
+
+impl ::std::str::FromStr for RestartPolicyName {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "" | "no" => Ok(RestartPolicyName::No),
+            "always" => Ok(RestartPolicyName::Always),
+            "unless-stopped" => Ok(RestartPolicyName::UnlessStopped),
+            "on-failure" => Ok(RestartPolicyName::OnFailure),
+            x => Err(format!("Invalid restart policy: {}", x)),
+        }
+    }
+}
+
+/// The behavior to apply when the container exits.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RestartPolicy {
+    pub name: RestartPolicyName,
+
+    /// If `on-failure` is used, the number of times to retry before giving up.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maximum_retry_count: Option<i64>,
+}
+
+impl From<BollardRestartPolicy> for RestartPolicy {
+    fn from(policy: BollardRestartPolicy) -> Self {
+        let name = match policy.name {
+            Some(RestartPolicyNameEnum::ALWAYS) => RestartPolicyName::Always,
+            Some(RestartPolicyNameEnum::UNLESS_STOPPED) => RestartPolicyName::UnlessStopped,
+            Some(RestartPolicyNameEnum::ON_FAILURE) => RestartPolicyName::OnFailure,
+            Some(RestartPolicyNameEnum::NO) | Some(RestartPolicyNameEnum::EMPTY) | None => {
+                RestartPolicyName::No
+            }
+        };
+
+        RestartPolicy {
+            name,
+            maximum_retry_count: policy.maximum_retry_count.filter(|count| *count > 0),
+        }
+    }
+}
+
+impl From<&RestartPolicy> for BollardRestartPolicy {
+    fn from(policy: &RestartPolicy) -> Self {
+        let name = match policy.name {
+            RestartPolicyName::No => RestartPolicyNameEnum::NO,
+            RestartPolicyName::Always => RestartPolicyNameEnum::ALWAYS,
+            RestartPolicyName::UnlessStopped => RestartPolicyNameEnum::UNLESS_STOPPED,
+            RestartPolicyName::OnFailure => RestartPolicyNameEnum::ON_FAILURE,
+        };
+
+        BollardRestartPolicy {
+            name: Some(name),
+            maximum_retry_count: policy.maximum_retry_count,
+        }
+    }
+}
+
+impl RestartPolicy {
+    pub fn validate(&self) -> Result<(), String> {
+        match (self.name, self.maximum_retry_count) {
+            (RestartPolicyName::OnFailure, Some(count)) if count < 0 => {
+                Err("Maximum retry count cannot be negative".to_string())
+            }
+            (RestartPolicyName::OnFailure, _) | (_, None) => Ok(()),
+            (name, Some(_)) => Err(format!(
+                "Maximum retry count can only be used with the on-failure restart policy, not {}",
+                name
+            )),
+        }
+    }
+}
+
+/// An environment variable passed to the container.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct EnvVar {
+    pub key: String,
+    pub value: String,
+}
+
+impl ::std::fmt::Display for EnvVar {
+    fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+        write!(f, "{}={}", self.key, self.value)
+    }
+}
+
+/// A container port published on the host.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PortBindingSpec {
+    /// Port inside the container.
+    pub container_port: u16,
+
+    /// Protocol of the port, `tcp` if not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub protocol: Option<PortTypeEnum>,
+
+    /// Host IP to bind to. All interfaces if not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host_ip: Option<String>,
+
+    /// Port on the host. An ephemeral port is chosen by the engine if not set.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub host_port: Option<u16>,
+}
+
+impl PortBindingSpec {
+    pub fn protocol_str(&self) -> &'static str {
+        match self.protocol {
+            Some(PortTypeEnum::Udp) => "udp",
+            Some(PortTypeEnum::Sctp) => "sctp",
+            Some(PortTypeEnum::Tcp) | Some(PortTypeEnum::Empty) | None => "tcp",
+        }
+    }
+
+    /// Key used by the engine for this port, e.g. `80/tcp`.
+    pub fn port_key(&self) -> String {
+        format!("{}/{}", self.container_port, self.protocol_str())
+    }
+}
+
+/// A bind mount, named/anonymous volume or tmpfs mount.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MountSpec {
+    /// One of `bind`, `volume` or `tmpfs`.
+    pub mount_type: MountPointType,
+
+    /// Host path for bind mounts, volume name for volumes. Empty for anonymous volumes and tmpfs.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+
+    /// Absolute path inside the container.
+    pub target: String,
+
+    #[serde(default)]
+    pub read_only: bool,
+
+    /// Size of a tmpfs mount in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tmpfs_size: Option<i64>,
+}
+
+impl From<&MountSpec> for Mount {
+    fn from(spec: &MountSpec) -> Self {
+        let typ = match spec.mount_type {
+            MountPointType::Bind => MountTypeEnum::BIND,
+            MountPointType::Tmpfs => MountTypeEnum::TMPFS,
+            MountPointType::Npipe => MountTypeEnum::NPIPE,
+            MountPointType::Cluster => MountTypeEnum::CLUSTER,
+            MountPointType::Volume | MountPointType::Image | MountPointType::Empty => {
+                MountTypeEnum::VOLUME
+            }
+        };
+
+        Mount {
+            target: Some(spec.target.clone()),
+            source: spec.source.clone().filter(|source| !source.is_empty()),
+            typ: Some(typ),
+            read_only: Some(spec.read_only),
+            tmpfs_options: spec.tmpfs_size.map(|size_bytes| MountTmpfsOptions {
+                size_bytes: Some(size_bytes),
+                ..Default::default()
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+/// A network the container is attached to.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct NetworkAttachment {
+    pub name: String,
+
+    /// Additional DNS names for the container on this network.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub aliases: Vec<String>,
+}
+
+impl From<&NetworkAttachment> for BollardEndpointSettings {
+    fn from(attachment: &NetworkAttachment) -> Self {
+        BollardEndpointSettings {
+            aliases: (!attachment.aliases.is_empty()).then(|| attachment.aliases.clone()),
+            ..Default::default()
+        }
+    }
+}
+
+/// CPU, memory and process limits of a container.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ResourceLimits {
+    /// Number of CPUs, e.g. `1.5`. Converted to `NanoCpus`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpus: Option<f64>,
+
+    /// Relative CPU weight versus other containers.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_shares: Option<i64>,
+
+    /// CPU CFS period in microseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_period: Option<i64>,
+
+    /// Microseconds of CPU time the container can get in a CPU period.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpu_quota: Option<i64>,
+
+    /// CPUs in which to allow execution, e.g. `0-3` or `0,1`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cpuset_cpus: Option<String>,
+
+    /// Memory limit in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory: Option<i64>,
+
+    /// Memory soft limit in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_reservation: Option<i64>,
+
+    /// Total memory limit (memory + swap) in bytes. `-1` enables unlimited swap.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub memory_swap: Option<i64>,
+
+    /// Maximum number of processes. `-1` or `0` for unlimited.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pids_limit: Option<i64>,
+}
+
+impl ResourceLimits {
+    pub fn validate(&self) -> Vec<String> {
+        let mut errors = Vec::new();
+
+        if let Some(cpus) = self.cpus {
+            if !cpus.is_finite() || cpus <= 0.0 {
+                errors.push("CPU limit must be greater than 0".to_string());
+            }
+        }
+        if matches!(self.cpu_shares, Some(shares) if shares < 0) {
+            errors.push("CPU shares cannot be negative".to_string());
+        }
+        if matches!(self.cpu_period, Some(period) if !(1000..=1_000_000).contains(&period)) {
+            errors.push("CPU period must be between 1000 and 1000000 microseconds".to_string());
+        }
+        if matches!(self.cpu_quota, Some(quota) if quota != -1 && quota < 1000) {
+            errors.push("CPU quota must be at least 1000 microseconds".to_string());
+        }
+        if let Some(cpuset) = &self.cpuset_cpus {
+            let valid = !cpuset.is_empty()
+                && cpuset
+                    .chars()
+                    .all(|c| c.is_ascii_digit() || c == ',' || c == '-');
+            if !valid {
+                errors.push(format!("Invalid cpuset: {}", cpuset));
+            }
+        }
+        if matches!(self.memory, Some(memory) if memory != 0 && memory < MIN_MEMORY_LIMIT) {
+            errors.push("Memory limit must be at least 6MB".to_string());
+        }
+        if matches!(self.memory_reservation, Some(reservation) if reservation < 0) {
+            errors.push("Memory reservation cannot be negative".to_string());
+        }
+        if let (Some(reservation), Some(memory)) = (self.memory_reservation, self.memory) {
+            if memory > 0 && reservation > memory {
+                errors.push("Memory reservation must be lower than the memory limit".to_string());
+            }
+        }
+        if let Some(swap) = self.memory_swap {
+            if swap != -1 && swap != 0 {
+                match self.memory {
+                    Some(memory) if memory > 0 && swap < memory => errors.push(
+                        "Memory + swap limit must be larger than the memory limit".to_string(),
+                    ),
+                    Some(memory) if memory > 0 => {}
+                    _ => errors
+                        .push("Memory + swap limit requires a memory limit to be set".to_string()),
+                }
+            }
+        }
+
+        errors
+    }
+
+    /// Apply the limits that are set to the given host config.
+    pub fn apply_to(&self, host_config: &mut HostConfig) {
+        if let Some(cpus) = self.cpus {
+            host_config.nano_cpus = Some((cpus * 1_000_000_000.0).round() as i64);
+        }
+        if self.cpu_shares.is_some() {
+            host_config.cpu_shares = self.cpu_shares;
+        }
+        if self.cpu_period.is_some() {
+            host_config.cpu_period = self.cpu_period;
+        }
+        if self.cpu_quota.is_some() {
+            host_config.cpu_quota = self.cpu_quota;
+        }
+        if self.cpuset_cpus.is_some() {
+            host_config.cpuset_cpus = self.cpuset_cpus.clone();
+        }
+        if self.memory.is_some() {
+            host_config.memory = self.memory;
+        }
+        if self.memory_reservation.is_some() {
+            host_config.memory_reservation = self.memory_reservation;
+        }
+        if self.memory_swap.is_some() {
+            host_config.memory_swap = self.memory_swap;
+        }
+        if self.pids_limit.is_some() {
+            host_config.pids_limit = self.pids_limit;
+        }
+    }
+}
+
+impl From<&HostConfig> for ResourceLimits {
+    /// Read the limits of an existing container. The engine reports `0` for unset limits.
+    fn from(host_config: &HostConfig) -> Self {
+        let set = |value: Option<i64>| value.filter(|value| *value != 0);
+
+        ResourceLimits {
+            cpus: set(host_config.nano_cpus).map(|nano_cpus| nano_cpus as f64 / 1_000_000_000.0),
+            cpu_shares: set(host_config.cpu_shares),
+            cpu_period: set(host_config.cpu_period),
+            cpu_quota: set(host_config.cpu_quota),
+            cpuset_cpus: host_config
+                .cpuset_cpus
+                .clone()
+                .filter(|cpuset| !cpuset.is_empty()),
+            memory: set(host_config.memory),
+            memory_reservation: set(host_config.memory_reservation),
+            memory_swap: set(host_config.memory_swap),
+            pids_limit: set(host_config.pids_limit),
+        }
+    }
+}
+
+/// Everything needed to create a container from an image.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContainerSpec {
+    /// Image reference, e.g. `nginx:1.27` or `ghcr.io/org/app@sha256:...`.
+    pub image: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+
+    /// Overrides the image `CMD`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub command: Option<Vec<String>>,
+
+    /// Overrides the image `ENTRYPOINT`. An empty list resets it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entrypoint: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub hostname: Option<String>,
+
+    pub env: Vec<EnvVar>,
+    pub ports: Vec<PortBindingSpec>,
+    pub mounts: Vec<MountSpec>,
+
+    /// Networks to attach to. The first one is used as the primary network.
+    pub networks: Vec<NetworkAttachment>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart_policy: Option<RestartPolicy>,
+
+    pub labels: HashMap<String, String>,
+    pub resources: ResourceLimits,
+
+    /// Allocate a pseudo-TTY.
+    pub tty: bool,
+
+    /// Keep stdin open even if not attached.
+    pub open_stdin: bool,
+
+    /// Remove the container when it exits.
+    pub auto_remove: bool,
+}
+
+impl ContainerSpec {
+    /// Validate the spec before sending it to the engine.
+    pub fn validate(&self) -> Result<(), String> {
+        let mut errors = validate_image(&self.image);
+
+        if let Some(name) = &self.name {
+            if !is_valid_container_name(name) {
+                errors.push(format!(
+                    "Invalid container name: {}. Only [a-zA-Z0-9][a-zA-Z0-9_.-] are allowed",
+                    name
+                ));
+            }
+        }
+
+        errors.extend(validate_env(&self.env));
+        errors.extend(validate_ports(&self.ports));
+        errors.extend(validate_mounts(&self.mounts));
+
+        let mut used_networks = HashSet::new();
+        for network in &self.networks {
+            if network.name.trim().is_empty() {
+                errors.push("Network name cannot be empty".to_string());
+            } else if !used_networks.insert(network.name.as_str()) {
+                errors.push(format!("Network {} is listed more than once", network.name));
+            }
+        }
+        if self.networks.len() > 1
+            && self
+                .networks
+                .iter()
+                .any(|n| matches!(n.name.as_str(), "host" | "none"))
+        {
+            errors
+                .push("host and none networks cannot be combined with other networks".to_string());
+        }
+
+        if let Some(policy) = &self.restart_policy {
+            if let Err(e) = policy.validate() {
+                errors.push(e);
+            }
+            if self.auto_remove && policy.name != RestartPolicyName::No {
+                errors.push("Auto-remove cannot be combined with a restart policy".to_string());
+            }
+        }
+
+        if self.labels.keys().any(|key| key.trim().is_empty()) {
+            errors.push("Label name cannot be empty".to_string());
+        }
+
+        errors.extend(self.resources.validate());
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+
+    /// Build the bollard create config. Only the primary network is set here,
+    /// any additional networks have to be connected after the container is created.
+    pub fn to_config(&self) -> Config<String> {
+        let mut exposed_ports: HashMap<String, HashMap<(), ()>> = HashMap::new();
+        let mut port_bindings: PortMap = HashMap::new();
+        for port in &self.ports {
+            let key = port.port_key();
+            exposed_ports.insert(key.clone(), HashMap::new());
+            port_bindings
+                .entry(key)
+                .or_insert_with(|| Some(Vec::new()))
+                .get_or_insert_with(Vec::new)
+                .push(BollardPortBinding {
+                    host_ip: port.host_ip.clone(),
+                    host_port: port.host_port.map(|p| p.to_string()),
+                });
+        }
+
+        let primary_network = self.networks.first();
+
+        let mut host_config = HostConfig {
+            port_bindings: (!port_bindings.is_empty()).then_some(port_bindings),
+            mounts: (!self.mounts.is_empty())
+                .then(|| self.mounts.iter().map(Mount::from).collect()),
+            network_mode: primary_network.map(|network| network.name.clone()),
+            restart_policy: self.restart_policy.as_ref().map(|policy| policy.into()),
+            auto_remove: self.auto_remove.then_some(true),
+            ..Default::default()
+        };
+        self.resources.apply_to(&mut host_config);
+
+        Config {
+            image: Some(self.image.clone()),
+            hostname: self.hostname.clone(),
+            user: self.user.clone(),
+            working_dir: self.working_dir.clone(),
+            cmd: self.command.clone(),
+            entrypoint: self.entrypoint.clone(),
+            env: (!self.env.is_empty())
+                .then(|| self.env.iter().map(|var| var.to_string()).collect()),
+            exposed_ports: (!exposed_ports.is_empty()).then_some(exposed_ports),
+            labels: (!self.labels.is_empty()).then(|| self.labels.clone()),
+            tty: Some(self.tty),
+            open_stdin: Some(self.open_stdin),
+            host_config: Some(host_config),
+            networking_config: primary_network.map(|network| {
+                bollard::container::NetworkingConfig {
+                    endpoints_config: HashMap::from([(network.name.clone(), network.into())]),
+                }
+            }),
+            ..Default::default()
+        }
+    }
+}
+
+/// Problems with an image reference.
+pub(crate) fn validate_image(image: &str) -> Vec<String> {
+    let mut errors = Vec::new();
+    if image.trim().is_empty() {
+        errors.push("Image cannot be empty".to_string());
+    } else if image.chars().any(char::is_whitespace) {
+        errors.push(format!("Invalid image reference: {}", image));
+    }
+    errors
+}
+
+/// Problems with environment variable names.
+pub(crate) fn validate_env(env: &[EnvVar]) -> Vec<String> {
+    let mut errors = Vec::new();
+    for var in env {
+        if var.key.is_empty() {
+            errors.push("Environment variable name cannot be empty".to_string());
+        } else if var.key.contains('=') || var.key.chars().any(char::is_whitespace) {
+            errors.push(format!("Invalid environment variable name: {}", var.key));
+        }
+    }
+    errors
+}
+
+/// Problems with published ports, such as a host port used twice.
+pub(crate) fn validate_ports(ports: &[PortBindingSpec]) -> Vec<String> {
+    let mut errors = Vec::new();
+    let mut used_host_ports = HashSet::new();
+    for port in ports {
+        if port.container_port == 0 {
+            errors.push("Container port cannot be 0".to_string());
+        }
+        if let Some(host_port) = port.host_port {
+            let host_ip = port.host_ip.as_deref().unwrap_or("0.0.0.0");
+            if !used_host_ports.insert((host_ip, host_port, port.protocol_str())) {
+                errors.push(format!(
+                    "Host port {} is published more than once",
+                    host_port
+                ));
+            }
+        }
+    }
+    errors
+}
+
+/// Problems with mounts, such as relative or duplicate targets.
+pub(crate) fn validate_mounts(mounts: &[MountSpec]) -> Vec<String> {
+    let mut errors = Vec::new();
+    let mut used_targets = HashSet::new();
+    for mount in mounts {
+        if !mount.target.starts_with('/') {
+            errors.push(format!(
+                "Mount target must be an absolute path: {}",
+                mount.target
+            ));
+        }
+        if !used_targets.insert(mount.target.as_str()) {
+            errors.push(format!("Duplicate mount target: {}", mount.target));
+        }
+
+        let source = mount.source.as_deref().unwrap_or("");
+        match mount.mount_type {
+            MountPointType::Bind if source.is_empty() => {
+                errors.push(format!("Bind mount for {} needs a host path", mount.target))
+            }
+            MountPointType::Bind if !is_absolute_host_path(source) => errors.push(format!(
+                "Bind mount source must be an absolute path: {}",
+                source
+            )),
+            MountPointType::Tmpfs if !source.is_empty() => errors.push(format!(
+                "Tmpfs mount for {} cannot have a source",
+                mount.target
+            )),
+            MountPointType::Bind | MountPointType::Volume | MountPointType::Tmpfs => {}
+            other => errors.push(format!("Unsupported mount type: {}", other)),
+        }
+
+        if mount.tmpfs_size.is_some() && mount.mount_type != MountPointType::Tmpfs {
+            errors.push(format!(
+                "Size can only be set on tmpfs mounts ({})",
+                mount.target
+            ));
+        }
+    }
+    errors
+}
+
+/// Container names follow the engine rule `[a-zA-Z0-9][a-zA-Z0-9_.-]+`.
+pub fn is_valid_container_name(name: &str) -> bool {
+    let name = name.strip_prefix('/').unwrap_or(name);
+    if name.len() < 2 {
+        return false;
+    }
+
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) if first.is_ascii_alphanumeric() => {
+            chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '-'))
+        }
+        _ => false,
+    }
+}
+
+/// Accept both unix style and Windows drive paths for bind mounts.
+fn is_absolute_host_path(path: &str) -> bool {
+    path.starts_with('/')
+        || path.starts_with("\\\\")
+        || (path.len() >= 3
+            && path.as_bytes()[0].is_ascii_alphabetic()
+            && path.as_bytes()[1] == b':'
+            && matches!(path.as_bytes()[2], b'\\' | b'/'))
+}
+
+/// Result of a create request.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateContainerResult {
+    pub id: String,
+    pub warnings: Vec<String>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn spec() -> ContainerSpec {
+        ContainerSpec {
+            image: "nginx:1.27".to_string(),
+            name: Some("web".to_string()),
+            env: vec![EnvVar {
+                key: "MODE".to_string(),
+                value: "prod".to_string(),
+            }],
+            ports: vec![
+                PortBindingSpec {
+                    container_port: 80,
+                    protocol: None,
+                    host_ip: Some("127.0.0.1".to_string()),
+                    host_port: Some(8080),
+                },
+                PortBindingSpec {
+                    container_port: 80,
+                    protocol: None,
+                    host_ip: None,
+                    host_port: None,
+                },
+                PortBindingSpec {
+                    container_port: 53,
+                    protocol: Some(PortTypeEnum::Udp),
+                    host_ip: None,
+                    host_port: Some(5353),
+                },
+            ],
+            mounts: vec![
+                MountSpec {
+                    mount_type: MountPointType::Bind,
+                    source: Some("/srv/www".to_string()),
+                    target: "/usr/share/nginx/html".to_string(),
+                    read_only: true,
+                    tmpfs_size: None,
+                },
+                MountSpec {
+                    mount_type: MountPointType::Tmpfs,
+                    source: None,
+                    target: "/tmp".to_string(),
+                    read_only: false,
+                    tmpfs_size: Some(64 * 1024 * 1024),
+                },
+            ],
+            networks: vec![
+                NetworkAttachment {
+                    name: "frontend".to_string(),
+                    aliases: vec!["www".to_string()],
+                },
+                NetworkAttachment {
+                    name: "backend".to_string(),
+                    aliases: Vec::new(),
+                },
+            ],
+            restart_policy: Some(RestartPolicy {
+                name: RestartPolicyName::OnFailure,
+                maximum_retry_count: Some(3),
+            }),
+            resources: ResourceLimits {
+                cpus: Some(1.5),
+                memory: Some(512 * 1024 * 1024),
+                ..Default::default()
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_valid_spec() {
+        assert_eq!(spec().validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_reports_all_problems() {
+        let mut spec = spec();
+        spec.image = String::new();
+        spec.name = Some("-web".to_string());
+        spec.ports[1].host_ip = Some("127.0.0.1".to_string());
+        spec.ports[1].host_port = Some(8080);
+        spec.mounts[1].source = Some("/srv".to_string());
+        spec.networks[1].name = "frontend".to_string();
+
+        assert_eq!(
+            spec.validate().unwrap_err(),
+            "Image cannot be empty; \
+             Invalid container name: -web. Only [a-zA-Z0-9][a-zA-Z0-9_.-] are allowed; \
+             Host port 8080 is published more than once; \
+             Tmpfs mount for /tmp cannot have a source; \
+             Network frontend is listed more than once"
+        );
+    }
+
+    #[test]
+    fn test_validate_errors() {
+        let errors = |change: fn(&mut ContainerSpec)| {
+            let mut spec = spec();
+            change(&mut spec);
+            spec.validate().unwrap_err()
+        };
+
+        assert_eq!(
+            errors(|spec| spec.image = "nginx latest".to_string()),
+            "Invalid image reference: nginx latest"
+        );
+        assert_eq!(
+            errors(|spec| spec.env[0].key = "A=B".to_string()),
+            "Invalid environment variable name: A=B"
+        );
+        assert_eq!(
+            errors(|spec| spec.ports[0].container_port = 0),
+            "Container port cannot be 0"
+        );
+        assert_eq!(
+            errors(|spec| spec.mounts[0].source = Some("www".to_string())),
+            "Bind mount source must be an absolute path: www"
+        );
+        assert_eq!(
+            errors(|spec| spec.mounts[0].tmpfs_size = Some(1)),
+            "Size can only be set on tmpfs mounts (/usr/share/nginx/html)"
+        );
+        assert_eq!(
+            errors(|spec| spec.networks[1].name = "host".to_string()),
+            "host and none networks cannot be combined with other networks"
+        );
+        assert_eq!(
+            errors(|spec| spec.auto_remove = true),
+            "Auto-remove cannot be combined with a restart policy"
+        );
+        assert_eq!(
+            errors(|spec| spec.restart_policy.as_mut().unwrap().name = RestartPolicyName::Always),
+            "Maximum retry count can only be used with the on-failure restart policy, not always"
+        );
+    }
+
+    #[test]
+    fn test_resource_limits_validate() {
+        let limits = ResourceLimits {
+            cpus: Some(0.0),
+            cpu_period: Some(10),
+            cpuset_cpus: Some("0-3a".to_string()),
+            memory: Some(1024),
+            memory_swap: Some(512),
+            ..Default::default()
+        };
+        assert_eq!(
+            limits.validate(),
+            vec![
+                "CPU limit must be greater than 0",
+                "CPU period must be between 1000 and 1000000 microseconds",
+                "Invalid cpuset: 0-3a",
+                "Memory limit must be at least 6MB",
+                "Memory + swap limit must be larger than the memory limit",
+            ]
+        );
+
+        let limits = ResourceLimits {
+            memory_swap: Some(1024 * 1024 * 1024),
+            ..Default::default()
+        };
+        assert_eq!(
+            limits.validate(),
+            vec!["Memory + swap limit requires a memory limit to be set"]
+        );
+        assert!(ResourceLimits {
+            memory_swap: Some(-1),
+            cpu_quota: Some(-1),
+            ..Default::default()
+        }
+        .validate()
+        .is_empty());
+    }
+
+    #[test]
+    fn test_to_config() {
+        let config = spec().to_config();
+        let host_config = config.host_config.unwrap();
+
+        let exposed_ports = config.exposed_ports.unwrap();
+        assert_eq!(exposed_ports.len(), 2);
+        assert!(exposed_ports.contains_key("80/tcp"));
+        assert!(exposed_ports.contains_key("53/udp"));
+
+        let port_bindings = host_config.port_bindings.unwrap();
+        let web = port_bindings["80/tcp"].as_ref().unwrap();
+        assert_eq!(web.len(), 2);
+        assert_eq!(web[0].host_ip.as_deref(), Some("127.0.0.1"));
+        assert_eq!(web[0].host_port.as_deref(), Some("8080"));
+        assert_eq!(web[1].host_port, None);
+        assert_eq!(
+            port_bindings["53/udp"].as_ref().unwrap()[0]
+                .host_port
+                .as_deref(),
+            Some("5353")
+        );
+
+        let mounts = host_config.mounts.unwrap();
+        assert_eq!(mounts[0].typ, Some(MountTypeEnum::BIND));
+        assert_eq!(mounts[0].source.as_deref(), Some("/srv/www"));
+        assert_eq!(mounts[0].read_only, Some(true));
+        assert_eq!(mounts[1].typ, Some(MountTypeEnum::TMPFS));
+        assert_eq!(mounts[1].source, None);
+        assert_eq!(
+            mounts[1]
+                .tmpfs_options
+                .as_ref()
+                .and_then(|options| options.size_bytes),
+            Some(64 * 1024 * 1024)
+        );
+
+        // Only the primary network is set, the others are connected afterwards
+        assert_eq!(host_config.network_mode.as_deref(), Some("frontend"));
+        let endpoints = config.networking_config.unwrap().endpoints_config;
+        assert_eq!(endpoints.len(), 1);
+        assert_eq!(endpoints["frontend"].aliases, Some(vec!["www".to_string()]));
+
+        let restart_policy = host_config.restart_policy.unwrap();
+        assert_eq!(restart_policy.name, Some(RestartPolicyNameEnum::ON_FAILURE));
+        assert_eq!(restart_policy.maximum_retry_count, Some(3));
+
+        assert_eq!(host_config.nano_cpus, Some(1_500_000_000));
+        assert_eq!(host_config.memory, Some(512 * 1024 * 1024));
+        assert_eq!(host_config.memory_swap, None);
+        assert_eq!(host_config.auto_remove, None);
+
+        assert_eq!(config.env, Some(vec!["MODE=prod".to_string()]));
+        assert_eq!(config.labels, None);
+    }
+}
@@ -0,0 +1,64 @@
+use serde::{Deserialize, Serialize};
+
+/// Options used when opening a stats subscription.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StatsSubscriptionOptions {
+    /// Containers to watch. When empty, all running containers are watched,
+    /// including the ones started after subscribing.
+    pub container_ids: Vec<String>,
+
+    /// How often stats are pushed to the frontend, in milliseconds.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub interval_ms: Option<u64>,
+}
+
+/// Resource usage of a container, computed from the raw cgroup counters.
+/// Rates are per second and averaged since the previous sample.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContainerStats {
+    pub container_id: String,
+    pub name: String,
+
+    /// Time the sample was taken by the engine, RFC3339.
+    pub read_at: String,
+
+    /// CPU usage in percent, where 100% is one fully used core.
+    pub cpu_percent: f64,
+    pub online_cpus: u64,
+
+    /// Memory in use, without the reclaimable page cache.
+    pub memory_usage: u64,
+    pub memory_limit: u64,
+    pub memory_percent: f64,
+
+    pub network_rx_bytes: u64,
+    pub network_tx_bytes: u64,
+    pub network_rx_rate: f64,
+    pub network_tx_rate: f64,
+
+    pub block_read_bytes: u64,
+    pub block_write_bytes: u64,
+    pub block_read_rate: f64,
+    pub block_write_rate: f64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pids: Option<u64>,
+}
+
+/// Stats of all containers of a subscription that changed since the previous batch.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsBatch {
+    pub subscription_id: String,
+    pub stats: Vec<ContainerStats>,
+}
+
+/// Emitted when a container of a subscription stops or its stats stream fails.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StatsStreamEnded {
+    pub subscription_id: String,
+    pub container_id: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
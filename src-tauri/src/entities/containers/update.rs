@@ -0,0 +1,182 @@
+use crate::entities::containers::{ResourceLimits, RestartPolicy};
+use crate::entities::DockerInfo;
+use bollard::container::UpdateContainerOptions;
+use serde::{Deserialize, Serialize};
+
+/// Changes applied to a running or stopped container without recreating it.
+/// Only the fields that are set are changed.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ContainerUpdate {
+    pub resources: ResourceLimits,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub restart_policy: Option<RestartPolicy>,
+}
+
+impl ContainerUpdate {
+    pub fn is_empty(&self) -> bool {
+        self.resources == ResourceLimits::default() && self.restart_policy.is_none()
+    }
+
+    /// Limits the container ends up with once the update is applied on top of `current`.
+    pub fn merged_limits(&self, current: &ResourceLimits) -> ResourceLimits {
+        let changes = &self.resources;
+        ResourceLimits {
+            cpus: changes.cpus.or(current.cpus),
+            cpu_shares: changes.cpu_shares.or(current.cpu_shares),
+            cpu_period: changes.cpu_period.or(current.cpu_period),
+            cpu_quota: changes.cpu_quota.or(current.cpu_quota),
+            cpuset_cpus: changes
+                .cpuset_cpus
+                .clone()
+                .or_else(|| current.cpuset_cpus.clone()),
+            memory: changes.memory.or(current.memory),
+            memory_reservation: changes.memory_reservation.or(current.memory_reservation),
+            memory_swap: changes.memory_swap.or(current.memory_swap),
+            pids_limit: changes.pids_limit.or(current.pids_limit),
+        }
+    }
+
+    /// Validate the update against the current limits of the container and the
+    /// cgroup features of the engine.
+    pub fn validate(&self, current: &ResourceLimits, info: &DockerInfo) -> Result<(), String> {
+        if self.is_empty() {
+            return Err("Nothing to update".to_string());
+        }
+
+        let changes = &self.resources;
+        let unsupported = |supported: Option<bool>| supported == Some(false);
+        let mut errors = Vec::new();
+
+        if (changes.memory.is_some() || changes.memory_reservation.is_some())
+            && unsupported(info.memory_limit)
+        {
+            errors.push("The engine does not support memory limits".to_string());
+        }
+        if changes.memory_swap.is_some() && unsupported(info.swap_limit) {
+            errors.push("The engine does not support swap limits".to_string());
+        }
+        if changes.cpu_shares.is_some() && unsupported(info.cpu_shares) {
+            errors.push("The engine does not support CPU shares".to_string());
+        }
+        if changes.cpu_period.is_some() && unsupported(info.cpu_cfs_period) {
+            errors.push("The engine does not support CPU CFS period".to_string());
+        }
+        if (changes.cpu_quota.is_some() || changes.cpus.is_some())
+            && unsupported(info.cpu_cfs_quota)
+        {
+            errors.push("The engine does not support CPU CFS quota".to_string());
+        }
+        if changes.cpuset_cpus.is_some() && unsupported(info.cpu_set) {
+            errors.push("The engine does not support cpusets".to_string());
+        }
+        if changes.pids_limit.is_some() && unsupported(info.pids_limit) {
+            errors.push("The engine does not support PID limits".to_string());
+        }
+
+        let merged = self.merged_limits(current);
+        errors.extend(merged.validate());
+        if merged.cpus.is_some() && (merged.cpu_quota.is_some() || merged.cpu_period.is_some()) {
+            errors.push("CPU limit cannot be combined with CPU period or quota".to_string());
+        }
+
+        if let Some(policy) = &self.restart_policy {
+            if let Err(error) = policy.validate() {
+                errors.push(error);
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors.join("; "))
+        }
+    }
+
+    pub fn to_options(&self) -> UpdateContainerOptions<String> {
+        let changes = &self.resources;
+        UpdateContainerOptions {
+            nano_cpus: changes
+                .cpus
+                .map(|cpus| (cpus * 1_000_000_000.0).round() as i64),
+            cpu_shares: changes.cpu_shares.map(|shares| shares as isize),
+            cpu_period: changes.cpu_period,
+            cpu_quota: changes.cpu_quota,
+            cpuset_cpus: changes.cpuset_cpus.clone(),
+            memory: changes.memory,
+            memory_reservation: changes.memory_reservation,
+            memory_swap: changes.memory_swap,
+            pids_limit: changes.pids_limit,
+            restart_policy: self.restart_policy.as_ref().map(|policy| policy.into()),
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::entities::containers::RestartPolicyName;
+
+    const MIB: i64 = 1024 * 1024;
+
+    #[test]
+    fn test_swap_update_uses_current_memory_limit() {
+        let current = ResourceLimits {
+            memory: Some(256 * MIB),
+            ..Default::default()
+        };
+        let update = ContainerUpdate {
+            resources: ResourceLimits {
+                memory_swap: Some(512 * MIB),
+                ..Default::default()
+            },
+            restart_policy: None,
+        };
+
+        assert!(update.validate(&current, &DockerInfo::default()).is_ok());
+        assert!(update
+            .validate(&ResourceLimits::default(), &DockerInfo::default())
+            .is_err());
+    }
+
+    #[test]
+    fn test_update_checks_engine_capabilities() {
+        let info = DockerInfo {
+            swap_limit: Some(false),
+            ..Default::default()
+        };
+        let update = ContainerUpdate {
+            resources: ResourceLimits {
+                memory: Some(256 * MIB),
+                memory_swap: Some(512 * MIB),
+                ..Default::default()
+            },
+            restart_policy: None,
+        };
+
+        let error = update
+            .validate(&ResourceLimits::default(), &info)
+            .unwrap_err();
+        assert_eq!(error, "The engine does not support swap limits");
+    }
+
+    #[test]
+    fn test_restart_policy_only_update() {
+        let update = ContainerUpdate {
+            resources: ResourceLimits::default(),
+            restart_policy: Some(RestartPolicy {
+                name: RestartPolicyName::UnlessStopped,
+                maximum_retry_count: None,
+            }),
+        };
+
+        assert!(update
+            .validate(&ResourceLimits::default(), &DockerInfo::default())
+            .is_ok());
+        assert!(ContainerUpdate::default()
+            .validate(&ResourceLimits::default(), &DockerInfo::default())
+            .is_err());
+    }
+}
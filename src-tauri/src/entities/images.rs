@@ -1,18 +1,856 @@
+use crate::entities::containers::{ContainerEnvVar, HealthcheckConfig, MASKED_VALUE};
+use bollard::models::{HistoryResponseItem, ImageInspect, ImageSummary, ProgressDetail};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct Image {
     pub id: String,
+
+    /// Repository of the first tag or digest, `<untagged>@...` for images with neither.
     pub repository: Option<String>,
+
+    /// Tag of the first tag, not set for images only referenced by digest.
     pub tag: Option<String>,
+
+    /// Every `repository:tag` the image is known as.
+    pub repo_tags: Vec<String>,
+
+    /// Every `repository@sha256:...` the image is known as.
+    pub repo_digests: Vec<String>,
+
     pub image_id: String,
     pub created: i64,
     pub size: i64,
     pub in_use: bool,
 }
 
+impl Image {
+    pub fn new(image: &ImageSummary, in_use: bool) -> Self {
+        // Older engines report `<none>:<none>` and `<none>@<none>` for dangling images
+        let repo_tags: Vec<String> = image
+            .repo_tags
+            .iter()
+            .filter(|tag| !tag.starts_with("<none>"))
+            .cloned()
+            .collect();
+        let repo_digests: Vec<String> = image
+            .repo_digests
+            .iter()
+            .filter(|digest| !digest.starts_with("<none>"))
+            .cloned()
+            .collect();
+
+        let (repository, tag) = match (repo_tags.first(), repo_digests.first()) {
+            (Some(reference), _) => {
+                let (repository, tag) = split_reference(reference);
+                (repository.to_string(), tag.map(str::to_string))
+            }
+            (None, Some(digest)) => {
+                let repository = digest
+                    .split_once('@')
+                    .map_or(digest.as_str(), |(name, _)| name);
+                (repository.to_string(), None)
+            }
+            // Untagged images are named after their id rather than given a misleading tag
+            (None, None) => {
+                let id = image.id.strip_prefix("sha256:").unwrap_or(&image.id);
+                (format!("<untagged>@{}", id), None)
+            }
+        };
+
+        Image {
+            id: image.id.clone(),
+            repository: Some(repository),
+            tag,
+            repo_tags,
+            repo_digests,
+            image_id: image.id.clone(),
+            created: image.created,
+            size: image.size,
+            in_use,
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone)]
 pub struct PruneResult {
     pub images_deleted: Vec<String>,
     pub space_reclaimed: i64,
 }
+
+/// Everything the engine reports about a local image, with its layer history.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ImageDetails {
+    pub id: String,
+    pub repo_tags: Vec<String>,
+
+    /// `repository@sha256:...` references of the image in registries.
+    pub repo_digests: Vec<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub parent: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub author: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub docker_version: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub architecture: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub variant: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub os: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub os_version: Option<String>,
+
+    pub size: i64,
+    pub config: ImageConfigDetails,
+
+    /// Well-known `org.opencontainers.image.*` labels.
+    pub oci: OciLabels,
+
+    /// Digests of the uncompressed layers, from the base layer up.
+    pub layers: Vec<String>,
+
+    /// Steps that produced the image, newest first.
+    pub history: Vec<ImageHistoryEntry>,
+}
+
+impl From<ImageInspect> for ImageDetails {
+    fn from(image: ImageInspect) -> Self {
+        let config = ImageConfigDetails::from(image.config.unwrap_or_default());
+        ImageDetails {
+            id: image.id.unwrap_or_default(),
+            repo_tags: image.repo_tags.unwrap_or_default(),
+            repo_digests: image.repo_digests.unwrap_or_default(),
+            parent: image.parent.filter(|parent| !parent.is_empty()),
+            comment: image.comment.filter(|comment| !comment.is_empty()),
+            created: image.created.filter(|created| !created.is_empty()),
+            author: image.author.filter(|author| !author.is_empty()),
+            docker_version: image.docker_version.filter(|version| !version.is_empty()),
+            architecture: image.architecture,
+            variant: image.variant,
+            os: image.os,
+            os_version: image.os_version,
+            size: image.size.unwrap_or(0),
+            oci: OciLabels::from_labels(&config.labels),
+            config,
+            layers: image
+                .root_fs
+                .and_then(|root_fs| root_fs.layers)
+                .unwrap_or_default(),
+            history: Vec::new(),
+        }
+    }
+}
+
+impl ImageDetails {
+    /// Replace the values of environment variables that look like secrets.
+    pub fn mask_sensitive_env(&mut self) {
+        for var in &mut self.config.env {
+            if var.is_sensitive() {
+                var.value = MASKED_VALUE.to_string();
+                var.masked = true;
+            }
+        }
+    }
+}
+
+/// Defaults an image gives the containers created from it.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct ImageConfigDetails {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+
+    pub env: Vec<ContainerEnvVar>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub cmd: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub entrypoint: Option<Vec<String>>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub working_dir: Option<String>,
+
+    /// Exposed ports in the `80/tcp` form.
+    pub exposed_ports: Vec<String>,
+
+    pub volumes: Vec<String>,
+    pub labels: HashMap<String, String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub stop_signal: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub healthcheck: Option<HealthcheckConfig>,
+
+    /// Instructions run when the image is used as a base.
+    pub on_build: Vec<String>,
+}
+
+impl From<bollard::models::ImageConfig> for ImageConfigDetails {
+    fn from(config: bollard::models::ImageConfig) -> Self {
+        let mut exposed_ports: Vec<String> = config
+            .exposed_ports
+            .unwrap_or_default()
+            .into_keys()
+            .collect();
+        exposed_ports.sort();
+        let mut volumes: Vec<String> = config.volumes.unwrap_or_default().into_keys().collect();
+        volumes.sort();
+
+        ImageConfigDetails {
+            user: config.user.filter(|user| !user.is_empty()),
+            env: config
+                .env
+                .unwrap_or_default()
+                .iter()
+                .map(|entry| ContainerEnvVar::parse(entry))
+                .collect(),
+            cmd: config.cmd,
+            entrypoint: config.entrypoint,
+            working_dir: config.working_dir.filter(|dir| !dir.is_empty()),
+            exposed_ports,
+            volumes,
+            labels: config.labels.unwrap_or_default(),
+            stop_signal: config.stop_signal,
+            healthcheck: config.healthcheck.map(|healthcheck| healthcheck.into()),
+            on_build: config.on_build.unwrap_or_default(),
+        }
+    }
+}
+
+/// Annotations of the OCI image spec, read from the image labels.
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+pub struct OciLabels {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub version: Option<String>,
+
+    /// Source control revision the image was built from.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub revision: Option<String>,
+
+    /// URL of the source code.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+
+    /// SPDX license expression.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub licenses: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vendor: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub authors: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub created: Option<String>,
+}
+
+impl OciLabels {
+    pub fn from_labels(labels: &HashMap<String, String>) -> Self {
+        let label = |name: &str| {
+            labels
+                .get(&format!("org.opencontainers.image.{}", name))
+                .filter(|value| !value.is_empty())
+                .cloned()
+        };
+        OciLabels {
+            title: label("title"),
+            description: label("description"),
+            version: label("version"),
+            revision: label("revision"),
+            source: label("source"),
+            url: label("url"),
+            licenses: label("licenses"),
+            vendor: label("vendor"),
+            authors: label("authors"),
+            created: label("created"),
+        }
+    }
+}
+
+/// One step of the image history.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct ImageHistoryEntry {
+    /// Id of the intermediate image, only known for steps built locally.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+
+    /// Unix timestamp in seconds.
+    pub created: i64,
+
+    /// Command recorded by the builder.
+    pub created_by: String,
+
+    /// Dockerfile instruction of the step, e.g. `RUN apt-get update`.
+    pub instruction: String,
+
+    pub tags: Vec<String>,
+
+    /// Size of the layer the step added, 0 for steps that only change metadata.
+    pub size: i64,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub comment: Option<String>,
+}
+
+impl From<HistoryResponseItem> for ImageHistoryEntry {
+    fn from(item: HistoryResponseItem) -> Self {
+        ImageHistoryEntry {
+            id: Some(item.id).filter(|id| id != "<missing>" && !id.is_empty()),
+            created: item.created,
+            instruction: history_instruction(&item.created_by),
+            created_by: item.created_by,
+            tags: item.tags,
+            size: item.size,
+            comment: Some(item.comment).filter(|comment| !comment.is_empty()),
+        }
+    }
+}
+
+/// Turn the command the classic builder or BuildKit recorded back into the Dockerfile instruction.
+fn history_instruction(created_by: &str) -> String {
+    let command = created_by.trim();
+    let command = command
+        .strip_suffix("# buildkit")
+        .map(str::trim_end)
+        .unwrap_or(command);
+
+    if let Some(instruction) = command.strip_prefix("/bin/sh -c #(nop)") {
+        return instruction.trim().to_string();
+    }
+    if let Some(script) = command.strip_prefix("/bin/sh -c ") {
+        return format!("RUN {}", script.trim());
+    }
+    // BuildKit records `RUN /bin/sh -c ...` and `|2 ARG=1 ... /bin/sh -c ...` when build args are set
+    if let Some(index) = command.find("/bin/sh -c ") {
+        if command.starts_with('|') || command.starts_with("RUN ") {
+            return format!("RUN {}", command[index + "/bin/sh -c ".len()..].trim());
+        }
+    }
+    command.to_string()
+}
+
+/// Progress of one layer of an image that is pulled.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+pub struct LayerProgress {
+    pub id: String,
+
+    /// Last status the engine reported for the layer, e.g. `Downloading` or `Pull complete`.
+    pub status: String,
+
+    /// Bytes handled in the current phase of the layer.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub current: Option<i64>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub total: Option<i64>,
+}
+
+/// Emitted as the layers of an image are pulled and once more with `done` set when the pull ends.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PullProgress {
+    pub pull_id: String,
+    pub image: String,
+
+    /// Layers in the order the engine first reported them.
+    pub layers: Vec<LayerProgress>,
+
+    /// Estimate for the whole pull, from 0 to 100.
+    pub percent: f64,
+
+    /// Last message about the whole image, e.g. `Status: Image is up to date for nginx:latest`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+
+    pub done: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PullResult {
+    pub pull_id: String,
+    pub image: String,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub image_id: Option<String>,
+
+    /// Digest of the manifest that was pulled.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+
+    /// The local image already was the latest one.
+    pub up_to_date: bool,
+}
+
+/// Emitted as the layers of an image are pushed and once more with `done` set when
+/// the push ends. Unlike pulls, pushes are only reported as a whole: the engine names
+/// the layer of every push message, but bollard's `PushImageInfo` drops that id.
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PushProgress {
+    pub push_id: String,
+    pub image: String,
+
+    /// Layers the engine is pushing or found in the registry already.
+    pub layers: u32,
+
+    /// Layers that are in the registry.
+    pub layers_done: u32,
+
+    /// Share of the layers that are in the registry, from 0 to 100.
+    pub percent: f64,
+
+    /// Last message about the whole image, e.g. `The push refers to repository [...]`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub status: Option<String>,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub digest: Option<String>,
+
+    pub done: bool,
+
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct PushResult {
+    pub push_id: String,
+    pub image: String,
+
+    /// Digest of the manifest in the registry.
+    pub digest: String,
+}
+
+/// Collects the progress messages the engine streams while pulling an image.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct LayerProgressTracker {
+    pub layers: Vec<LayerProgress>,
+
+    /// Last message that is not about a single layer.
+    pub status: Option<String>,
+
+    pub digest: Option<String>,
+    pub error: Option<String>,
+}
+
+impl LayerProgressTracker {
+    /// Apply one message of the stream. Returns `true` when the status of a layer
+    /// or of the image changed, and `false` when only byte counts advanced.
+    pub fn update(
+        &mut self,
+        id: Option<String>,
+        status: Option<String>,
+        detail: Option<ProgressDetail>,
+        error: Option<String>,
+    ) -> bool {
+        if let Some(error) = error {
+            self.error = Some(error);
+            return true;
+        }
+        let Some(status) = status else {
+            return false;
+        };
+        if let Some(digest) = parse_digest(&status) {
+            self.digest = Some(digest);
+        }
+
+        // Messages about the whole image either have no id or, like
+        // `latest: Pulling from library/nginx`, carry the tag without progress details
+        let id = match id {
+            Some(id) if detail.is_some() || is_layer_id(&id) => id,
+            _ => {
+                self.status = Some(status);
+                return true;
+            }
+        };
+
+        let detail = detail.unwrap_or_default();
+        let layer = match self.layers.iter_mut().position(|layer| layer.id == id) {
+            Some(index) => &mut self.layers[index],
+            None => {
+                self.layers.push(LayerProgress {
+                    id,
+                    status: String::new(),
+                    current: None,
+                    total: None,
+                });
+                self.layers.last_mut().unwrap()
+            }
+        };
+        let changed = layer.status != status;
+        layer.status = status;
+        layer.current = detail.current;
+        layer.total = detail.total;
+        changed
+    }
+
+    /// Estimated progress from 0 to 100. Downloading and extracting a layer
+    /// count for half of it each.
+    pub fn percent(&self) -> f64 {
+        if self.layers.is_empty() {
+            return 0.0;
+        }
+        let done: f64 = self.layers.iter().map(layer_fraction).sum();
+        (done / self.layers.len() as f64 * 100.0).clamp(0.0, 100.0)
+    }
+}
+
+fn layer_fraction(layer: &LayerProgress) -> f64 {
+    let ratio = match (layer.current, layer.total) {
+        (Some(current), Some(total)) if total > 0 => (current as f64 / total as f64).min(1.0),
+        _ => 0.0,
+    };
+    match layer.status.as_str() {
+        "Downloading" => 0.5 * ratio,
+        "Verifying Checksum" | "Download complete" => 0.5,
+        "Extracting" => 0.5 + 0.5 * ratio,
+        "Pull complete" | "Already exists" => 1.0,
+        _ => 0.0,
+    }
+}
+
+/// Collects the progress messages the engine streams while pushing an image.
+/// Layers are counted rather than told apart, see `PushProgress`.
+#[derive(Debug, Clone, Default)]
+pub(crate) struct PushProgressTracker {
+    pub layers: u32,
+    pub layers_done: u32,
+
+    /// Last message that is not about a single layer.
+    pub status: Option<String>,
+
+    pub digest: Option<String>,
+    pub error: Option<String>,
+}
+
+impl PushProgressTracker {
+    /// Apply one message of the stream. Returns `true` when a layer was added or
+    /// finished or the status of the image changed.
+    pub fn update(&mut self, status: Option<String>, error: Option<String>) -> bool {
+        if let Some(error) = error {
+            self.error = Some(error);
+            return true;
+        }
+        let Some(status) = status else {
+            return false;
+        };
+        if let Some(digest) = parse_digest(&status) {
+            self.digest = Some(digest);
+        }
+
+        match status.as_str() {
+            // Every layer starts with `Preparing`
+            "Preparing" => self.layers += 1,
+            "Pushed" | "Layer already exists" => self.layers_done += 1,
+            status if status.starts_with("Mounted from") => self.layers_done += 1,
+            "Waiting" | "Pushing" => return false,
+            _ => self.status = Some(status),
+        }
+        true
+    }
+
+    /// Share of the layers that are in the registry, from 0 to 100.
+    pub fn percent(&self) -> f64 {
+        if self.layers == 0 {
+            return 0.0;
+        }
+        (self.layers_done as f64 / self.layers as f64 * 100.0).clamp(0.0, 100.0)
+    }
+}
+
+/// Layers are reported by the first 12 characters of their digest.
+fn is_layer_id(id: &str) -> bool {
+    id.len() == 12 && id.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+/// Digest in `Digest: sha256:...` (pull) or `latest: digest: sha256:... size: 528` (push).
+fn parse_digest(status: &str) -> Option<String> {
+    let index = status.to_lowercase().find("digest: ")?;
+    status[index + "digest: ".len()..]
+        .split_whitespace()
+        .next()
+        .filter(|digest| digest.contains(':'))
+        .map(str::to_string)
+}
+
+/// Split `repository[:tag]`, ignoring the colon of a registry port.
+pub(crate) fn split_reference(reference: &str) -> (&str, Option<&str>) {
+    let name_start = reference.rfind('/').map_or(0, |index| index + 1);
+    match reference[name_start..].rfind(':') {
+        Some(index) => (
+            &reference[..name_start + index],
+            Some(&reference[name_start + index + 1..]),
+        ),
+        None => (reference, None),
+    }
+}
+
+/// Check the repository and tag an image is about to be created as.
+pub(crate) fn validate_image_reference(repository: Option<&str>, tag: Option<&str>) -> Vec<String> {
+    let mut errors = Vec::new();
+
+    match (repository, tag) {
+        (Some(repository), _) if !is_valid_repository(repository) => {
+            errors.push(format!("Invalid repository: {}", repository))
+        }
+        (None, Some(_)) => errors.push("A tag requires a repository".to_string()),
+        _ => {}
+    }
+    if let Some(tag) = tag {
+        if !is_valid_tag(tag) {
+            errors.push(format!("Invalid tag: {}", tag));
+        }
+    }
+
+    errors
+}
+
+/// `repository:tag`, with the tag defaulting to `latest`.
+pub(crate) fn image_reference(repository: Option<&str>, tag: Option<&str>) -> Option<String> {
+    repository.map(|repository| format!("{}:{}", repository, tag.unwrap_or("latest")))
+}
+
+/// Add the `latest` tag to a reference that has neither a tag nor a digest,
+/// so pulling it does not fetch every tag of the repository.
+pub(crate) fn with_default_tag(reference: &str) -> String {
+    let name = reference.rsplit('/').next().unwrap_or(reference);
+    if reference.contains('@') || name.contains(':') {
+        reference.to_string()
+    } else {
+        format!("{}:latest", reference)
+    }
+}
+
+/// Repository names are lowercase path components separated by `/`,
+/// optionally prefixed with a registry host that may carry a port.
+fn is_valid_repository(repository: &str) -> bool {
+    let (registry, path) = match repository.split_once('/') {
+        Some((host, rest)) if host.contains('.') || host.contains(':') || host == "localhost" => {
+            (Some(host), rest)
+        }
+        _ => (None, repository),
+    };
+
+    let valid_registry = registry.is_none_or(|host| {
+        !host.is_empty()
+            && host
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '.' || c == '-' || c == ':')
+    });
+
+    valid_registry
+        && path.split('/').all(|component| {
+            !component.is_empty()
+                && component.starts_with(|c: char| c.is_ascii_lowercase() || c.is_ascii_digit())
+                && component.chars().all(|c| {
+                    c.is_ascii_lowercase() || c.is_ascii_digit() || c == '.' || c == '_' || c == '-'
+                })
+        })
+}
+
+/// Tags are up to 128 characters of `[A-Za-z0-9_.-]`, not starting with `.` or `-`.
+fn is_valid_tag(tag: &str) -> bool {
+    tag.len() <= 128
+        && tag.starts_with(|c: char| c.is_ascii_alphanumeric() || c == '_')
+        && tag
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '_' || c == '.' || c == '-')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn detail(current: i64, total: i64) -> Option<ProgressDetail> {
+        Some(ProgressDetail {
+            current: Some(current),
+            total: Some(total),
+        })
+    }
+
+    #[test]
+    fn test_image_names() {
+        let summary = |id: &str, tags: &[&str], digests: &[&str]| ImageSummary {
+            id: id.to_string(),
+            repo_tags: tags.iter().map(|tag| tag.to_string()).collect(),
+            repo_digests: digests.iter().map(|digest| digest.to_string()).collect(),
+            ..Default::default()
+        };
+
+        let image = Image::new(
+            &summary(
+                "sha256:1",
+                &["localhost:5000/app:1.4", "localhost:5000/app:latest"],
+                &["localhost:5000/app@sha256:aa"],
+            ),
+            true,
+        );
+        assert_eq!(image.repository.as_deref(), Some("localhost:5000/app"));
+        assert_eq!(image.tag.as_deref(), Some("1.4"));
+        assert_eq!(image.repo_tags.len(), 2);
+        assert_eq!(image.repo_digests, vec!["localhost:5000/app@sha256:aa"]);
+
+        let image = Image::new(&summary("sha256:2", &[], &["nginx@sha256:bb"]), false);
+        assert_eq!(image.repository.as_deref(), Some("nginx"));
+        assert_eq!(image.tag, None);
+
+        let image = Image::new(
+            &summary("sha256:3", &["<none>:<none>"], &["<none>@<none>"]),
+            false,
+        );
+        assert_eq!(image.repository.as_deref(), Some("<untagged>@3"));
+        assert!(image.repo_tags.is_empty() && image.repo_digests.is_empty());
+    }
+
+    #[test]
+    fn test_history_instruction() {
+        assert_eq!(
+            history_instruction("/bin/sh -c #(nop)  CMD [\"nginx\" \"-g\" \"daemon off;\"]"),
+            "CMD [\"nginx\" \"-g\" \"daemon off;\"]"
+        );
+        assert_eq!(
+            history_instruction("/bin/sh -c apt-get update"),
+            "RUN apt-get update"
+        );
+        assert_eq!(
+            history_instruction("RUN /bin/sh -c make install # buildkit"),
+            "RUN make install"
+        );
+        assert_eq!(
+            history_instruction("|1 VERSION=1.2 /bin/sh -c echo $VERSION"),
+            "RUN echo $VERSION"
+        );
+        assert_eq!(history_instruction("COPY . /app # buildkit"), "COPY . /app");
+        assert_eq!(
+            history_instruction(
+                "ADD file:9a4f77dfaba7fd2aa78186e4ef0e7486ad55101cefc1fabbc1b385601bb38920 in / "
+            ),
+            "ADD file:9a4f77dfaba7fd2aa78186e4ef0e7486ad55101cefc1fabbc1b385601bb38920 in /"
+        );
+    }
+
+    #[test]
+    fn test_oci_labels() {
+        let mut labels = HashMap::new();
+        labels.insert(
+            "org.opencontainers.image.source".to_string(),
+            "https://github.com/nginx/docker-nginx".to_string(),
+        );
+        labels.insert(
+            "org.opencontainers.image.licenses".to_string(),
+            "".to_string(),
+        );
+        let oci = OciLabels::from_labels(&labels);
+        assert_eq!(
+            oci.source.as_deref(),
+            Some("https://github.com/nginx/docker-nginx")
+        );
+        assert_eq!(oci.licenses, None);
+    }
+
+    #[test]
+    fn test_push_progress_tracker() {
+        let mut tracker = PushProgressTracker::default();
+        let mut update = |status: &str| tracker.update(Some(status.to_string()), None);
+
+        assert!(update("The push refers to repository [localhost:5000/app]"));
+        assert!(update("Preparing"));
+        assert!(update("Preparing"));
+        assert!(update("Preparing"));
+        assert!(!update("Waiting"));
+        assert!(update("Layer already exists"));
+        assert!(!update("Pushing"));
+        assert!(update("Pushed"));
+        assert!(update("1.0: digest: sha256:f00 size: 528"));
+
+        assert_eq!((tracker.layers, tracker.layers_done), (3, 2));
+        assert!((tracker.percent() - 200.0 / 3.0).abs() < 1e-9);
+        assert_eq!(tracker.digest.as_deref(), Some("sha256:f00"));
+
+        tracker.update(None, Some("denied: requested access".to_string()));
+        assert_eq!(tracker.error.as_deref(), Some("denied: requested access"));
+    }
+
+    #[test]
+    fn test_layer_progress_tracker() {
+        let mut tracker = LayerProgressTracker::default();
+        let mut update = |id: Option<&str>, status: &str, detail| {
+            tracker.update(
+                id.map(str::to_string),
+                Some(status.to_string()),
+                detail,
+                None,
+            )
+        };
+
+        assert!(update(Some("latest"), "Pulling from library/nginx", None));
+        assert!(update(
+            Some("a1b2c3d4e5f6"),
+            "Pulling fs layer",
+            Some(ProgressDetail::default())
+        ));
+        assert!(update(
+            Some("0123456789ab"),
+            "Already exists",
+            Some(ProgressDetail::default())
+        ));
+        assert!(update(Some("a1b2c3d4e5f6"), "Downloading", detail(50, 100)));
+        assert!(!update(
+            Some("a1b2c3d4e5f6"),
+            "Downloading",
+            detail(100, 100)
+        ));
+        assert!(update(Some("a1b2c3d4e5f6"), "Extracting", detail(50, 100)));
+        assert!(update(None, "Digest: sha256:abc123", None));
+        assert!(update(
+            None,
+            "Status: Downloaded newer image for nginx:latest",
+            None
+        ));
+
+        assert_eq!(tracker.layers.len(), 2);
+        assert_eq!(tracker.percent(), 87.5);
+        assert_eq!(tracker.digest.as_deref(), Some("sha256:abc123"));
+        assert_eq!(
+            tracker.status.as_deref(),
+            Some("Status: Downloaded newer image for nginx:latest")
+        );
+
+        tracker.update(None, None, None, Some("manifest unknown".to_string()));
+        assert_eq!(tracker.error.as_deref(), Some("manifest unknown"));
+        assert_eq!(
+            parse_digest("latest: digest: sha256:f00 size: 528").as_deref(),
+            Some("sha256:f00")
+        );
+    }
+}
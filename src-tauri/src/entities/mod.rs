@@ -1,15 +1,51 @@
+//! Types exchanged with the frontend and the engine.
+//!
+//! Validation (`validate`, `ParsedRunCommand::parse`) does not stop at the first
+//! problem: all problems found are reported at once, separated by `; `.
+//!
+//! Options of long-running operations carry an optional id (`transfer_id`,
+//! `build_id`, ...) that is put on their progress events, so the caller can match
+//! the events to its request. A random one is generated if it is not set.
+
+mod archive;
+mod build;
+mod bulk;
+mod compose;
 mod config;
 mod containers;
 mod engine;
 mod engine_state;
 mod images;
 mod networks;
+mod registry;
 mod volumes;
 
+pub use self::archive::*;
+pub use self::build::*;
+pub use self::bulk::*;
+pub use self::compose::*;
 pub use self::config::*;
-pub use self::containers::Container;
+pub use self::containers::{
+    ChangeNode, CloneContainerOptions, CommitContainerOptions, CommitResult, Container,
+    ContainerChanges, ContainerDefinition, ContainerDetails, ContainerPatch, ContainerProcess,
+    ContainerSpec, ContainerState, ContainerStats, ContainerUpdate, CopyDirection, CopyOptions,
+    CopyProgress, CopyResult, CreateContainerResult, ExecOutput, ExecSessionClosed,
+    ExecSessionOptions, FileEntry, FileEntryType, FilePreview, LogBatch, LogLine, LogStream,
+    LogSubscriptionClosed, LogSubscriptionOptions, ParsedRunCommand, ProcessList,
+    ProcessListOptions, ProcessListUpdate, ProcessSource, ProcessSubscriptionEnded,
+    ProcessSubscriptionOptions, RecreateContainerResult, ResourceLimits, StatsBatch,
+    StatsStreamEnded, StatsSubscriptionOptions, DEFAULT_PS_ARGS,
+};
+pub(crate) use self::containers::{ReplacementConfig, ReplacementKind};
 pub use self::engine::*;
 pub use self::engine_state::EngineState;
-pub use self::images::{Image, PruneResult};
+pub(crate) use self::images::{
+    split_reference, validate_image_reference, with_default_tag, LayerProgressTracker,
+    PushProgressTracker,
+};
+pub use self::images::{
+    Image, ImageDetails, PruneResult, PullProgress, PullResult, PushProgress, PushResult,
+};
 pub use self::networks::*;
+pub use self::registry::*;
 pub use self::volumes::*;
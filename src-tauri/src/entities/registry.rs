@@ -0,0 +1,230 @@
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Host of Docker Hub in references, e.g. `docker.io/library/nginx`.
+pub const DOCKER_HUB_HOST: &str = "docker.io";
+
+/// Address Docker Hub credentials are stored under by the Docker CLI.
+pub const DOCKER_HUB_SERVER: &str = "https://index.docker.io/v1/";
+
+/// Credentials used to authenticate to a registry. They are only ever read from
+/// the frontend or the Docker config, never serialized, and `Debug` hides the secrets.
+#[derive(Clone, Default, PartialEq, Deserialize)]
+#[serde(default)]
+pub struct RegistryCredentials {
+    pub username: Option<String>,
+    pub password: Option<String>,
+
+    /// OAuth token some registries hand out instead of a password.
+    pub identity_token: Option<String>,
+}
+
+impl fmt::Debug for RegistryCredentials {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let redacted = |secret: &Option<String>| secret.as_ref().map(|_| "<redacted>");
+        f.debug_struct("RegistryCredentials")
+            .field("username", &self.username)
+            .field("password", &redacted(&self.password))
+            .field("identity_token", &redacted(&self.identity_token))
+            .finish()
+    }
+}
+
+impl RegistryCredentials {
+    pub fn to_docker(&self, server_address: &str) -> bollard::auth::DockerCredentials {
+        bollard::auth::DockerCredentials {
+            username: self.username.clone(),
+            password: self.password.clone(),
+            identitytoken: self.identity_token.clone(),
+            serveraddress: Some(server_address.to_string()),
+            ..Default::default()
+        }
+    }
+}
+
+/// Registry host of an image reference, `docker.io` for Docker Hub images.
+pub fn registry_host(reference: &str) -> &str {
+    match reference.split_once('/') {
+        Some((host, _)) if host.contains('.') || host.contains(':') || host == "localhost" => host,
+        _ => DOCKER_HUB_HOST,
+    }
+}
+
+/// Address credentials of a registry host are stored and sent under.
+pub fn server_address(host: &str) -> String {
+    match normalize_host(host) {
+        DOCKER_HUB_HOST => DOCKER_HUB_SERVER.to_string(),
+        host => host.to_string(),
+    }
+}
+
+/// Host without scheme and path, with Docker Hub's aliases folded into `docker.io`.
+fn normalize_host(address: &str) -> &str {
+    let address = address
+        .strip_prefix("https://")
+        .or_else(|| address.strip_prefix("http://"))
+        .unwrap_or(address);
+    let host = address.split('/').next().unwrap_or(address);
+    match host {
+        "index.docker.io" | "registry-1.docker.io" => DOCKER_HUB_HOST,
+        host => host,
+    }
+}
+
+/// The parts of the Docker CLI `config.json` that hold registry credentials.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub(crate) struct DockerConfigFile {
+    auths: HashMap<String, DockerAuthEntry>,
+
+    #[serde(rename = "credsStore")]
+    creds_store: Option<String>,
+
+    #[serde(rename = "credHelpers")]
+    cred_helpers: HashMap<String, String>,
+}
+
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+struct DockerAuthEntry {
+    /// Base64 of `username:password`.
+    auth: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    identitytoken: Option<String>,
+}
+
+impl DockerConfigFile {
+    pub fn parse(content: &str) -> Result<Self, String> {
+        serde_json::from_str(content).map_err(|e| format!("Invalid Docker config: {}", e))
+    }
+
+    /// Credentials stored in the file itself for `host`.
+    pub fn credentials(&self, host: &str) -> Option<RegistryCredentials> {
+        let host = normalize_host(host);
+        let entry = self
+            .auths
+            .iter()
+            .find(|(address, _)| normalize_host(address) == host)
+            .map(|(_, entry)| entry)?;
+
+        let (username, password) = match &entry.auth {
+            Some(auth) if !auth.is_empty() => {
+                let decoded = BASE64.decode(auth.trim()).ok()?;
+                let decoded = String::from_utf8(decoded).ok()?;
+                let (username, password) = decoded.split_once(':')?;
+                (Some(username.to_string()), Some(password.to_string()))
+            }
+            _ => (entry.username.clone(), entry.password.clone()),
+        };
+        let credentials = RegistryCredentials {
+            username,
+            password,
+            identity_token: entry
+                .identitytoken
+                .clone()
+                .filter(|token| !token.is_empty()),
+        };
+        (credentials != RegistryCredentials::default()).then_some(credentials)
+    }
+
+    /// Name of the credential helper for `host`, used as `docker-credential-<name>`.
+    pub fn credential_helper(&self, host: &str) -> Option<&str> {
+        let host = normalize_host(host);
+        self.cred_helpers
+            .iter()
+            .find(|(address, _)| normalize_host(address) == host)
+            .map(|(_, helper)| helper.as_str())
+            .or(self.creds_store.as_deref())
+            .filter(|helper| !helper.is_empty())
+    }
+}
+
+/// Reply of `docker-credential-<helper> get`.
+#[derive(Clone, Deserialize)]
+pub(crate) struct CredentialHelperReply {
+    #[serde(rename = "Username")]
+    pub username: String,
+
+    #[serde(rename = "Secret")]
+    pub secret: String,
+}
+
+impl From<CredentialHelperReply> for RegistryCredentials {
+    fn from(reply: CredentialHelperReply) -> Self {
+        // Helpers return identity tokens with this placeholder as user name
+        if reply.username == "<token>" {
+            RegistryCredentials {
+                identity_token: Some(reply.secret),
+                ..Default::default()
+            }
+        } else {
+            RegistryCredentials {
+                username: Some(reply.username),
+                password: Some(reply.secret),
+                identity_token: None,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_registry_host() {
+        assert_eq!(registry_host("nginx:latest"), "docker.io");
+        assert_eq!(registry_host("team/app:1.0"), "docker.io");
+        assert_eq!(registry_host("localhost:5000/app:dev"), "localhost:5000");
+        assert_eq!(registry_host("ghcr.io/team/app"), "ghcr.io");
+        assert_eq!(server_address("docker.io"), DOCKER_HUB_SERVER);
+        assert_eq!(server_address("localhost:5000"), "localhost:5000");
+    }
+
+    #[test]
+    fn test_docker_config_credentials() {
+        let config = DockerConfigFile::parse(
+            r#"{
+                "auths": {
+                    "https://index.docker.io/v1/": {"auth": "dXNlcjpzM2NyZXQ="},
+                    "localhost:5000": {"identitytoken": "abc"},
+                    "ghcr.io": {}
+                },
+                "credsStore": "desktop",
+                "credHelpers": {"gcr.io": "gcloud"}
+            }"#,
+        )
+        .unwrap();
+
+        let hub = config.credentials("docker.io").unwrap();
+        assert_eq!(hub.username.as_deref(), Some("user"));
+        assert_eq!(hub.password.as_deref(), Some("s3cret"));
+        assert_eq!(
+            config
+                .credentials("localhost:5000")
+                .and_then(|credentials| credentials.identity_token),
+            Some("abc".to_string())
+        );
+        assert_eq!(config.credentials("ghcr.io"), None);
+
+        assert_eq!(config.credential_helper("gcr.io"), Some("gcloud"));
+        assert_eq!(config.credential_helper("ghcr.io"), Some("desktop"));
+    }
+
+    #[test]
+    fn test_credentials_debug_hides_secrets() {
+        let credentials = RegistryCredentials {
+            username: Some("user".to_string()),
+            password: Some("s3cret".to_string()),
+            identity_token: Some("t0ken".to_string()),
+        };
+        let debug = format!("{:?}", credentials);
+        assert!(debug.contains("user"));
+        assert!(!debug.contains("s3cret"));
+        assert!(!debug.contains("t0ken"));
+    }
+}
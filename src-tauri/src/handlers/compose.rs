@@ -0,0 +1,166 @@
+use super::containers::default_stop_timeout;
+use crate::entities::{
+    BulkOperationReport, ComposeDownOptions, ComposeProject, ComposeUpOptions, ComposeValidation,
+    DependencyGraph,
+};
+use crate::services::ComposeService;
+use crate::state::SharedEngineState;
+use tauri::State;
+use tracing::{debug, instrument};
+
+/// List the compose projects with their services, containers and working directory.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn list_compose_projects(
+    state: State<'_, SharedEngineState>,
+) -> Result<Vec<ComposeProject>, String> {
+    debug!("Listing compose projects");
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ComposeService::list_projects(docker).await
+}
+
+/// Dependency graph of compose services and containers as nodes, edges and DOT.
+/// Limited to one compose project when `project` is given.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn dependency_graph(
+    state: State<'_, SharedEngineState>,
+    project: Option<String>,
+) -> Result<DependencyGraph, String> {
+    debug!("Building dependency graph for project: {:?}", project);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ComposeService::dependency_graph(docker, project.as_deref()).await
+}
+
+/// Start a compose project, dependencies first.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn start_compose_project(
+    state: State<'_, SharedEngineState>,
+    name: String,
+) -> Result<BulkOperationReport, String> {
+    debug!("Starting compose project: {}", name);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ComposeService::start_project(docker, &name).await
+}
+
+/// Stop a compose project, dependents first.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn stop_compose_project(
+    state: State<'_, SharedEngineState>,
+    name: String,
+) -> Result<BulkOperationReport, String> {
+    debug!("Stopping compose project: {}", name);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ComposeService::stop_project(docker, &name, default_stop_timeout()).await
+}
+
+/// Restart a compose project, dependencies first.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn restart_compose_project(
+    state: State<'_, SharedEngineState>,
+    name: String,
+) -> Result<BulkOperationReport, String> {
+    debug!("Restarting compose project: {}", name);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ComposeService::restart_project(docker, &name, default_stop_timeout()).await
+}
+
+/// Stop and remove the containers of a compose project, dependents first.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn remove_compose_project(
+    state: State<'_, SharedEngineState>,
+    name: String,
+) -> Result<BulkOperationReport, String> {
+    debug!("Removing compose project: {}", name);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ComposeService::remove_project(docker, &name, default_stop_timeout()).await
+}
+
+/// Load compose files and report the problems found, with the lines they are on.
+/// Without `files`, the default compose file of `working_dir` is used.
+#[tauri::command]
+#[instrument(skip_all)]
+pub async fn validate_compose_files(
+    files: Vec<String>,
+    working_dir: Option<String>,
+) -> ComposeValidation {
+    debug!("Validating compose files: {:?}", files);
+
+    ComposeService::load_project(&files, working_dir.as_deref()).into()
+}
+
+/// Create and start the services of compose files without the compose plugin.
+/// Progress is emitted as `compose-progress` events.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn compose_up(
+    app: tauri::AppHandle,
+    state: State<'_, SharedEngineState>,
+    files: Vec<String>,
+    working_dir: Option<String>,
+    options: Option<ComposeUpOptions>,
+    operation_id: Option<String>,
+) -> Result<BulkOperationReport, String> {
+    debug!("Bringing up compose files: {:?}", files);
+
+    let project =
+        ComposeService::load_project(&files, working_dir.as_deref()).map_err(|errors| {
+            errors
+                .iter()
+                .map(ToString::to_string)
+                .collect::<Vec<_>>()
+                .join("\n")
+        })?;
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ComposeService::up(
+        &app,
+        docker,
+        &project,
+        &options.unwrap_or_default(),
+        operation_id,
+    )
+    .await
+}
+
+/// Remove the containers and networks of a compose project, and its volumes if asked to.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn compose_down(
+    app: tauri::AppHandle,
+    state: State<'_, SharedEngineState>,
+    name: String,
+    options: Option<ComposeDownOptions>,
+    operation_id: Option<String>,
+) -> Result<BulkOperationReport, String> {
+    debug!("Bringing down compose project: {}", name);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ComposeService::down(
+        &app,
+        docker,
+        &name,
+        &options.unwrap_or_default(),
+        default_stop_timeout(),
+        operation_id,
+    )
+    .await
+}
@@ -1,4 +1,6 @@
-use crate::entities::{AppConfig, Language, StartupSettings, TelemetrySettings, Theme};
+use crate::entities::{
+    AppConfig, ContainerSettings, Language, StartupSettings, TelemetrySettings, Theme,
+};
 use crate::services::{ConfigService, UpdaterService};
 use tracing::{debug, instrument};
 
@@ -94,3 +96,15 @@ pub async fn update_sidebar_collapsed(sidebar_collapsed: bool) -> Result<(), Str
 
     ConfigService::save_config(&config)
 }
+
+/// Update container settings, e.g. the default stop timeout
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn update_container_settings(settings: ContainerSettings) -> Result<(), String> {
+    debug!("Updating container settings: {:?}", settings);
+    settings.validate()?;
+
+    let mut config = get_config().await?;
+    config.containers = settings;
+    ConfigService::save_config(&config)
+}
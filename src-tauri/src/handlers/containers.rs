@@ -1,8 +1,16 @@
-use crate::entities::Container;
-use crate::services::{shell, ContainersService};
+use crate::entities::{
+    BulkOperationReport, CloneContainerOptions, CommitContainerOptions, CommitResult, Container,
+    ContainerChanges, ContainerDefinition, ContainerDetails, ContainerPatch, ContainerSpec,
+    ContainerUpdate, CopyOptions, CopyResult, CreateContainerResult, ExportContainerOptions,
+    ExportResult, FileEntry, FilePreview, ParsedRunCommand, RecreateContainerResult,
+};
+use crate::services::{
+    shell, ArchiveService, BulkService, ConfigService, ContainersService, CopyService, FilesService,
+};
 use crate::state::SharedEngineState;
+use std::path::Path;
 use tauri::State;
-use tracing::{debug, instrument};
+use tracing::{debug, instrument, warn};
 
 #[tauri::command]
 #[instrument(skip_all, err)]
@@ -18,6 +26,75 @@ pub async fn list_containers(
     Ok(containers.into_iter().map(|c| c.into()).collect())
 }
 
+/// Everything the engine knows about a container. Values of environment
+/// variables that look like secrets are masked unless `reveal_secrets` is set.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn inspect_container(
+    state: State<'_, SharedEngineState>,
+    id: String,
+    size: Option<bool>,
+    reveal_secrets: Option<bool>,
+) -> Result<ContainerDetails, String> {
+    debug!("Inspecting container: {}", id);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    let container =
+        ContainersService::inspect_container(docker, &id, size.unwrap_or_default()).await?;
+
+    let mut details = ContainerDetails::from(container);
+    if !reveal_secrets.unwrap_or_default() {
+        details.mask_sensitive_env();
+    }
+
+    Ok(details)
+}
+
+/// Check a container spec without creating anything, used by the creation wizard.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn validate_container_spec(spec: ContainerSpec) -> Result<(), String> {
+    debug!("Validating container spec for image: {}", spec.image);
+    spec.validate()
+}
+
+/// Turn a pasted `docker run` command into a container spec for review. The
+/// spec is created and started with `create_container` once confirmed.
+/// Relative `--env-file` paths are resolved against `working_dir`, the directory
+/// the command would have been run in.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn parse_docker_run(
+    command: String,
+    working_dir: Option<String>,
+) -> Result<ParsedRunCommand, String> {
+    debug!("Parsing docker run command");
+    ParsedRunCommand::parse(&command, |path| {
+        let path = Path::new(path);
+        let path = match &working_dir {
+            _ if path.is_absolute() => path.to_path_buf(),
+            Some(dir) => Path::new(dir).join(path),
+            None => return Err("relative env files need a working directory".to_string()),
+        };
+        std::fs::read_to_string(path).map_err(|e| e.to_string())
+    })
+}
+
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn create_container(
+    state: State<'_, SharedEngineState>,
+    spec: ContainerSpec,
+    start: Option<bool>,
+) -> Result<CreateContainerResult, String> {
+    debug!("Creating container from image: {}", spec.image);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ContainersService::create_container(docker, &spec, start.unwrap_or(false)).await
+}
+
 #[tauri::command]
 #[instrument(skip_all, err)]
 pub async fn start_container(
@@ -33,12 +110,93 @@ pub async fn start_container(
 
 #[tauri::command]
 #[instrument(skip_all, err)]
-pub async fn stop_container(state: State<'_, SharedEngineState>, id: String) -> Result<(), String> {
+pub async fn stop_container(
+    state: State<'_, SharedEngineState>,
+    id: String,
+    timeout: Option<i64>,
+) -> Result<(), String> {
     debug!("Stopping container: {}", id);
 
     let engine = state.get_engine().await?;
     let docker = engine.docker.as_ref().ok_or("Docker not found")?;
-    ContainersService::stop_container(docker, &id).await
+    ContainersService::stop_container(docker, &id, timeout, default_stop_timeout()).await
+}
+
+/// Send a signal such as `SIGTERM`, `SIGHUP` or `SIGUSR1` to a container, `SIGKILL` by default.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn kill_container(
+    state: State<'_, SharedEngineState>,
+    id: String,
+    signal: Option<String>,
+) -> Result<(), String> {
+    debug!("Killing container {} with {:?}", id, signal);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ContainersService::kill_container(docker, &id, signal.as_deref()).await
+}
+
+/// Change resource limits and the restart policy of a container without recreating it.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn update_container(
+    state: State<'_, SharedEngineState>,
+    id: String,
+    update: ContainerUpdate,
+) -> Result<(), String> {
+    debug!("Updating container: {}", id);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    let info = ContainersService::get_engine_capabilities(docker).await?;
+    ContainersService::update_container(docker, &id, &update, &info).await
+}
+
+/// Replace a container with a new one that has `patch` applied, e.g. to change
+/// its image, environment, ports or mounts. The old container is restored if
+/// the new one cannot be started.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn recreate_container(
+    state: State<'_, SharedEngineState>,
+    id: String,
+    patch: ContainerPatch,
+) -> Result<RecreateContainerResult, String> {
+    debug!("Recreating container: {}", id);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ContainersService::recreate_container(docker, &id, &patch, default_stop_timeout()).await
+}
+
+/// Create a copy of a container under a new name.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn clone_container(
+    state: State<'_, SharedEngineState>,
+    id: String,
+    options: CloneContainerOptions,
+) -> Result<CreateContainerResult, String> {
+    debug!("Cloning container {} as {}", id, options.name);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ContainersService::clone_container(docker, &id, &options).await
+}
+
+/// Generate a `docker run` command and a compose service for a container.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn container_definition(
+    state: State<'_, SharedEngineState>,
+    id: String,
+) -> Result<ContainerDefinition, String> {
+    debug!("Generating definition of container: {}", id);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ContainersService::container_definition(docker, &id).await
 }
 
 #[tauri::command]
@@ -72,138 +230,202 @@ pub async fn unpause_container(
 pub async fn restart_container(
     state: State<'_, SharedEngineState>,
     id: String,
+    timeout: Option<i64>,
 ) -> Result<(), String> {
     debug!("Restarting container: {}", id);
 
     let engine = state.get_engine().await?;
     let docker = engine.docker.as_ref().ok_or("Docker not found")?;
-    ContainersService::restart_container(docker, &id).await
+    ContainersService::restart_container(docker, &id, timeout, default_stop_timeout()).await
 }
 
 #[tauri::command]
 #[instrument(skip_all, err)]
 pub async fn bulk_start_containers(
+    app: tauri::AppHandle,
     state: State<'_, SharedEngineState>,
     ids: Vec<String>,
-) -> Result<(), String> {
+    operation_id: Option<String>,
+) -> Result<BulkOperationReport, String> {
     debug!("Starting {} containers", ids.len());
 
     let engine = state.get_engine().await?;
     let docker = engine.docker.as_ref().ok_or("Docker not found")?;
-    for id in &ids {
-        if let Err(e) = ContainersService::start_container(docker, id).await {
-            return Err(format!("Failed to start container {}: {}", id, e));
-        }
-    }
-    Ok(())
+    let report = BulkService::run(&app, "start", operation_id, ids, |id| async move {
+        ContainersService::start_container(docker, &id).await
+    })
+    .await;
+
+    Ok(report)
 }
 
 #[tauri::command]
 #[instrument(skip_all, err)]
 pub async fn bulk_stop_containers(
+    app: tauri::AppHandle,
     state: State<'_, SharedEngineState>,
     ids: Vec<String>,
-) -> Result<(), String> {
+    timeout: Option<i64>,
+    operation_id: Option<String>,
+) -> Result<BulkOperationReport, String> {
     debug!("Stopping {} containers", ids.len());
 
     let engine = state.get_engine().await?;
     let docker = engine.docker.as_ref().ok_or("Docker not found")?;
-    for id in &ids {
-        if let Err(e) = ContainersService::stop_container(docker, id).await {
-            return Err(format!("Failed to stop container {}: {}", id, e));
-        }
-    }
-    Ok(())
+    let default_timeout = default_stop_timeout();
+    let report = BulkService::run(&app, "stop", operation_id, ids, |id| async move {
+        ContainersService::stop_container(docker, &id, timeout, default_timeout).await
+    })
+    .await;
+
+    Ok(report)
+}
+
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn bulk_kill_containers(
+    app: tauri::AppHandle,
+    state: State<'_, SharedEngineState>,
+    ids: Vec<String>,
+    signal: Option<String>,
+    operation_id: Option<String>,
+) -> Result<BulkOperationReport, String> {
+    debug!("Killing {} containers with {:?}", ids.len(), signal);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    let signal = signal.as_deref();
+    let report = BulkService::run(&app, "kill", operation_id, ids, |id| async move {
+        ContainersService::kill_container(docker, &id, signal).await
+    })
+    .await;
+
+    Ok(report)
+}
+
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn bulk_update_containers(
+    app: tauri::AppHandle,
+    state: State<'_, SharedEngineState>,
+    ids: Vec<String>,
+    update: ContainerUpdate,
+    operation_id: Option<String>,
+) -> Result<BulkOperationReport, String> {
+    debug!("Updating {} containers", ids.len());
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    let info = ContainersService::get_engine_capabilities(docker).await?;
+    let (update, info) = (&update, &info);
+    let report = BulkService::run(&app, "update", operation_id, ids, |id| async move {
+        ContainersService::update_container(docker, &id, update, info).await
+    })
+    .await;
+
+    Ok(report)
 }
 
 #[tauri::command]
 #[instrument(skip_all, err)]
 pub async fn bulk_pause_containers(
+    app: tauri::AppHandle,
     state: State<'_, SharedEngineState>,
     ids: Vec<String>,
-) -> Result<(), String> {
+    operation_id: Option<String>,
+) -> Result<BulkOperationReport, String> {
     debug!("Pausing {} containers", ids.len());
 
     let engine = state.get_engine().await?;
     let docker = engine.docker.as_ref().ok_or("Docker not found")?;
-    for id in &ids {
-        if let Err(e) = ContainersService::pause_container(docker, id).await {
-            return Err(format!("Failed to pause container {}: {}", id, e));
-        }
-    }
-    Ok(())
+    let report = BulkService::run(&app, "pause", operation_id, ids, |id| async move {
+        ContainersService::pause_container(docker, &id).await
+    })
+    .await;
+
+    Ok(report)
 }
 
 #[tauri::command]
 #[instrument(skip_all, err)]
 pub async fn bulk_unpause_containers(
+    app: tauri::AppHandle,
     state: State<'_, SharedEngineState>,
     ids: Vec<String>,
-) -> Result<(), String> {
+    operation_id: Option<String>,
+) -> Result<BulkOperationReport, String> {
     debug!("Unpausing {} containers", ids.len());
 
     let engine = state.get_engine().await?;
     let docker = engine.docker.as_ref().ok_or("Docker not found")?;
-    for id in &ids {
-        if let Err(e) = ContainersService::unpause_container(docker, id).await {
-            return Err(format!("Failed to unpause container {}: {}", id, e));
-        }
-    }
-    Ok(())
+    let report = BulkService::run(&app, "unpause", operation_id, ids, |id| async move {
+        ContainersService::unpause_container(docker, &id).await
+    })
+    .await;
+
+    Ok(report)
 }
 
 #[tauri::command]
 #[instrument(skip_all, err)]
 pub async fn bulk_restart_containers(
+    app: tauri::AppHandle,
     state: State<'_, SharedEngineState>,
     ids: Vec<String>,
-) -> Result<(), String> {
+    timeout: Option<i64>,
+    operation_id: Option<String>,
+) -> Result<BulkOperationReport, String> {
     debug!("Restarting {} containers", ids.len());
 
     let engine = state.get_engine().await?;
     let docker = engine.docker.as_ref().ok_or("Docker not found")?;
-    for id in &ids {
-        if let Err(e) = ContainersService::restart_container(docker, id).await {
-            return Err(format!("Failed to restart container {}: {}", id, e));
-        }
-    }
-    Ok(())
+    let default_timeout = default_stop_timeout();
+    let report = BulkService::run(&app, "restart", operation_id, ids, |id| async move {
+        ContainersService::restart_container(docker, &id, timeout, default_timeout).await
+    })
+    .await;
+
+    Ok(report)
 }
 
 #[tauri::command]
 #[instrument(skip_all, err)]
 pub async fn bulk_remove_containers(
+    app: tauri::AppHandle,
     state: State<'_, SharedEngineState>,
     ids: Vec<String>,
-) -> Result<(), String> {
+    operation_id: Option<String>,
+) -> Result<BulkOperationReport, String> {
     debug!("Removing {} containers", ids.len());
 
     let engine = state.get_engine().await?;
     let docker = engine.docker.as_ref().ok_or("Docker not found")?;
-    for id in &ids {
-        if let Err(e) = ContainersService::remove_container(docker, id).await {
-            return Err(format!("Failed to remove container {}: {}", id, e));
-        }
-    }
-    Ok(())
+    let report = BulkService::run(&app, "remove", operation_id, ids, |id| async move {
+        ContainersService::remove_container(docker, &id).await
+    })
+    .await;
+
+    Ok(report)
 }
 
 #[tauri::command]
 #[instrument(skip_all, err)]
 pub async fn bulk_force_remove_containers(
+    app: tauri::AppHandle,
     state: State<'_, SharedEngineState>,
     ids: Vec<String>,
-) -> Result<(), String> {
+    operation_id: Option<String>,
+) -> Result<BulkOperationReport, String> {
     debug!("Force removing {} containers", ids.len());
 
     let engine = state.get_engine().await?;
     let docker = engine.docker.as_ref().ok_or("Docker not found")?;
-    for id in &ids {
-        if let Err(e) = ContainersService::force_remove_container(docker, id).await {
-            return Err(format!("Failed to force remove container {}: {}", id, e));
-        }
-    }
-    Ok(())
+    let report = BulkService::run(&app, "force-remove", operation_id, ids, |id| async move {
+        ContainersService::force_remove_container(docker, &id).await
+    })
+    .await;
+
+    Ok(report)
 }
 
 #[tauri::command]
@@ -228,6 +450,23 @@ pub async fn open_terminal(
     shell::open_container_terminal(&app, &id).await
 }
 
+/// Create an image from a container. The image list is refreshed by the
+/// engine state monitor once the engine reports the commit.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn commit_container(
+    state: State<'_, SharedEngineState>,
+    id: String,
+    options: Option<CommitContainerOptions>,
+) -> Result<CommitResult, String> {
+    let options = options.unwrap_or_default();
+    debug!("Committing container {} as {:?}", id, options.reference());
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ContainersService::commit_container(docker, &id, &options).await
+}
+
 #[tauri::command]
 #[instrument(skip_all, err)]
 pub async fn container_logs(
@@ -244,12 +483,137 @@ pub async fn container_logs(
 #[tauri::command]
 #[instrument(skip_all, err)]
 pub async fn container_files(
-    _state: State<'_, SharedEngineState>,
+    state: State<'_, SharedEngineState>,
     id: String,
-) -> Result<(), String> {
-    debug!("Opening files for container: {}", id);
-    // TODO: Implement container files functionality
-    Err("Container files functionality is not implemented yet".to_string())
+    path: Option<String>,
+) -> Result<Vec<FileEntry>, String> {
+    let path = path.unwrap_or_else(|| "/".to_string());
+    debug!("Listing files in container {}: {}", id, path);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    FilesService::list_directory(docker, &id, &path).await
+}
+
+/// Filesystem changes of a container relative to its image, as a tree with counts.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn container_changes(
+    state: State<'_, SharedEngineState>,
+    id: String,
+) -> Result<ContainerChanges, String> {
+    debug!("Getting filesystem changes of container: {}", id);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    FilesService::container_changes(docker, &id).await
+}
+
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn stat_container_path(
+    state: State<'_, SharedEngineState>,
+    id: String,
+    path: String,
+) -> Result<FileEntry, String> {
+    debug!("Getting file info in container {}: {}", id, path);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    FilesService::stat_path(docker, &id, &path).await
+}
+
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn preview_container_file(
+    state: State<'_, SharedEngineState>,
+    id: String,
+    path: String,
+    max_bytes: Option<u64>,
+) -> Result<FilePreview, String> {
+    debug!("Previewing file in container {}: {}", id, path);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    FilesService::preview_file(docker, &id, &path, max_bytes).await
+}
+
+/// Copy a host file or directory into a directory of the container.
+/// Progress is reported through `container-copy-progress` events.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn copy_to_container(
+    app: tauri::AppHandle,
+    state: State<'_, SharedEngineState>,
+    id: String,
+    host_path: String,
+    container_path: String,
+    options: Option<CopyOptions>,
+) -> Result<CopyResult, String> {
+    debug!(
+        "Copying {} into container {}: {}",
+        host_path, id, container_path
+    );
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    CopyService::copy_to_container(
+        app,
+        docker,
+        &id,
+        &host_path,
+        &container_path,
+        options.unwrap_or_default(),
+    )
+    .await
+}
+
+/// Copy a file or directory of the container into a host directory.
+/// Progress is reported through `container-copy-progress` events.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn copy_from_container(
+    app: tauri::AppHandle,
+    state: State<'_, SharedEngineState>,
+    id: String,
+    container_path: String,
+    host_path: String,
+    options: Option<CopyOptions>,
+) -> Result<CopyResult, String> {
+    debug!(
+        "Copying {} out of container {}: {}",
+        container_path, id, host_path
+    );
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    CopyService::copy_from_container(
+        app,
+        docker,
+        &id,
+        &container_path,
+        &host_path,
+        options.unwrap_or_default(),
+    )
+    .await
+}
+
+/// Write the filesystem of a container to a `.tar` or `.tar.gz` file.
+/// Progress is reported with `archive-progress` events.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn export_container(
+    app: tauri::AppHandle,
+    state: State<'_, SharedEngineState>,
+    id: String,
+    path: String,
+    options: Option<ExportContainerOptions>,
+) -> Result<ExportResult, String> {
+    debug!("Exporting container {} to {}", id, path);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ArchiveService::export_container(app, docker, &id, &path, options.unwrap_or_default()).await
 }
 
 #[tauri::command]
@@ -287,3 +651,14 @@ pub async fn prune_containers(state: State<'_, SharedEngineState>) -> Result<(),
     let docker = engine.docker.as_ref().ok_or("Docker not found")?;
     ContainersService::prune_containers(docker).await
 }
+
+/// The user's default stop timeout. A broken config must not prevent stopping containers.
+pub(crate) fn default_stop_timeout() -> Option<i64> {
+    match ConfigService::read_config() {
+        Ok(config) => config.containers.default_stop_timeout,
+        Err(e) => {
+            warn!("Failed to read the default stop timeout: {}", e);
+            None
+        }
+    }
+}
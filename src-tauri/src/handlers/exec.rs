@@ -0,0 +1,55 @@
+use crate::entities::ExecSessionOptions;
+use crate::services::{ContainersService, ExecService};
+use crate::state::SharedEngineState;
+use tauri::State;
+use tracing::{debug, instrument};
+
+/// Open an in-app terminal session inside a running container.
+/// Output is delivered as `exec-output` events keyed by the returned session id.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn open_exec_session(
+    app: tauri::AppHandle,
+    state: State<'_, SharedEngineState>,
+    id: String,
+    options: Option<ExecSessionOptions>,
+) -> Result<String, String> {
+    debug!("Opening exec session for container: {}", id);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+
+    if !ContainersService::is_container_running(docker, &id).await? {
+        return Err(format!("Container {} is not running", id));
+    }
+
+    ExecService::open_session(app, docker.clone(), &id, options.unwrap_or_default()).await
+}
+
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn write_exec_stdin(session_id: String, data: String) -> Result<(), String> {
+    ExecService::write_stdin(&session_id, &data).await
+}
+
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn resize_exec_session(
+    state: State<'_, SharedEngineState>,
+    session_id: String,
+    cols: u16,
+    rows: u16,
+) -> Result<(), String> {
+    debug!("Resizing exec session {} to {}x{}", session_id, cols, rows);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ExecService::resize(docker, &session_id, cols, rows).await
+}
+
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn close_exec_session(session_id: String) -> Result<(), String> {
+    debug!("Closing exec session: {}", session_id);
+    ExecService::close_session(&session_id).await
+}
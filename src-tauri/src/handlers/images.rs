@@ -1,5 +1,8 @@
-use crate::entities::{Image, PruneResult};
-use crate::services::ImagesService;
+use crate::entities::{
+    BuildImageOptions, BuildResult, Image, ImageDetails, ImportImageOptions, ImportResult,
+    PruneResult, PullResult, PushResult, RegistryCredentials,
+};
+use crate::services::{ArchiveService, ImagesService};
 use crate::state::SharedEngineState;
 use tauri::State;
 use tracing::{debug, instrument};
@@ -13,6 +16,27 @@ pub async fn list_images(state: State<'_, SharedEngineState>) -> Result<Vec<Imag
     ImagesService::get_images(docker).await
 }
 
+/// Inspect an image with its layer history. Environment variables that look like
+/// secrets are masked unless `reveal_secrets` is set.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn inspect_image(
+    state: State<'_, SharedEngineState>,
+    id: String,
+    reveal_secrets: Option<bool>,
+) -> Result<ImageDetails, String> {
+    debug!("Inspecting image: {}", id);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    let mut details = ImagesService::inspect_image(docker, &id).await?;
+    if !reveal_secrets.unwrap_or_default() {
+        details.mask_sensitive_env();
+    }
+
+    Ok(details)
+}
+
 #[tauri::command]
 #[instrument(skip_all, err)]
 pub async fn prune_images(state: State<'_, SharedEngineState>) -> Result<PruneResult, String> {
@@ -36,14 +60,32 @@ pub async fn delete_image(
     ImagesService::delete_image(docker, &image_id).await
 }
 
+/// Remove a `repository:tag` from an image while keeping the image and its other tags.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn untag_image(
+    state: State<'_, SharedEngineState>,
+    reference: String,
+) -> Result<(), String> {
+    debug!("Untagging image: {}", reference);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ImagesService::untag_image(docker, &reference).await
+}
+
+/// Pull an image. Progress of every layer is emitted as `image-pull-progress`
+/// events keyed by `pull_id`, which can be passed to `cancel_pull`.
 #[tauri::command]
 #[instrument(skip_all, err)]
 pub async fn pull_image(
+    app: tauri::AppHandle,
     state: State<'_, SharedEngineState>,
     image_name: String,
     tag: String,
     registry: String,
-) -> Result<(), String> {
+    pull_id: Option<String>,
+) -> Result<PullResult, String> {
     debug!(
         "Pulling image: {}:{} from registry: {}",
         image_name, tag, registry
@@ -51,7 +93,80 @@ pub async fn pull_image(
 
     let engine = state.get_engine().await?;
     let docker = engine.docker.as_ref().ok_or("Docker not found")?;
-    ImagesService::pull_image(docker, &image_name, &tag, &registry).await
+    ImagesService::pull_image(app, docker, &image_name, &tag, &registry, pull_id).await
+}
+
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn cancel_pull(pull_id: String) -> Result<(), String> {
+    debug!("Cancelling pull: {}", pull_id);
+    ImagesService::cancel_pull(&pull_id).await
+}
+
+/// Add a new `repository[:tag]` to an image.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn tag_image(
+    state: State<'_, SharedEngineState>,
+    image: String,
+    target: String,
+) -> Result<(), String> {
+    debug!("Tagging image {} as {}", image, target);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ImagesService::tag_image(docker, &image, &target).await
+}
+
+/// Push a tag of an image. Progress is emitted as `image-push-progress`
+/// events keyed by `push_id`.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn push_image(
+    app: tauri::AppHandle,
+    state: State<'_, SharedEngineState>,
+    reference: String,
+    credentials: Option<RegistryCredentials>,
+    push_id: Option<String>,
+) -> Result<PushResult, String> {
+    debug!("Pushing image: {}", reference);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ImagesService::push_image(app, docker, &reference, credentials, push_id).await
+}
+
+/// Create an image from a filesystem tarball, such as one written by `export_container`.
+/// Progress is reported with `archive-progress` events.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn import_image(
+    app: tauri::AppHandle,
+    state: State<'_, SharedEngineState>,
+    path: String,
+    options: Option<ImportImageOptions>,
+) -> Result<ImportResult, String> {
+    debug!("Importing image from {}", path);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ArchiveService::import_image(app, docker, &path, options.unwrap_or_default()).await
+}
+
+/// Build an image from a context directory. Output is emitted as
+/// `image-build-progress` events keyed by the build id of the options.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn build_image(
+    app: tauri::AppHandle,
+    state: State<'_, SharedEngineState>,
+    options: BuildImageOptions,
+) -> Result<BuildResult, String> {
+    debug!("Building image from {}", options.context);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ImagesService::build_image(app, docker, options).await
 }
 
 #[tauri::command]
@@ -0,0 +1,29 @@
+use crate::entities::LogSubscriptionOptions;
+use crate::services::LogsService;
+use crate::state::SharedEngineState;
+use tauri::State;
+use tracing::{debug, instrument};
+
+/// Start following the logs of a container. Lines are delivered as `container-logs` events.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn subscribe_container_logs(
+    app: tauri::AppHandle,
+    state: State<'_, SharedEngineState>,
+    id: String,
+    options: Option<LogSubscriptionOptions>,
+) -> Result<String, String> {
+    debug!("Subscribing to logs for container: {}", id);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    LogsService::subscribe(app, docker.clone(), id, options.unwrap_or_default()).await
+}
+
+/// Stop a log subscription previously opened with `subscribe_container_logs`.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn unsubscribe_container_logs(subscription_id: String) -> Result<(), String> {
+    debug!("Unsubscribing from logs: {}", subscription_id);
+    LogsService::unsubscribe(&subscription_id).await
+}
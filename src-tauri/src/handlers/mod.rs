@@ -1,15 +1,25 @@
+mod compose;
 mod config;
 mod containers;
 mod engine_state;
+mod exec;
 mod images;
+mod logs;
 mod networks;
+mod processes;
+mod stats;
 mod system;
 mod volumes;
 
+pub use compose::*;
 pub use config::*;
 pub use containers::*;
 pub use engine_state::*;
+pub use exec::*;
 pub use images::*;
+pub use logs::*;
 pub use networks::*;
+pub use processes::*;
+pub use stats::*;
 pub use system::*;
 pub use volumes::*;
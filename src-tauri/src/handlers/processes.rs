@@ -0,0 +1,49 @@
+use crate::entities::{ProcessList, ProcessListOptions, ProcessSubscriptionOptions};
+use crate::services::ProcessesService;
+use crate::state::SharedEngineState;
+use tauri::State;
+use tracing::{debug, instrument};
+
+/// List the processes running in a container, with `ps` or from `/proc`
+/// when the engine cannot run `ps`.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn container_processes(
+    state: State<'_, SharedEngineState>,
+    id: String,
+    options: Option<ProcessListOptions>,
+) -> Result<ProcessList, String> {
+    debug!("Listing processes of container {}", id);
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ProcessesService::list_processes(docker, &id, &options.unwrap_or_default()).await
+}
+
+/// Start refreshing the process list of a container as `container-processes` events.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn subscribe_container_processes(
+    app: tauri::AppHandle,
+    state: State<'_, SharedEngineState>,
+    options: ProcessSubscriptionOptions,
+) -> Result<String, String> {
+    debug!(
+        "Subscribing to processes of container {}",
+        options.container_id
+    );
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    ProcessesService::subscribe(app, docker.clone(), options).await
+}
+
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn unsubscribe_container_processes(subscription_id: String) -> Result<(), String> {
+    debug!(
+        "Unsubscribing from container processes: {}",
+        subscription_id
+    );
+    ProcessesService::unsubscribe(&subscription_id).await
+}
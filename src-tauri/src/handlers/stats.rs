@@ -0,0 +1,36 @@
+use crate::entities::StatsSubscriptionOptions;
+use crate::services::StatsService;
+use crate::state::SharedEngineState;
+use tauri::State;
+use tracing::{debug, instrument};
+
+/// Start streaming resource usage of containers as `container-stats` events.
+/// Without container ids all running containers are watched.
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn subscribe_container_stats(
+    app: tauri::AppHandle,
+    state: State<'_, SharedEngineState>,
+    options: Option<StatsSubscriptionOptions>,
+) -> Result<String, String> {
+    let options = options.unwrap_or_default();
+    debug!(
+        "Subscribing to stats of {} containers",
+        if options.container_ids.is_empty() {
+            "all running".to_string()
+        } else {
+            options.container_ids.len().to_string()
+        }
+    );
+
+    let engine = state.get_engine().await?;
+    let docker = engine.docker.as_ref().ok_or("Docker not found")?;
+    StatsService::subscribe(app, docker.clone(), options).await
+}
+
+#[tauri::command]
+#[instrument(skip_all, err)]
+pub async fn unsubscribe_container_stats(subscription_id: String) -> Result<(), String> {
+    debug!("Unsubscribing from container stats: {}", subscription_id);
+    StatsService::unsubscribe(&subscription_id).await
+}
@@ -5,7 +5,9 @@ mod services;
 mod state;
 
 use crate::handlers::{
+    build_image,
     bulk_force_remove_containers,
+    bulk_kill_containers,
     bulk_pause_containers,
     bulk_remove_containers,
     bulk_remove_networks,
@@ -14,12 +16,27 @@ use crate::handlers::{
     bulk_start_containers,
     bulk_stop_containers,
     bulk_unpause_containers,
+    bulk_update_containers,
+    cancel_pull,
     check_colima_availability,
     check_homebrew_availability,
+    clone_container,
+    close_exec_session,
+    commit_container,
+    compose_down,
+    compose_up,
+    container_changes,
+    container_definition,
     container_files,
     container_logs,
+    container_processes,
+    copy_from_container,
+    copy_to_container,
+    create_container,
     delete_image,
+    dependency_graph,
     engine_status,
+    export_container,
     fetch_image_tags,
     force_remove_container,
     // Configuration
@@ -28,8 +45,13 @@ use crate::handlers::{
     get_engine_state,
     get_language,
     get_theme,
+    import_image,
+    inspect_container,
+    inspect_image,
     inspect_volume,
     install_colima_command,
+    kill_container,
+    list_compose_projects,
     // Containers
     list_containers,
     // Images
@@ -38,30 +60,54 @@ use crate::handlers::{
     list_networks,
     // Volumes
     list_volumes,
+    open_exec_session,
     open_terminal,
     // System
     open_url,
+    parse_docker_run,
     pause_container,
+    preview_container_file,
     prune_containers,
     prune_images,
     prune_volumes,
     pull_image,
+    push_image,
+    recreate_container,
+    remove_compose_project,
     remove_container,
     remove_network,
     remove_volume,
+    resize_exec_session,
+    restart_compose_project,
     restart_container,
     search_docker_hub,
     start_colima_vm_command,
+    start_compose_project,
     start_container,
     start_engine_state_monitoring,
+    stat_container_path,
+    stop_compose_project,
     stop_container,
+    subscribe_container_logs,
+    subscribe_container_processes,
+    subscribe_container_stats,
+    tag_image,
     unpause_container,
+    unsubscribe_container_logs,
+    unsubscribe_container_processes,
+    unsubscribe_container_stats,
+    untag_image,
+    update_container,
+    update_container_settings,
     update_language,
     update_last_update_check,
     update_sidebar_collapsed,
     update_startup_settings,
     update_telemetry_settings,
     update_theme,
+    validate_compose_files,
+    validate_container_spec,
+    write_exec_stdin,
 };
 use crate::sentry::flush_sentry;
 use crate::services::ConfigService;
@@ -149,32 +195,79 @@ pub fn run() {
             update_telemetry_settings,
             update_startup_settings,
             update_sidebar_collapsed,
+            update_container_settings,
             update_last_update_check,
             // Containers
             list_containers,
+            inspect_container,
+            validate_container_spec,
+            parse_docker_run,
+            create_container,
             start_container,
             stop_container,
+            kill_container,
+            update_container,
+            recreate_container,
+            clone_container,
+            container_definition,
+            commit_container,
             pause_container,
             unpause_container,
             restart_container,
             bulk_start_containers,
             bulk_stop_containers,
+            bulk_kill_containers,
+            bulk_update_containers,
             bulk_pause_containers,
             bulk_unpause_containers,
             bulk_restart_containers,
             bulk_remove_containers,
             bulk_force_remove_containers,
             open_terminal,
+            open_exec_session,
+            write_exec_stdin,
+            resize_exec_session,
+            close_exec_session,
             container_logs,
+            subscribe_container_logs,
+            unsubscribe_container_logs,
+            subscribe_container_stats,
+            unsubscribe_container_stats,
+            container_processes,
+            subscribe_container_processes,
+            unsubscribe_container_processes,
             container_files,
+            container_changes,
+            stat_container_path,
+            preview_container_file,
+            copy_to_container,
+            copy_from_container,
+            export_container,
             remove_container,
             force_remove_container,
             prune_containers,
+            // Compose
+            list_compose_projects,
+            dependency_graph,
+            start_compose_project,
+            stop_compose_project,
+            restart_compose_project,
+            remove_compose_project,
+            validate_compose_files,
+            compose_up,
+            compose_down,
             // Images
             list_images,
+            inspect_image,
             prune_images,
             delete_image,
+            untag_image,
             pull_image,
+            cancel_pull,
+            tag_image,
+            push_image,
+            import_image,
+            build_image,
             search_docker_hub,
             fetch_image_tags,
             // Networks
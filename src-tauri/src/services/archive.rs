@@ -0,0 +1,622 @@
+use super::progress::{ProgressReader, ProgressReporter};
+use crate::entities::{
+    ArchiveOperation, ArchiveProgress, ExportContainerOptions, ExportResult, ImportImageOptions,
+    ImportResult,
+};
+use crate::services::ContainersService;
+use bollard::image::ImportImageOptions as BollardImportImageOptions;
+use bollard::models::ContainerConfig;
+use bollard::Docker;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures_util::StreamExt;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tokio_util::io::{ReaderStream, StreamReader, SyncIoBridge};
+use tracing::{debug, instrument, warn};
+use uuid::Uuid;
+
+/// Event emitted while an export or import is running.
+const ARCHIVE_PROGRESS_EVENT: &str = "archive-progress";
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+
+/// Size of the in-memory pipe between the archive writer and the upload.
+const PIPE_BUFFER_SIZE: usize = 256 * 1024;
+
+#[derive(Default, Debug)]
+pub struct ArchiveService {}
+
+impl ArchiveService {
+    /// Write the flattened filesystem of a container to `path`. Paths ending in
+    /// `.tar.gz` or `.tgz` are gzipped. The file only appears once the export is complete.
+    #[instrument(skip_all, err)]
+    pub async fn export_container(
+        app_handle: AppHandle,
+        docker: &Docker,
+        container_id: &str,
+        path: &str,
+        options: ExportContainerOptions,
+    ) -> Result<ExportResult, String> {
+        let destination = PathBuf::from(path);
+        let compressed = export_compression(&destination)?;
+        let dir = match destination.parent() {
+            Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        if !dir.is_dir() {
+            return Err(format!("{} is not a directory", dir.display()));
+        }
+
+        // The export is about as large as the container filesystem, which makes a good estimate
+        let container = ContainersService::inspect_container(docker, container_id, true).await?;
+        let total_bytes = container
+            .size_root_fs
+            .and_then(|size| u64::try_from(size).ok());
+
+        debug!(
+            "Exporting container {} to {}",
+            container_id,
+            destination.display()
+        );
+
+        let transfer_id = options
+            .transfer_id
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let reporter = ProgressReporter::new(
+            app_handle,
+            ARCHIVE_PROGRESS_EVENT,
+            ArchiveProgress::new(&transfer_id, ArchiveOperation::ExportContainer, total_bytes),
+        );
+
+        let stream = docker
+            .export_container(container_id)
+            .map(|chunk| chunk.map_err(io::Error::other));
+        let reader = ProgressReader::new(
+            SyncIoBridge::new(StreamReader::new(Box::pin(stream))),
+            reporter.clone(),
+        );
+
+        let target = destination.clone();
+        let result =
+            tokio::task::spawn_blocking(move || write_export(reader, &dir, &target, compressed))
+                .await
+                .map_err(|e| format!("Failed to export container: {}", e));
+        reporter.finish();
+
+        let bytes_written = result?.map_err(|e| {
+            format!(
+                "Failed to export container to {}: {}",
+                destination.display(),
+                e
+            )
+        })?;
+
+        Ok(ExportResult {
+            transfer_id,
+            path: destination.display().to_string(),
+            bytes_written,
+            compressed,
+        })
+    }
+
+    /// Create a single-layer image from a filesystem tarball such as the ones
+    /// written by `export_container`, plain or gzipped.
+    ///
+    /// The archive is sent to the engine as an image archive, as `docker save`
+    /// writes them, so it can be streamed. The file is read twice: once to
+    /// compute the layer digest the image config needs and once to upload it.
+    #[instrument(skip_all, err)]
+    pub async fn import_image(
+        app_handle: AppHandle,
+        docker: &Docker,
+        path: &str,
+        options: ImportImageOptions,
+    ) -> Result<ImportResult, String> {
+        options.validate()?;
+
+        let source = PathBuf::from(path);
+        let metadata =
+            std::fs::metadata(&source).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+        if !metadata.is_file() {
+            return Err(format!("{} is not a file", path));
+        }
+        let file_size = metadata.len();
+
+        let version = docker
+            .version()
+            .await
+            .map_err(|e| format!("Failed to get Docker version: {}", e))?;
+
+        debug!("Importing image from {}", path);
+
+        // Changes are applied before the archive is read so mistakes show up right away
+        let mut image_config = ContainerConfig::default();
+        for change in &options.changes {
+            apply_change(&mut image_config, change)?;
+        }
+
+        let transfer_id = options
+            .transfer_id
+            .clone()
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let reporter = ProgressReporter::new(
+            app_handle,
+            ARCHIVE_PROGRESS_EVENT,
+            ArchiveProgress::new(
+                &transfer_id,
+                ArchiveOperation::ImportImage,
+                Some(file_size * 2),
+            ),
+        );
+
+        let layer_source = source.clone();
+        let layer_reporter = reporter.clone();
+        let diff_id =
+            tokio::task::spawn_blocking(move || layer_diff_id(&layer_source, layer_reporter))
+                .await
+                .map_err(io::Error::other)
+                .and_then(|result| result);
+        let diff_id = match diff_id {
+            Ok(diff_id) => diff_id,
+            Err(e) => {
+                reporter.finish();
+                return Err(format!("Failed to read {}: {}", path, e));
+            }
+        };
+
+        let file_name = source
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let config = image_config_json(
+            image_config,
+            options.message.as_deref(),
+            &diff_id,
+            version.arch.as_deref().unwrap_or("amd64"),
+            version.os.as_deref().unwrap_or("linux"),
+            &file_name,
+        );
+        // Only names the config in the archive. The engine decides the id of the
+        // image, which is the manifest digest on the containerd image store.
+        let config_digest = format!("sha256:{:x}", Sha256::digest(&config));
+
+        let reference = options.reference();
+        let entries =
+            ImageArchiveEntries::new(&diff_id, &config_digest, config, reference.as_deref());
+
+        let (pipe_reader, pipe_writer) = tokio::io::duplex(PIPE_BUFFER_SIZE);
+        let writer = SyncIoBridge::new(pipe_writer);
+        let layer_reporter = reporter.clone();
+        let writer_task = tokio::task::spawn_blocking(move || {
+            write_image_archive(writer, &source, file_size, entries, layer_reporter)
+        });
+
+        let body = ReaderStream::new(pipe_reader).scan((), |_, chunk| {
+            let chunk = match chunk {
+                Ok(chunk) => Some(chunk),
+                Err(e) => {
+                    warn!("Failed to read image archive for upload: {}", e);
+                    None
+                }
+            };
+            futures_util::future::ready(chunk)
+        });
+
+        let mut stream =
+            docker.import_image_stream(BollardImportImageOptions { quiet: true }, body, None);
+        let mut engine_error = None;
+        let mut loaded_id = None;
+        let mut loaded_reference = None;
+        while let Some(result) = stream.next().await {
+            match result {
+                Ok(info) => {
+                    let message = info.stream.or(info.status).unwrap_or_default();
+                    debug!("Import: {}", message.trim());
+                    match parse_loaded(&message) {
+                        Some(LoadedImage::Id(id)) => loaded_id = Some(id),
+                        Some(LoadedImage::Reference(reference)) => {
+                            loaded_reference = Some(reference)
+                        }
+                        None => {}
+                    }
+                }
+                Err(e) => engine_error = Some(e.to_string()),
+            }
+        }
+        drop(stream);
+
+        let written = writer_task
+            .await
+            .map_err(|e| format!("Failed to import image: {}", e));
+        reporter.finish();
+
+        // A failed read cuts the upload short and explains the engine error as well.
+        // A broken pipe only means the engine stopped reading, so its error wins then.
+        match (written?, engine_error) {
+            (Err(e), _) if e.kind() != io::ErrorKind::BrokenPipe => {
+                return Err(format!("Failed to read {}: {}", path, e));
+            }
+            (_, Some(error)) => return Err(format!("Failed to import image: {}", error)),
+            (Err(e), None) => return Err(format!("Failed to import image: {}", e)),
+            (Ok(()), None) => {}
+        }
+
+        let image_id = match (loaded_id, loaded_reference.or_else(|| reference.clone())) {
+            (Some(id), _) => id,
+            (None, Some(loaded)) => docker
+                .inspect_image(&loaded)
+                .await
+                .map_err(|e| format!("Failed to inspect imported image {}: {}", loaded, e))?
+                .id
+                .ok_or_else(|| format!("Engine did not report the id of {}", loaded))?,
+            (None, None) => {
+                return Err("Failed to import image: engine did not report the image id".to_string())
+            }
+        };
+
+        Ok(ImportResult {
+            transfer_id,
+            image_id,
+            reference,
+        })
+    }
+}
+
+/// What a line of the engine's load output names.
+#[derive(Debug, PartialEq)]
+enum LoadedImage {
+    /// `Loaded image ID: sha256:...`, sent for untagged images.
+    Id(String),
+    /// `Loaded image: repository:tag`, sent for tagged images.
+    Reference(String),
+}
+
+fn parse_loaded(message: &str) -> Option<LoadedImage> {
+    let message = message.trim();
+    if let Some(id) = message.strip_prefix("Loaded image ID: ") {
+        Some(LoadedImage::Id(id.trim().to_string()))
+    } else {
+        message
+            .strip_prefix("Loaded image: ")
+            .map(|reference| LoadedImage::Reference(reference.trim().to_string()))
+    }
+}
+
+/// Whether an export to `path` is gzipped.
+fn export_compression(path: &Path) -> Result<bool, String> {
+    let name = path
+        .file_name()
+        .map(|name| name.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    if name.ends_with(".tar.gz") || name.ends_with(".tgz") {
+        Ok(true)
+    } else if name.ends_with(".tar") {
+        Ok(false)
+    } else {
+        Err(format!(
+            "Export path must end with .tar, .tar.gz or .tgz: {}",
+            path.display()
+        ))
+    }
+}
+
+/// Copy the export into a temporary file next to `path` and move it into place when done.
+fn write_export<R: Read>(
+    mut reader: R,
+    dir: &Path,
+    path: &Path,
+    compressed: bool,
+) -> io::Result<u64> {
+    let mut file = tempfile::NamedTempFile::new_in(dir)?;
+
+    if compressed {
+        let mut encoder = GzEncoder::new(file.as_file_mut(), Compression::default());
+        io::copy(&mut reader, &mut encoder)?;
+        encoder.finish()?;
+    } else {
+        io::copy(&mut reader, file.as_file_mut())?;
+    }
+    file.as_file().sync_all()?;
+
+    let file = file.persist(path).map_err(|e| e.error)?;
+    Ok(file.metadata()?.len())
+}
+
+/// Digest of the uncompressed content of a tarball, as used in `rootfs.diff_ids`.
+fn layer_diff_id(path: &Path, reporter: ProgressReporter<ArchiveProgress>) -> io::Result<String> {
+    let mut magic = [0u8; 2];
+    let gzipped = File::open(path)?
+        .read_exact(&mut magic)
+        .is_ok_and(|_| magic == GZIP_MAGIC);
+
+    let reader = ProgressReader::new(File::open(path)?, reporter);
+    let mut reader: Box<dyn Read> = if gzipped {
+        Box::new(GzDecoder::new(reader))
+    } else {
+        Box::new(reader)
+    };
+
+    let mut hasher = Sha256::new();
+    io::copy(&mut reader, &mut hasher)?;
+    Ok(format!("sha256:{:x}", hasher.finalize()))
+}
+
+/// Image config of a single-layer image, serialized the way it is hashed into the image ID.
+fn image_config_json(
+    config: ContainerConfig,
+    message: Option<&str>,
+    diff_id: &str,
+    architecture: &str,
+    os: &str,
+    file_name: &str,
+) -> Vec<u8> {
+    let created = chrono::Utc::now().to_rfc3339_opts(chrono::SecondsFormat::Secs, true);
+    let comment = message
+        .map(|message| message.to_string())
+        .unwrap_or_else(|| format!("Imported from {}", file_name));
+
+    serde_json::json!({
+        "architecture": architecture,
+        "os": os,
+        "created": created,
+        "config": config,
+        "rootfs": {
+            "type": "layers",
+            "diff_ids": [diff_id],
+        },
+        "history": [{
+            "created": created,
+            "comment": comment,
+        }],
+    })
+    .to_string()
+    .into_bytes()
+}
+
+/// Apply a Dockerfile instruction such as `ENV DEBUG=1` to an image config.
+fn apply_change(config: &mut ContainerConfig, change: &str) -> Result<(), String> {
+    let (instruction, args) = change
+        .trim()
+        .split_once(char::is_whitespace)
+        .ok_or_else(|| format!("Invalid change: {}", change))?;
+    let args = args.trim();
+
+    match instruction.to_uppercase().as_str() {
+        "CMD" => config.cmd = Some(command_args(args)),
+        "ENTRYPOINT" => config.entrypoint = Some(command_args(args)),
+        "ENV" => {
+            let env = config.env.get_or_insert_with(Vec::new);
+            for (key, value) in key_values(args)? {
+                env.retain(|entry| entry.split_once('=').map(|(k, _)| k) != Some(key.as_str()));
+                env.push(format!("{}={}", key, value));
+            }
+        }
+        "LABEL" => {
+            let labels = config.labels.get_or_insert_with(HashMap::new);
+            labels.extend(key_values(args)?);
+        }
+        "EXPOSE" => {
+            let ports = config.exposed_ports.get_or_insert_with(HashMap::new);
+            for port in args.split_whitespace() {
+                let port = if port.contains('/') {
+                    port.to_lowercase()
+                } else {
+                    format!("{}/tcp", port)
+                };
+                ports.insert(port, HashMap::new());
+            }
+        }
+        "VOLUME" => {
+            let volumes = config.volumes.get_or_insert_with(HashMap::new);
+            let paths = serde_json::from_str::<Vec<String>>(args).unwrap_or_else(|_| {
+                args.split_whitespace()
+                    .map(|path| path.to_string())
+                    .collect()
+            });
+            for path in paths {
+                volumes.insert(path, HashMap::new());
+            }
+        }
+        "USER" => config.user = Some(args.to_string()),
+        "WORKDIR" => config.working_dir = Some(args.to_string()),
+        "ONBUILD" => config
+            .on_build
+            .get_or_insert_with(Vec::new)
+            .push(args.to_string()),
+        other => return Err(format!("Unsupported change: {}", other)),
+    }
+
+    Ok(())
+}
+
+/// Arguments of `CMD` and `ENTRYPOINT`, either in exec form (`["nginx", "-g", "daemon off;"]`)
+/// or in shell form, which is run through `/bin/sh -c`.
+fn command_args(args: &str) -> Vec<String> {
+    if args.starts_with('[') {
+        if let Ok(command) = serde_json::from_str::<Vec<String>>(args) {
+            return command;
+        }
+    }
+    vec!["/bin/sh".to_string(), "-c".to_string(), args.to_string()]
+}
+
+/// Pairs of `ENV` and `LABEL`, either `KEY=value KEY2="other value"` or the legacy `KEY value`.
+fn key_values(args: &str) -> Result<Vec<(String, String)>, String> {
+    let first_word = args.split_whitespace().next().unwrap_or_default();
+    if !first_word.contains('=') {
+        let (key, value) = args.split_once(char::is_whitespace).unwrap_or((args, ""));
+        return Ok(vec![(key.to_string(), unquote(value.trim()))]);
+    }
+
+    let mut pairs = Vec::new();
+    let mut token = String::new();
+    let mut quote = None;
+    for c in args.chars().chain(std::iter::once(' ')) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), c) => token.push(c),
+            (None, '"' | '\'') => quote = Some(c),
+            (None, c) if c.is_whitespace() => {
+                if !token.is_empty() {
+                    let (key, value) = token
+                        .split_once('=')
+                        .ok_or_else(|| format!("Expected KEY=value, got {}", token))?;
+                    pairs.push((key.to_string(), value.to_string()));
+                    token.clear();
+                }
+            }
+            (None, c) => token.push(c),
+        }
+    }
+    if quote.is_some() {
+        return Err(format!("Unterminated quote in {}", args));
+    }
+
+    Ok(pairs)
+}
+
+fn unquote(value: &str) -> String {
+    let quoted = value.len() >= 2
+        && ((value.starts_with('"') && value.ends_with('"'))
+            || (value.starts_with('\'') && value.ends_with('\'')));
+    if quoted {
+        value[1..value.len() - 1].to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Names and metadata of the files of an image archive.
+struct ImageArchiveEntries {
+    layer_name: String,
+    config_name: String,
+    config: Vec<u8>,
+    manifest: Vec<u8>,
+}
+
+impl ImageArchiveEntries {
+    fn new(diff_id: &str, config_digest: &str, config: Vec<u8>, reference: Option<&str>) -> Self {
+        let layer_name = format!("{}/layer.tar", diff_id.trim_start_matches("sha256:"));
+        let config_name = format!("{}.json", config_digest.trim_start_matches("sha256:"));
+        let manifest = serde_json::json!([{
+            "Config": config_name,
+            "RepoTags": reference.map(|reference| vec![reference]),
+            "Layers": [layer_name],
+        }]);
+
+        ImageArchiveEntries {
+            layer_name,
+            config_name,
+            config,
+            manifest: manifest.to_string().into_bytes(),
+        }
+    }
+}
+
+/// Write an image archive with the tarball at `layer_path` as its only layer.
+fn write_image_archive<W: Write>(
+    writer: W,
+    layer_path: &Path,
+    layer_size: u64,
+    entries: ImageArchiveEntries,
+    reporter: ProgressReporter<ArchiveProgress>,
+) -> io::Result<()> {
+    let mut builder = tar::Builder::new(writer);
+
+    let mut header = archive_header(entries.config.len() as u64);
+    builder.append_data(&mut header, &entries.config_name, entries.config.as_slice())?;
+
+    let layer = ProgressReader::new(File::open(layer_path)?, reporter);
+    let mut header = archive_header(layer_size);
+    builder.append_data(&mut header, &entries.layer_name, layer)?;
+
+    let mut header = archive_header(entries.manifest.len() as u64);
+    builder.append_data(&mut header, "manifest.json", entries.manifest.as_slice())?;
+
+    builder.into_inner()?.flush()
+}
+
+fn archive_header(size: u64) -> tar::Header {
+    let mut header = tar::Header::new_ustar();
+    header.set_size(size);
+    header.set_mode(0o644);
+    header.set_mtime(0);
+    header
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_values() {
+        assert_eq!(
+            key_values(r#"A=1 B="two words" C='x=y'"#).unwrap(),
+            vec![
+                ("A".to_string(), "1".to_string()),
+                ("B".to_string(), "two words".to_string()),
+                ("C".to_string(), "x=y".to_string()),
+            ]
+        );
+        assert_eq!(
+            key_values("GREETING hello world").unwrap(),
+            vec![("GREETING".to_string(), "hello world".to_string())]
+        );
+        assert!(key_values(r#"A="unterminated"#).is_err());
+    }
+
+    #[test]
+    fn test_apply_change() {
+        let mut config = ContainerConfig::default();
+        apply_change(&mut config, r#"CMD ["nginx", "-g", "daemon off;"]"#).unwrap();
+        apply_change(&mut config, "ENTRYPOINT exec ./run.sh").unwrap();
+        apply_change(&mut config, "ENV DEBUG=1").unwrap();
+        apply_change(&mut config, "env DEBUG=0").unwrap();
+        apply_change(&mut config, "EXPOSE 80 53/UDP").unwrap();
+
+        assert_eq!(
+            config.cmd,
+            Some(vec![
+                "nginx".to_string(),
+                "-g".to_string(),
+                "daemon off;".to_string()
+            ])
+        );
+        assert_eq!(
+            config.entrypoint.unwrap()[..2],
+            ["/bin/sh".to_string(), "-c".to_string()]
+        );
+        assert_eq!(config.env, Some(vec!["DEBUG=0".to_string()]));
+        let ports = config.exposed_ports.unwrap();
+        assert!(ports.contains_key("80/tcp") && ports.contains_key("53/udp"));
+        assert!(apply_change(&mut ContainerConfig::default(), "RUN make").is_err());
+    }
+
+    #[test]
+    fn test_parse_loaded() {
+        assert_eq!(
+            parse_loaded("Loaded image ID: sha256:f00\n"),
+            Some(LoadedImage::Id("sha256:f00".to_string()))
+        );
+        assert_eq!(
+            parse_loaded("Loaded image: team/app:1.0\n"),
+            Some(LoadedImage::Reference("team/app:1.0".to_string()))
+        );
+        assert_eq!(parse_loaded("Loading layer"), None);
+    }
+
+    #[test]
+    fn test_export_compression() {
+        assert_eq!(export_compression(Path::new("/tmp/app.tar")), Ok(false));
+        assert_eq!(export_compression(Path::new("/tmp/app.TAR.GZ")), Ok(true));
+        assert_eq!(export_compression(Path::new("/tmp/app.tgz")), Ok(true));
+        assert!(export_compression(Path::new("/tmp/app.zip")).is_err());
+    }
+}
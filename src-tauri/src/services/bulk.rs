@@ -0,0 +1,91 @@
+use crate::entities::{BulkItemResult, BulkOperationProgress, BulkOperationReport};
+use futures_util::{stream, StreamExt};
+use std::future::Future;
+use tauri::{AppHandle, Emitter};
+use tracing::{debug, warn};
+use uuid::Uuid;
+
+/// How many items of a bulk operation are processed at the same time.
+const BULK_CONCURRENCY: usize = 8;
+
+#[derive(Default, Debug)]
+pub struct BulkService {}
+
+impl BulkService {
+    /// Run `action` for every id with bounded parallelism. Failures do not stop
+    /// the remaining items, each one ends up in the report instead. A
+    /// `bulk-operation-progress` event is emitted as every item completes.
+    pub async fn run<F, Fut>(
+        app_handle: &AppHandle,
+        operation: &str,
+        operation_id: Option<String>,
+        ids: Vec<String>,
+        action: F,
+    ) -> BulkOperationReport
+    where
+        F: Fn(String) -> Fut,
+        Fut: Future<Output = Result<(), String>>,
+    {
+        let operation_id = operation_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+
+        let mut unique_ids: Vec<String> = Vec::with_capacity(ids.len());
+        for id in ids {
+            if !unique_ids.contains(&id) {
+                unique_ids.push(id);
+            }
+        }
+        let total = unique_ids.len();
+        debug!("Running bulk {} on {} items", operation, total);
+
+        let mut results: Vec<Option<BulkItemResult>> = vec![None; total];
+        let mut completed = 0;
+
+        let mut outcomes = stream::iter(unique_ids.into_iter().enumerate())
+            .map(|(index, id)| {
+                let outcome = action(id.clone());
+                async move { (index, id, outcome.await) }
+            })
+            .buffer_unordered(BULK_CONCURRENCY);
+
+        while let Some((index, id, outcome)) = outcomes.next().await {
+            let result = match outcome {
+                Ok(()) => BulkItemResult {
+                    id,
+                    success: true,
+                    error: None,
+                },
+                Err(e) => BulkItemResult {
+                    id,
+                    success: false,
+                    error: Some(e),
+                },
+            };
+            completed += 1;
+
+            let progress = BulkOperationProgress {
+                operation_id: operation_id.clone(),
+                operation: operation.to_string(),
+                result: result.clone(),
+                completed,
+                total,
+            };
+            if let Err(e) = app_handle.emit("bulk-operation-progress", &progress) {
+                warn!("Failed to emit bulk operation progress: {}", e);
+            }
+
+            results[index] = Some(result);
+        }
+
+        let results: Vec<BulkItemResult> = results.into_iter().flatten().collect();
+        let succeeded = results.iter().filter(|result| result.success).count();
+
+        BulkOperationReport {
+            operation_id,
+            operation: operation.to_string(),
+            total,
+            succeeded,
+            failed: total - succeeded,
+            results,
+        }
+    }
+}
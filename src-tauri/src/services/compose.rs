@@ -0,0 +1,856 @@
+use crate::entities::{
+    parse_dotenv, with_default_tag, BulkItemResult, BulkOperationReport, ComposeDownOptions,
+    ComposeIssue, ComposeProgress, ComposeProgressStatus, ComposeProject, ComposeProjectConfig,
+    ComposeSource, ComposeUpOptions, Container, ContainerState, DependencyGraph, ProjectContainer,
+    ProjectService, COMPOSE_CONFIG_HASH_LABEL, COMPOSE_PROJECT_LABEL,
+};
+use crate::services::{ContainersService, NetworksService, VolumesService};
+use bollard::container::{InspectContainerOptions, ListContainersOptions};
+use bollard::errors::Error as BollardError;
+use bollard::image::CreateImageOptions;
+use bollard::models::{ContainerStateStatusEnum, HealthStatusEnum};
+use bollard::network::{InspectNetworkOptions, ListNetworksOptions};
+use bollard::volume::ListVolumesOptions;
+use bollard::Docker;
+use futures_util::future::join_all;
+use futures_util::StreamExt;
+use std::collections::HashMap;
+use std::future::Future;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tracing::{debug, instrument, warn};
+use uuid::Uuid;
+
+/// Files looked up in the working directory when no compose files are given,
+/// in order of preference.
+const COMPOSE_FILE_NAMES: [&str; 4] = [
+    "compose.yaml",
+    "compose.yml",
+    "docker-compose.yaml",
+    "docker-compose.yml",
+];
+
+/// Override files applied on top of the default compose file when present.
+const OVERRIDE_FILE_NAMES: [&str; 4] = [
+    "compose.override.yaml",
+    "compose.override.yml",
+    "docker-compose.override.yaml",
+    "docker-compose.override.yml",
+];
+
+/// How long `up` waits for a dependency to become healthy or complete.
+const DEPENDENCY_TIMEOUT: Duration = Duration::from_secs(300);
+
+const DEPENDENCY_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Emits the `compose-progress` events of one operation.
+#[derive(Clone, Copy)]
+struct ProgressEmitter<'a> {
+    app_handle: &'a AppHandle,
+    operation_id: &'a str,
+    project: &'a str,
+}
+
+impl ProgressEmitter<'_> {
+    fn emit(
+        &self,
+        service: Option<&str>,
+        resource: &str,
+        status: ComposeProgressStatus,
+        message: Option<String>,
+    ) {
+        let progress = ComposeProgress {
+            operation_id: self.operation_id.to_string(),
+            project: self.project.to_string(),
+            service: service.map(str::to_string),
+            resource: resource.to_string(),
+            status,
+            message,
+        };
+        if let Err(e) = self.app_handle.emit("compose-progress", &progress) {
+            warn!("Failed to emit compose progress: {}", e);
+        }
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct ComposeService {}
+
+impl ComposeService {
+    /// List the compose projects that have containers, sorted by name.
+    #[instrument(skip_all, err)]
+    pub async fn list_projects(docker: &Docker) -> Result<Vec<ComposeProject>, String> {
+        let containers = Self::project_containers(docker, Some(COMPOSE_PROJECT_LABEL)).await?;
+        Ok(ComposeProject::from_containers(containers))
+    }
+
+    #[instrument(skip_all, err)]
+    pub async fn get_project(docker: &Docker, name: &str) -> Result<ComposeProject, String> {
+        let filter = format!("{}={}", COMPOSE_PROJECT_LABEL, name);
+        let containers = Self::project_containers(docker, Some(&filter)).await?;
+
+        ComposeProject::from_containers(containers)
+            .into_iter()
+            .find(|project| project.name == name)
+            .ok_or_else(|| format!("Compose project {} not found", name))
+    }
+
+    /// Graph of how services and containers depend on each other, through
+    /// `depends_on`, legacy links, shared networks and shared volumes. With
+    /// `project`, only the containers of that compose project are included.
+    #[instrument(skip_all, err)]
+    pub async fn dependency_graph(
+        docker: &Docker,
+        project: Option<&str>,
+    ) -> Result<DependencyGraph, String> {
+        let label = project.map(|project| format!("{}={}", COMPOSE_PROJECT_LABEL, project));
+        let containers = Self::project_containers(docker, label.as_deref()).await?;
+        Ok(DependencyGraph::from_containers(&containers))
+    }
+
+    /// Start the containers of a project, every service after the ones it depends on.
+    /// Services whose dependencies failed to start are not started.
+    #[instrument(skip_all, err)]
+    pub async fn start_project(docker: &Docker, name: &str) -> Result<BulkOperationReport, String> {
+        let project = Self::get_project(docker, name).await?;
+        let order = project.start_order()?;
+
+        Ok(
+            Self::run_in_order("compose-start", order, true, |_, container| async move {
+                if container.state == Some(ContainerState::Running) {
+                    return Ok(());
+                }
+                ContainersService::start_container(docker, &container.id).await
+            })
+            .await,
+        )
+    }
+
+    /// Stop the containers of a project, every service before the ones it depends on.
+    #[instrument(skip_all, err)]
+    pub async fn stop_project(
+        docker: &Docker,
+        name: &str,
+        default_timeout: Option<i64>,
+    ) -> Result<BulkOperationReport, String> {
+        let project = Self::get_project(docker, name).await?;
+        let mut order = project.start_order()?;
+        order.reverse();
+
+        Ok(
+            Self::run_in_order("compose-stop", order, false, |_, container| async move {
+                Self::stop_if_running(docker, &container, default_timeout).await
+            })
+            .await,
+        )
+    }
+
+    /// Restart the containers of a project in the order they are started in.
+    #[instrument(skip_all, err)]
+    pub async fn restart_project(
+        docker: &Docker,
+        name: &str,
+        default_timeout: Option<i64>,
+    ) -> Result<BulkOperationReport, String> {
+        let project = Self::get_project(docker, name).await?;
+        let order = project.start_order()?;
+
+        Ok(
+            Self::run_in_order("compose-restart", order, true, |_, container| async move {
+                ContainersService::restart_container(docker, &container.id, None, default_timeout)
+                    .await
+            })
+            .await,
+        )
+    }
+
+    /// Stop and remove the containers of a project, every service before the
+    /// ones it depends on. Volumes and networks are kept.
+    #[instrument(skip_all, err)]
+    pub async fn remove_project(
+        docker: &Docker,
+        name: &str,
+        default_timeout: Option<i64>,
+    ) -> Result<BulkOperationReport, String> {
+        let project = Self::get_project(docker, name).await?;
+        let mut order = project.start_order()?;
+        order.reverse();
+
+        Ok(
+            Self::run_in_order("compose-remove", order, false, |_, container| async move {
+                Self::stop_if_running(docker, &container, default_timeout).await?;
+                ContainersService::remove_container(docker, &container.id).await
+            })
+            .await,
+        )
+    }
+
+    /// Read and load compose files. Without `files`, the default compose file of
+    /// `working_dir` and its override are used. The working directory defaults to
+    /// the directory of the first file. Variables come from the `.env` file of the
+    /// working directory, overridden by the environment of the app.
+    #[instrument(skip_all)]
+    pub fn load_project(
+        files: &[String],
+        working_dir: Option<&str>,
+    ) -> Result<ComposeProjectConfig, Vec<ComposeIssue>> {
+        let current_dir = std::env::current_dir().unwrap_or_default();
+        Self::load_project_in(&current_dir, files, working_dir)
+    }
+
+    /// `load_project` with relative paths resolved against `base`. Files are relative
+    /// to the working directory when one is given and to `base` otherwise.
+    fn load_project_in(
+        base: &Path,
+        files: &[String],
+        working_dir: Option<&str>,
+    ) -> Result<ComposeProjectConfig, Vec<ComposeIssue>> {
+        let issue = |file: &str, message: String| ComposeIssue {
+            file: file.to_string(),
+            line: None,
+            column: None,
+            message,
+        };
+
+        let working_dir = working_dir.map(|dir| base.join(dir));
+        let files: Vec<PathBuf> = files
+            .iter()
+            .map(|file| working_dir.as_deref().unwrap_or(base).join(file))
+            .collect();
+        let working_dir: PathBuf = match (working_dir, files.first()) {
+            (Some(dir), _) => dir,
+            (None, Some(file)) => file.parent().map(Path::to_path_buf).unwrap_or_default(),
+            (None, None) => {
+                return Err(vec![issue(
+                    "",
+                    "Either compose files or a working directory are needed".to_string(),
+                )])
+            }
+        };
+
+        let paths: Vec<PathBuf> = if files.is_empty() {
+            let file = COMPOSE_FILE_NAMES
+                .iter()
+                .map(|name| working_dir.join(name))
+                .find(|path| path.is_file())
+                .ok_or_else(|| {
+                    vec![issue(
+                        &working_dir.to_string_lossy(),
+                        "No compose.yaml or docker-compose.yml found".to_string(),
+                    )]
+                })?;
+            let override_file = OVERRIDE_FILE_NAMES
+                .iter()
+                .map(|name| working_dir.join(name))
+                .find(|path| path.is_file());
+            std::iter::once(file).chain(override_file).collect()
+        } else {
+            files
+        };
+
+        let mut sources = Vec::new();
+        let mut errors = Vec::new();
+        for path in paths {
+            let path = path.to_string_lossy().to_string();
+            match std::fs::read_to_string(&path) {
+                Ok(content) => sources.push(ComposeSource { path, content }),
+                Err(e) => errors.push(issue(&path, format!("Failed to read file: {}", e))),
+            }
+        }
+        if !errors.is_empty() {
+            return Err(errors);
+        }
+
+        let mut environment = std::fs::read_to_string(working_dir.join(".env"))
+            .map(|content| parse_dotenv(&content))
+            .unwrap_or_default();
+        environment.extend(std::env::vars());
+
+        ComposeProjectConfig::load(&sources, &working_dir.to_string_lossy(), environment)
+    }
+
+    /// Create the networks, volumes and containers of a project and start them,
+    /// every service after the ones it depends on. Containers whose configuration
+    /// and image did not change are only started. Every step is emitted as a
+    /// `compose-progress` event.
+    #[instrument(skip_all, err)]
+    pub async fn up(
+        app_handle: &AppHandle,
+        docker: &Docker,
+        project: &ComposeProjectConfig,
+        options: &ComposeUpOptions,
+        operation_id: Option<String>,
+    ) -> Result<BulkOperationReport, String> {
+        let operation_id = operation_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+        let progress = ProgressEmitter {
+            app_handle,
+            operation_id: &operation_id,
+            project: &project.name,
+        };
+        let services = project.selected_services(&options.services, &options.profiles)?;
+        debug!(
+            "Bringing up services {:?} of project {}",
+            services, project.name
+        );
+
+        for key in project.used_networks(&services) {
+            Self::ensure_network(docker, project, &key, progress).await?;
+        }
+        for key in project.used_volumes(&services) {
+            Self::ensure_volume(docker, project, &key, progress).await?;
+        }
+
+        let mut results = Vec::new();
+        let mut failed_services: Vec<&str> = Vec::new();
+        for service in &services {
+            let container_name = project.container_name(service);
+            let failed_dependency = project
+                .dependencies(service)
+                .into_iter()
+                .find(|dependency| failed_services.contains(dependency));
+
+            let outcome = match failed_dependency {
+                Some(dependency) => {
+                    let error = format!("Skipped because {} failed", dependency);
+                    progress.emit(
+                        Some(service),
+                        &container_name,
+                        ComposeProgressStatus::Skipped,
+                        Some(error.clone()),
+                    );
+                    Err(error)
+                }
+                None => {
+                    let outcome =
+                        Self::up_service(docker, project, service, &services, options, progress)
+                            .await;
+                    if let Err(e) = &outcome {
+                        progress.emit(
+                            Some(service),
+                            &container_name,
+                            ComposeProgressStatus::Error,
+                            Some(e.clone()),
+                        );
+                    }
+                    outcome
+                }
+            };
+
+            if outcome.is_err() {
+                failed_services.push(service);
+            }
+            results.push(BulkItemResult {
+                id: container_name,
+                success: outcome.is_ok(),
+                error: outcome.err(),
+            });
+        }
+
+        Ok(Self::report(operation_id, "compose-up", results))
+    }
+
+    /// Stop and remove the containers of a project, every service before the ones
+    /// it depends on, then remove its networks and optionally its volumes.
+    #[instrument(skip_all, err)]
+    pub async fn down(
+        app_handle: &AppHandle,
+        docker: &Docker,
+        name: &str,
+        options: &ComposeDownOptions,
+        default_timeout: Option<i64>,
+        operation_id: Option<String>,
+    ) -> Result<BulkOperationReport, String> {
+        let operation_id = operation_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+        let progress = ProgressEmitter {
+            app_handle,
+            operation_id: &operation_id,
+            project: name,
+        };
+        let label = format!("{}={}", COMPOSE_PROJECT_LABEL, name);
+        let filters = HashMap::from([("label".to_string(), vec![label.clone()])]);
+
+        let containers = Self::project_containers(docker, Some(&label)).await?;
+        let project = ComposeProject::from_containers(containers)
+            .into_iter()
+            .find(|project| project.name == name);
+
+        let mut results = Vec::new();
+        if let Some(project) = &project {
+            let mut order = project.start_order()?;
+            order.reverse();
+            let report = Self::run_in_order(
+                "compose-down",
+                order,
+                false,
+                |service, container| async move {
+                    let service = Some(service.as_str());
+                    progress.emit(
+                        service,
+                        &container.name,
+                        ComposeProgressStatus::Stopping,
+                        None,
+                    );
+                    let outcome = async {
+                        Self::stop_if_running(docker, &container, default_timeout).await?;
+                        progress.emit(
+                            service,
+                            &container.name,
+                            ComposeProgressStatus::Stopped,
+                            None,
+                        );
+                        progress.emit(
+                            service,
+                            &container.name,
+                            ComposeProgressStatus::Removing,
+                            None,
+                        );
+                        ContainersService::remove_container(docker, &container.id).await
+                    }
+                    .await;
+                    let (status, message) = match &outcome {
+                        Ok(()) => (ComposeProgressStatus::Removed, None),
+                        Err(e) => (ComposeProgressStatus::Error, Some(e.clone())),
+                    };
+                    progress.emit(service, &container.name, status, message);
+                    outcome
+                },
+            )
+            .await;
+            results = report.results;
+        }
+
+        let networks = docker
+            .list_networks(Some(ListNetworksOptions {
+                filters: filters.clone(),
+            }))
+            .await
+            .map_err(|e| format!("Failed to list networks: {}", e))?;
+        let volumes = match options.volumes {
+            true => docker
+                .list_volumes(Some(ListVolumesOptions { filters }))
+                .await
+                .map_err(|e| format!("Failed to list volumes: {}", e))?
+                .volumes
+                .unwrap_or_default(),
+            false => Vec::new(),
+        };
+        if project.is_none() && networks.is_empty() && volumes.is_empty() {
+            return Err(format!("Compose project {} not found", name));
+        }
+
+        for network in networks.into_iter().filter_map(|network| network.name) {
+            progress.emit(None, &network, ComposeProgressStatus::Removing, None);
+            let outcome = NetworksService::remove_network(docker, &network).await;
+            results.push(Self::resource_result(progress, network, outcome));
+        }
+        for volume in volumes {
+            progress.emit(None, &volume.name, ComposeProgressStatus::Removing, None);
+            let outcome = VolumesService::remove_volume(docker, &volume.name).await;
+            results.push(Self::resource_result(progress, volume.name, outcome));
+        }
+
+        Ok(Self::report(operation_id, "compose-down", results))
+    }
+
+    fn resource_result(
+        progress: ProgressEmitter,
+        resource: String,
+        outcome: Result<(), String>,
+    ) -> BulkItemResult {
+        let (status, message) = match &outcome {
+            Ok(()) => (ComposeProgressStatus::Removed, None),
+            Err(e) => (ComposeProgressStatus::Error, Some(e.clone())),
+        };
+        progress.emit(None, &resource, status, message);
+        BulkItemResult {
+            id: resource,
+            success: outcome.is_ok(),
+            error: outcome.err(),
+        }
+    }
+
+    /// Create a network of the project unless it exists. External networks have to exist.
+    async fn ensure_network(
+        docker: &Docker,
+        project: &ComposeProjectConfig,
+        key: &str,
+        progress: ProgressEmitter<'_>,
+    ) -> Result<(), String> {
+        let name = project.network_name(key);
+        match docker
+            .inspect_network(&name, None::<InspectNetworkOptions<String>>)
+            .await
+        {
+            Ok(_) => return Ok(()),
+            Err(e) if !is_not_found(&e) => {
+                return Err(format!("Failed to inspect network {}: {}", name, e))
+            }
+            Err(_) => {}
+        }
+
+        let options = project
+            .network_options(key)
+            .ok_or_else(|| format!("External network {} not found", name))?;
+        progress.emit(None, &name, ComposeProgressStatus::Creating, None);
+        docker
+            .create_network(options)
+            .await
+            .map_err(|e| format!("Failed to create network {}: {}", name, e))?;
+        progress.emit(None, &name, ComposeProgressStatus::Created, None);
+        Ok(())
+    }
+
+    /// Create a volume of the project unless it exists. External volumes have to exist.
+    async fn ensure_volume(
+        docker: &Docker,
+        project: &ComposeProjectConfig,
+        key: &str,
+        progress: ProgressEmitter<'_>,
+    ) -> Result<(), String> {
+        let name = project.volume_name(key);
+        match docker.inspect_volume(&name).await {
+            Ok(_) => return Ok(()),
+            Err(e) if !is_not_found(&e) => {
+                return Err(format!("Failed to inspect volume {}: {}", name, e))
+            }
+            Err(_) => {}
+        }
+
+        let options = project
+            .volume_options(key)
+            .ok_or_else(|| format!("External volume {} not found", name))?;
+        progress.emit(None, &name, ComposeProgressStatus::Creating, None);
+        docker
+            .create_volume(options)
+            .await
+            .map_err(|e| format!("Failed to create volume {}: {}", name, e))?;
+        progress.emit(None, &name, ComposeProgressStatus::Created, None);
+        Ok(())
+    }
+
+    /// Pull the image of a service if needed, wait for its dependencies, then
+    /// create and start its container.
+    async fn up_service(
+        docker: &Docker,
+        project: &ComposeProjectConfig,
+        service: &str,
+        selected: &[String],
+        options: &ComposeUpOptions,
+        progress: ProgressEmitter<'_>,
+    ) -> Result<(), String> {
+        let config = &project.file.services[service];
+        let name = project.container_name(service);
+        let image = config.image.clone().unwrap_or_default();
+
+        let local_image = docker.inspect_image(&image).await.ok();
+        let pull = match config.pull_policy.as_deref() {
+            Some("always") => true,
+            Some("never") if local_image.is_none() => {
+                return Err(format!(
+                    "Image {} is not present and pull_policy is never",
+                    image
+                ))
+            }
+            Some("never") => false,
+            _ => options.pull || local_image.is_none(),
+        };
+        let image_id = match pull {
+            true => {
+                progress.emit(Some(service), &image, ComposeProgressStatus::Pulling, None);
+                Self::pull(docker, &image).await?;
+                progress.emit(Some(service), &image, ComposeProgressStatus::Pulled, None);
+                docker
+                    .inspect_image(&image)
+                    .await
+                    .ok()
+                    .and_then(|image| image.id)
+            }
+            false => local_image.and_then(|image| image.id),
+        };
+
+        let replacement = project.container_config(service, |path| {
+            std::fs::read_to_string(path)
+                .map_err(|e| format!("Failed to read env file {}: {}", path.display(), e))
+        })?;
+
+        for (dependency, condition) in &config.depends_on {
+            if !selected.contains(dependency) || condition.condition == "service_started" {
+                continue;
+            }
+            let dependency_name = project.container_name(dependency);
+            progress.emit(
+                Some(service),
+                &name,
+                ComposeProgressStatus::Waiting,
+                Some(format!("Waiting for {}", dependency_name)),
+            );
+            Self::wait_for_dependency(docker, &dependency_name, &condition.condition).await?;
+        }
+
+        match docker
+            .inspect_container(&name, None::<InspectContainerOptions>)
+            .await
+        {
+            Ok(existing) => {
+                let labels = existing
+                    .config
+                    .as_ref()
+                    .and_then(|config| config.labels.clone())
+                    .unwrap_or_default();
+                if labels.get(COMPOSE_PROJECT_LABEL) != Some(&project.name) {
+                    return Err(format!(
+                        "Container name {} is already in use by a container outside of project {}",
+                        name, project.name
+                    ));
+                }
+
+                let id = existing.id.clone().unwrap_or_default();
+                let up_to_date = labels.get(COMPOSE_CONFIG_HASH_LABEL)
+                    == Some(&project.config_hash(service))
+                    && existing.image == image_id;
+                if up_to_date && !options.force_recreate {
+                    let running = existing
+                        .state
+                        .as_ref()
+                        .and_then(|state| state.running)
+                        .unwrap_or(false);
+                    if !running {
+                        progress.emit(Some(service), &name, ComposeProgressStatus::Starting, None);
+                        ContainersService::start_container(docker, &id).await?;
+                    }
+                    progress.emit(Some(service), &name, ComposeProgressStatus::Started, None);
+                    return Ok(());
+                }
+
+                progress.emit(
+                    Some(service),
+                    &name,
+                    ComposeProgressStatus::Removing,
+                    Some("Recreating".to_string()),
+                );
+                ContainersService::force_remove_container(docker, &id).await?;
+            }
+            Err(e) if !is_not_found(&e) => {
+                return Err(format!("Failed to inspect container {}: {}", name, e))
+            }
+            Err(_) => {}
+        }
+
+        progress.emit(Some(service), &name, ComposeProgressStatus::Creating, None);
+        let created =
+            ContainersService::create_replacement(docker, &name, replacement, false).await?;
+        progress.emit(Some(service), &name, ComposeProgressStatus::Created, None);
+
+        progress.emit(Some(service), &name, ComposeProgressStatus::Starting, None);
+        ContainersService::start_container(docker, &created.id).await?;
+        progress.emit(Some(service), &name, ComposeProgressStatus::Started, None);
+        Ok(())
+    }
+
+    /// Wait until a container is healthy or exited successfully.
+    async fn wait_for_dependency(
+        docker: &Docker,
+        container: &str,
+        condition: &str,
+    ) -> Result<(), String> {
+        let started = Instant::now();
+        loop {
+            let inspect = ContainersService::inspect_container(docker, container, false).await?;
+            let state = inspect.state.unwrap_or_default();
+            let exited = state.status == Some(ContainerStateStatusEnum::EXITED);
+
+            if condition == "service_completed_successfully" && exited {
+                return match state.exit_code {
+                    Some(0) => Ok(()),
+                    code => Err(format!(
+                        "{} exited with code {}",
+                        container,
+                        code.unwrap_or_default()
+                    )),
+                };
+            }
+            if condition == "service_healthy" {
+                match state.health.and_then(|health| health.status) {
+                    Some(HealthStatusEnum::HEALTHY) => return Ok(()),
+                    Some(HealthStatusEnum::UNHEALTHY) => {
+                        return Err(format!("{} is unhealthy", container))
+                    }
+                    Some(HealthStatusEnum::STARTING) if !exited => {}
+                    _ if exited => return Err(format!("{} exited", container)),
+                    _ => return Err(format!("{} has no healthcheck", container)),
+                }
+            }
+
+            if started.elapsed() > DEPENDENCY_TIMEOUT {
+                return Err(format!("Timed out waiting for {}", container));
+            }
+            tokio::time::sleep(DEPENDENCY_POLL_INTERVAL).await;
+        }
+    }
+
+    async fn pull(docker: &Docker, image: &str) -> Result<(), String> {
+        let options = CreateImageOptions {
+            from_image: with_default_tag(image),
+            ..Default::default()
+        };
+        let mut stream = docker.create_image(Some(options), None, None);
+        while let Some(result) = stream.next().await {
+            let info = result.map_err(|e| format!("Failed to pull image {}: {}", image, e))?;
+            if let Some(error) = info.error {
+                return Err(format!("Failed to pull image {}: {}", image, error));
+            }
+        }
+        Ok(())
+    }
+
+    /// All containers, or the ones with `label` when given.
+    async fn project_containers(
+        docker: &Docker,
+        label: Option<&str>,
+    ) -> Result<Vec<Container>, String> {
+        let filters = match label {
+            Some(label) => HashMap::from([("label".to_string(), vec![label.to_string()])]),
+            None => HashMap::new(),
+        };
+        let options = ListContainersOptions {
+            all: true,
+            filters,
+            ..Default::default()
+        };
+
+        let containers = docker
+            .list_containers(Some(options))
+            .await
+            .map_err(|e| format!("Failed to list containers: {}", e))?;
+
+        Ok(containers.into_iter().map(Container::from).collect())
+    }
+
+    async fn stop_if_running(
+        docker: &Docker,
+        container: &ProjectContainer,
+        default_timeout: Option<i64>,
+    ) -> Result<(), String> {
+        if !matches!(
+            container.state,
+            Some(ContainerState::Running | ContainerState::Paused | ContainerState::Restarting)
+        ) {
+            return Ok(());
+        }
+        ContainersService::stop_container(docker, &container.id, None, default_timeout).await
+    }
+
+    /// Run `action` for the containers of each service, one service after the
+    /// other. The containers of a service are handled at the same time. With
+    /// `needs_dependencies` a service is skipped when one of its dependencies failed.
+    async fn run_in_order<F, Fut>(
+        operation: &str,
+        order: Vec<&ProjectService>,
+        needs_dependencies: bool,
+        action: F,
+    ) -> BulkOperationReport
+    where
+        F: Fn(String, ProjectContainer) -> Fut,
+        Fut: Future<Output = Result<(), String>>,
+    {
+        let mut results: Vec<BulkItemResult> = Vec::new();
+        let mut failed_services: Vec<&str> = Vec::new();
+
+        for service in order {
+            let failed_dependency = service
+                .depends_on
+                .iter()
+                .find(|dependency| failed_services.contains(&dependency.service.as_str()));
+
+            let outcomes: Vec<Result<(), String>> = match failed_dependency {
+                Some(dependency) if needs_dependencies => {
+                    debug!(
+                        "Skipping service {} since {} failed",
+                        service.name, dependency.service
+                    );
+                    let error = format!("Skipped because {} failed", dependency.service);
+                    vec![Err(error); service.containers.len()]
+                }
+                _ => {
+                    join_all(
+                        service
+                            .containers
+                            .iter()
+                            .map(|container| action(service.name.clone(), container.clone())),
+                    )
+                    .await
+                }
+            };
+
+            if outcomes.iter().any(Result::is_err) {
+                failed_services.push(&service.name);
+            }
+            results.extend(
+                service
+                    .containers
+                    .iter()
+                    .zip(outcomes)
+                    .map(|(container, outcome)| BulkItemResult {
+                        id: container.id.clone(),
+                        success: outcome.is_ok(),
+                        error: outcome.err(),
+                    }),
+            );
+        }
+
+        Self::report(Uuid::new_v4().to_string(), operation, results)
+    }
+
+    fn report(
+        operation_id: String,
+        operation: &str,
+        results: Vec<BulkItemResult>,
+    ) -> BulkOperationReport {
+        let total = results.len();
+        let succeeded = results.iter().filter(|result| result.success).count();
+        BulkOperationReport {
+            operation_id,
+            operation: operation.to_string(),
+            total,
+            succeeded,
+            failed: total - succeeded,
+            results,
+        }
+    }
+}
+
+fn is_not_found(error: &BollardError) -> bool {
+    matches!(
+        error,
+        BollardError::DockerResponseServerError {
+            status_code: 404,
+            ..
+        }
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load_project_relative_file() {
+        let base = tempfile::tempdir().unwrap();
+        std::fs::create_dir(base.path().join("proj")).unwrap();
+        std::fs::write(
+            base.path().join("proj").join("compose.yaml"),
+            "services:\n  web:\n    image: nginx\n",
+        )
+        .unwrap();
+
+        let project =
+            ComposeService::load_project_in(base.path(), &["proj/compose.yaml".to_string()], None)
+                .unwrap();
+        assert_eq!(
+            Path::new(&project.working_dir),
+            base.path().join("proj").as_path()
+        );
+        assert_eq!(project.name, "proj");
+    }
+}
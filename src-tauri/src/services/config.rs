@@ -35,7 +35,11 @@ impl ConfigService {
                     VersionedAppConfig::V2(config.into())
                 }
                 VersionedAppConfig::V2(config) => {
-                    debug!("Config already at V2, no migration needed");
+                    info!("Migrating config from V2 to V3");
+                    VersionedAppConfig::V3(config.into())
+                }
+                VersionedAppConfig::V3(config) => {
+                    debug!("Config already at V3, no migration needed");
                     return Ok(config);
                 }
             };
@@ -62,7 +66,7 @@ impl ConfigService {
                     // Try to parse as legacy V1 config
                     match serde_json::from_str::<AppConfigV1>(&content) {
                         Ok(legacy_config) => {
-                            warn!("Detected legacy V1 config, migrating to V2");
+                            warn!("Detected legacy V1 config, migrating to V3");
                             let migrated_config = AppConfig::from(legacy_config);
                             // Save the migrated config to update the file
                             ConfigService::save_config(&migrated_config)?;
@@ -87,6 +91,25 @@ impl ConfigService {
         }
     }
 
+    /// Read the current configuration without writing the file back.
+    /// A missing file reads as the defaults, a corrupted one is an error.
+    #[instrument(skip_all, err)]
+    pub fn read_config() -> Result<AppConfig, String> {
+        let config_path = ConfigService::get_config_path()?;
+        if !config_path.exists() {
+            return Ok(AppConfig::default());
+        }
+
+        let content = fs::read_to_string(&config_path)
+            .map_err(|e| format!("Failed to read config file: {}", e))?;
+        match serde_json::from_str::<VersionedAppConfig>(&content) {
+            Ok(versioned_config) => ConfigService::migrate_config(versioned_config),
+            Err(_) => serde_json::from_str::<AppConfigV1>(&content)
+                .map(AppConfig::from)
+                .map_err(|e| format!("Config file is corrupted: {}", e)),
+        }
+    }
+
     /// Save configuration to file
     #[instrument(skip_all, err)]
     pub fn save_config(config: &AppConfig) -> Result<(), String> {
@@ -1,12 +1,30 @@
-use bollard::models::{ContainerStateStatusEnum, ContainerSummary};
+use crate::entities::{
+    CloneContainerOptions, CommitContainerOptions, CommitResult, ContainerDefinition,
+    ContainerPatch, ContainerSpec, ContainerUpdate, CreateContainerResult, DockerInfo,
+    RecreateContainerResult, ReplacementConfig, ReplacementKind, ResourceLimits,
+};
+use bollard::image::CommitContainerOptions as BollardCommitContainerOptions;
+use bollard::models::{ContainerInspectResponse, ContainerStateStatusEnum, ContainerSummary};
+use bollard::network::ConnectNetworkOptions;
 use bollard::{
     container::{
-        ListContainersOptions, LogsOptions, RemoveContainerOptions, RestartContainerOptions,
-        StartContainerOptions, StopContainerOptions,
+        Config, CreateContainerOptions, InspectContainerOptions, KillContainerOptions,
+        ListContainersOptions, LogsOptions, RemoveContainerOptions, RenameContainerOptions,
+        RestartContainerOptions, StartContainerOptions, StopContainerOptions,
     },
     Docker,
 };
-use tracing::instrument;
+use tracing::{debug, instrument, warn};
+
+/// Seconds the engine waits before killing a stopping container unless told otherwise.
+const DEFAULT_STOP_TIMEOUT: i64 = 10;
+
+/// Signals `kill_container` accepts by name, without the `SIG` prefix.
+const SIGNALS: [&str; 33] = [
+    "ABRT", "ALRM", "BUS", "CHLD", "CONT", "FPE", "HUP", "ILL", "INT", "IO", "IOT", "KILL", "PIPE",
+    "POLL", "PROF", "PWR", "QUIT", "SEGV", "STKFLT", "STOP", "SYS", "TERM", "TRAP", "TSTP", "TTIN",
+    "TTOU", "URG", "USR1", "USR2", "VTALRM", "WINCH", "XCPU", "XFSZ",
+];
 
 #[derive(Default, Debug)]
 pub struct ContainersService {}
@@ -28,6 +46,74 @@ impl ContainersService {
         Ok(containers.to_vec())
     }
 
+    /// Full engine-side view of a container. With `size` the engine also
+    /// computes the size of the writable layer, which can be slow.
+    #[instrument(skip_all, err)]
+    pub async fn inspect_container(
+        docker: &Docker,
+        id: &str,
+        size: bool,
+    ) -> Result<ContainerInspectResponse, String> {
+        docker
+            .inspect_container(id, Some(InspectContainerOptions { size }))
+            .await
+            .map_err(|e| format!("Failed to inspect container: {}", e))
+    }
+
+    /// Create a container from a validated spec and optionally start it right away.
+    #[instrument(skip_all, err)]
+    pub async fn create_container(
+        docker: &Docker,
+        spec: &ContainerSpec,
+        start: bool,
+    ) -> Result<CreateContainerResult, String> {
+        spec.validate()?;
+
+        let options = spec.name.as_ref().map(|name| CreateContainerOptions {
+            name: name.clone(),
+            platform: None,
+        });
+
+        let response = docker
+            .create_container(options, spec.to_config())
+            .await
+            .map_err(|e| format!("Failed to create container: {}", e))?;
+
+        debug!("Created container: {}", response.id);
+        for warning in &response.warnings {
+            warn!("Container create warning: {}", warning);
+        }
+
+        // Only the primary network can be set at creation time on older engines
+        for network in spec.networks.iter().skip(1) {
+            let options = ConnectNetworkOptions {
+                container: response.id.clone(),
+                endpoint_config: network.into(),
+            };
+
+            if let Err(e) = docker.connect_network(&network.name, options).await {
+                // Don't leave a half-configured container behind
+                if let Err(remove_error) = Self::force_remove_container(docker, &response.id).await
+                {
+                    warn!("Failed to clean up container after error: {}", remove_error);
+                }
+                return Err(format!(
+                    "Failed to connect container to network {}: {}",
+                    network.name, e
+                ));
+            }
+        }
+
+        if start {
+            Self::start_container(docker, &response.id).await?;
+        }
+
+        Ok(CreateContainerResult {
+            id: response.id,
+            warnings: response.warnings,
+        })
+    }
+
     #[instrument(skip_all, err)]
     pub async fn start_container(docker: &Docker, id: &str) -> Result<(), String> {
         let options = StartContainerOptions::<String> {
@@ -42,9 +128,17 @@ impl ContainersService {
         Ok(())
     }
 
+    /// Stop a container, sending its stop signal and killing it once the timeout expires.
+    /// See [`ContainersService::resolve_stop_timeout`] for how the timeout is chosen.
     #[instrument(skip_all, err)]
-    pub async fn stop_container(docker: &Docker, id: &str) -> Result<(), String> {
-        let options = StopContainerOptions { t: 0 };
+    pub async fn stop_container(
+        docker: &Docker,
+        id: &str,
+        timeout: Option<i64>,
+        default_timeout: Option<i64>,
+    ) -> Result<(), String> {
+        let t = Self::resolve_stop_timeout(docker, id, timeout, default_timeout).await?;
+        let options = StopContainerOptions { t };
 
         docker
             .stop_container(id, Some(options))
@@ -74,9 +168,16 @@ impl ContainersService {
         Ok(())
     }
 
+    /// Restart a container, stopping it the same way as [`ContainersService::stop_container`].
     #[instrument(skip_all, err)]
-    pub async fn restart_container(docker: &Docker, id: &str) -> Result<(), String> {
-        let options = RestartContainerOptions { t: 0 };
+    pub async fn restart_container(
+        docker: &Docker,
+        id: &str,
+        timeout: Option<i64>,
+        default_timeout: Option<i64>,
+    ) -> Result<(), String> {
+        let t = Self::resolve_stop_timeout(docker, id, timeout, default_timeout).await?;
+        let options = RestartContainerOptions { t: t as isize };
 
         docker
             .restart_container(id, Some(options))
@@ -86,6 +187,401 @@ impl ContainersService {
         Ok(())
     }
 
+    /// Send a signal to the main process of a container, `SIGKILL` when not specified.
+    #[instrument(skip_all, err)]
+    pub async fn kill_container(
+        docker: &Docker,
+        id: &str,
+        signal: Option<&str>,
+    ) -> Result<(), String> {
+        let signal = match signal {
+            Some(signal) => normalize_signal(signal)?,
+            None => "SIGKILL".to_string(),
+        };
+
+        docker
+            .kill_container(id, Some(KillContainerOptions { signal }))
+            .await
+            .map_err(|e| format!("Failed to kill container: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Change resource limits and the restart policy of a container in place.
+    /// The update is validated against the current limits of the container
+    /// and the cgroup features the engine reports in `info`.
+    #[instrument(skip_all, err)]
+    pub async fn update_container(
+        docker: &Docker,
+        id: &str,
+        update: &ContainerUpdate,
+        info: &DockerInfo,
+    ) -> Result<(), String> {
+        let container = Self::inspect_container(docker, id, false).await?;
+        let current = container
+            .host_config
+            .as_ref()
+            .map(ResourceLimits::from)
+            .unwrap_or_default();
+        update.validate(&current, info)?;
+
+        docker
+            .update_container(id, update.to_options())
+            .await
+            .map_err(|e| format!("Failed to update container: {}", e))?;
+
+        Ok(())
+    }
+
+    /// Create an image from the current filesystem and config of a container.
+    #[instrument(skip_all, err)]
+    pub async fn commit_container(
+        docker: &Docker,
+        id: &str,
+        options: &CommitContainerOptions,
+    ) -> Result<CommitResult, String> {
+        options.validate()?;
+
+        let commit_options = BollardCommitContainerOptions {
+            container: id.to_string(),
+            repo: options.repository.clone().unwrap_or_default(),
+            tag: options.tag.clone().unwrap_or_default(),
+            comment: options.message.clone().unwrap_or_default(),
+            author: options.author.clone().unwrap_or_default(),
+            pause: options.pause,
+            changes: (!options.changes.is_empty()).then(|| options.changes.join("\n")),
+        };
+
+        let commit = docker
+            .commit_container(commit_options, Config::<String>::default())
+            .await
+            .map_err(|e| format!("Failed to commit container: {}", e))?;
+
+        let reference = options.reference();
+        // The engine answers with `Id` while the response model expects `ID`,
+        // so look the image up by its reference when there is one.
+        let image_id = match (&commit.id, &reference) {
+            (Some(image_id), _) => Some(image_id.clone()),
+            (None, Some(reference)) => {
+                docker
+                    .inspect_image(reference)
+                    .await
+                    .map_err(|e| format!("Failed to inspect committed image: {}", e))?
+                    .id
+            }
+            (None, None) => None,
+        };
+
+        Ok(CommitResult {
+            image_id,
+            reference,
+        })
+    }
+
+    /// Replace a container with a new one created from its current settings and
+    /// `patch`, for changes the engine cannot apply to an existing container.
+    ///
+    /// The old container is stopped and renamed out of the way, then the new
+    /// one is created under the original name and started if the old one was
+    /// running. If that fails, the new container is removed and the old one
+    /// gets its name back and is started again. The old container is only
+    /// removed once the new one is up. Its anonymous volumes are reused.
+    #[instrument(skip_all, err)]
+    pub async fn recreate_container(
+        docker: &Docker,
+        id: &str,
+        patch: &ContainerPatch,
+        default_timeout: Option<i64>,
+    ) -> Result<RecreateContainerResult, String> {
+        patch.validate()?;
+
+        let container = Self::inspect_container(docker, id, false).await?;
+        let old_id = container.id.clone().unwrap_or_else(|| id.to_string());
+        let name = container
+            .name
+            .as_deref()
+            .map(|name| name.trim_start_matches('/').to_string())
+            .ok_or("Container has no name")?;
+        let was_running = container
+            .state
+            .as_ref()
+            .and_then(|state| state.running)
+            .unwrap_or(false);
+
+        // Stopping such a container removes it, leaving nothing to roll back to
+        let auto_remove = container
+            .host_config
+            .as_ref()
+            .and_then(|host_config| host_config.auto_remove)
+            .unwrap_or(false);
+        if was_running && auto_remove {
+            return Err(
+                "Running containers that are removed when they stop cannot be recreated"
+                    .to_string(),
+            );
+        }
+
+        let replacement =
+            Self::replacement_config(docker, &container, ReplacementKind::Recreate, patch).await?;
+
+        if was_running {
+            Self::stop_container(docker, &old_id, None, default_timeout).await?;
+        }
+
+        let backup_name = format!("{}-{}-old", name, &old_id[..old_id.len().min(12)]);
+        debug!("Renaming container {} to {}", name, backup_name);
+        if let Err(e) = docker
+            .rename_container(&old_id, RenameContainerOptions { name: &backup_name })
+            .await
+        {
+            if was_running {
+                if let Err(start_error) = Self::start_container(docker, &old_id).await {
+                    warn!("Failed to restart container after error: {}", start_error);
+                }
+            }
+            return Err(format!("Failed to rename container: {}", e));
+        }
+
+        match Self::create_replacement(docker, &name, replacement, was_running).await {
+            Ok(created) => {
+                if let Err(e) = docker
+                    .remove_container(
+                        &old_id,
+                        Some(RemoveContainerOptions {
+                            force: true,
+                            link: false,
+                            v: false,
+                        }),
+                    )
+                    .await
+                {
+                    warn!("Failed to remove replaced container {}: {}", old_id, e);
+                }
+
+                Ok(RecreateContainerResult {
+                    id: created.id,
+                    replaced_id: old_id,
+                    warnings: created.warnings,
+                })
+            }
+            Err(e) => {
+                warn!("Recreating container {} failed, rolling back: {}", name, e);
+
+                let mut rollback_errors = Vec::new();
+                if let Err(rename_error) = docker
+                    .rename_container(&old_id, RenameContainerOptions { name: &name })
+                    .await
+                {
+                    rollback_errors.push(format!("Failed to rename container: {}", rename_error));
+                }
+                if was_running {
+                    if let Err(start_error) = Self::start_container(docker, &old_id).await {
+                        rollback_errors.push(start_error);
+                    }
+                }
+
+                if rollback_errors.is_empty() {
+                    Err(format!("{}. The original container was restored", e))
+                } else {
+                    Err(format!(
+                        "{}. Restoring the original container {} failed: {}",
+                        e,
+                        backup_name,
+                        rollback_errors.join("; ")
+                    ))
+                }
+            }
+        }
+    }
+
+    /// Create a copy of a container under a new name, with `patch` applied.
+    /// Static addresses and anonymous volumes are not copied.
+    #[instrument(skip_all, err)]
+    pub async fn clone_container(
+        docker: &Docker,
+        id: &str,
+        options: &CloneContainerOptions,
+    ) -> Result<CreateContainerResult, String> {
+        options.validate()?;
+
+        let container = Self::inspect_container(docker, id, false).await?;
+        let replacement =
+            Self::replacement_config(docker, &container, ReplacementKind::Clone, &options.patch)
+                .await?;
+
+        Self::create_replacement(docker, &options.name, replacement, options.start).await
+    }
+
+    /// Generate a `docker run` command and a compose service that create a
+    /// container with the same settings, leaving out the defaults of its image.
+    #[instrument(skip_all, err)]
+    pub async fn container_definition(
+        docker: &Docker,
+        id: &str,
+    ) -> Result<ContainerDefinition, String> {
+        let container = Self::inspect_container(docker, id, false).await?;
+        let name = container
+            .name
+            .as_deref()
+            .unwrap_or_default()
+            .trim_start_matches('/')
+            .to_string();
+        // Containers of a compose project keep their service name
+        let service_name = container
+            .config
+            .as_ref()
+            .and_then(|config| config.labels.as_ref())
+            .and_then(|labels| labels.get("com.docker.compose.service"))
+            .cloned()
+            .unwrap_or_else(|| name.clone());
+
+        let replacement = Self::replacement_config(
+            docker,
+            &container,
+            ReplacementKind::Definition,
+            &ContainerPatch::default(),
+        )
+        .await?;
+
+        ContainerDefinition::from_config(
+            container.id.as_deref().unwrap_or(id),
+            &name,
+            &service_name,
+            &replacement,
+        )
+    }
+
+    async fn replacement_config(
+        docker: &Docker,
+        container: &ContainerInspectResponse,
+        kind: ReplacementKind,
+        patch: &ContainerPatch,
+    ) -> Result<ReplacementConfig, String> {
+        // Fail before anything is changed when the new image is not there
+        if let Some(image) = &patch.image {
+            docker
+                .inspect_image(image)
+                .await
+                .map_err(|e| format!("Image {} is not available: {}", image, e))?;
+        }
+
+        // Defaults of the current image are left out so a new image can bring its own
+        let image = match container.image.as_deref() {
+            Some(image_id) => match docker.inspect_image(image_id).await {
+                Ok(image) => image.config,
+                Err(e) => {
+                    warn!("Failed to inspect image {}: {}", image_id, e);
+                    None
+                }
+            },
+            None => None,
+        };
+
+        Ok(ReplacementConfig::from_inspect(
+            container,
+            image.as_ref(),
+            kind,
+            patch,
+        ))
+    }
+
+    /// Create a container from a replacement config, connect its extra networks
+    /// and optionally start it. The container is removed again if any step fails.
+    pub(crate) async fn create_replacement(
+        docker: &Docker,
+        name: &str,
+        replacement: ReplacementConfig,
+        start: bool,
+    ) -> Result<CreateContainerResult, String> {
+        let response = docker
+            .create_container(
+                Some(CreateContainerOptions {
+                    name: name.to_string(),
+                    platform: None,
+                }),
+                replacement.config,
+            )
+            .await
+            .map_err(|e| format!("Failed to create container: {}", e))?;
+
+        debug!("Created container {} as {}", response.id, name);
+        for warning in &response.warnings {
+            warn!("Container create warning: {}", warning);
+        }
+
+        let mut result = Ok(());
+        for (network, endpoint_config) in replacement.extra_networks {
+            let options = ConnectNetworkOptions {
+                container: response.id.clone(),
+                endpoint_config,
+            };
+            if let Err(e) = docker.connect_network(&network, options).await {
+                result = Err(format!(
+                    "Failed to connect container to network {}: {}",
+                    network, e
+                ));
+                break;
+            }
+        }
+        if result.is_ok() && start {
+            result = Self::start_container(docker, &response.id).await;
+        }
+
+        if let Err(e) = result {
+            if let Err(remove_error) = Self::force_remove_container(docker, &response.id).await {
+                warn!("Failed to clean up container after error: {}", remove_error);
+            }
+            return Err(e);
+        }
+
+        Ok(CreateContainerResult {
+            id: response.id,
+            warnings: response.warnings,
+        })
+    }
+
+    /// Engine info used to check which resource limits can be applied.
+    #[instrument(skip_all, err)]
+    pub async fn get_engine_capabilities(docker: &Docker) -> Result<DockerInfo, String> {
+        let info = docker
+            .info()
+            .await
+            .map_err(|e| format!("Failed to get Docker info: {}", e))?;
+        let version = docker
+            .version()
+            .await
+            .map_err(|e| format!("Failed to get Docker version: {}", e))?;
+
+        Ok(DockerInfo::from((info, version)))
+    }
+
+    /// Seconds to wait before a stopping container is killed. An explicit `timeout`
+    /// wins, then the container's own `StopTimeout`, then the user's default and
+    /// finally the engine default.
+    async fn resolve_stop_timeout(
+        docker: &Docker,
+        id: &str,
+        timeout: Option<i64>,
+        default_timeout: Option<i64>,
+    ) -> Result<i64, String> {
+        if let Some(timeout) = timeout {
+            if timeout < 0 {
+                return Err("Stop timeout cannot be negative".to_string());
+            }
+            return Ok(timeout);
+        }
+
+        let container = docker
+            .inspect_container(id, None)
+            .await
+            .map_err(|e| format!("Failed to inspect container: {}", e))?;
+        let container_timeout = container.config.and_then(|config| config.stop_timeout);
+
+        Ok(container_timeout
+            .or(default_timeout)
+            .unwrap_or(DEFAULT_STOP_TIMEOUT))
+    }
+
     #[instrument(skip_all, err)]
     pub async fn remove_container(docker: &Docker, id: &str) -> Result<(), String> {
         let options = RemoveContainerOptions {
@@ -189,3 +685,54 @@ impl ContainersService {
         Ok(is_running)
     }
 }
+
+/// Turn `term`, `TERM`, `SIGTERM`, `SIGRTMIN+3` or `15` into the form the engine expects.
+fn normalize_signal(signal: &str) -> Result<String, String> {
+    let signal = signal.trim();
+    if let Ok(number) = signal.parse::<u8>() {
+        return if (1..=64).contains(&number) {
+            Ok(number.to_string())
+        } else {
+            Err(format!("Invalid signal number: {}", signal))
+        };
+    }
+
+    let upper = signal.to_uppercase();
+    let name = upper.strip_prefix("SIG").unwrap_or(&upper);
+    let known = SIGNALS.contains(&name)
+        || name == "RTMIN"
+        || name == "RTMAX"
+        || name
+            .strip_prefix("RTMIN+")
+            .or_else(|| name.strip_prefix("RTMAX-"))
+            .and_then(|offset| offset.parse::<u8>().ok())
+            .is_some_and(|offset| (1..=15).contains(&offset));
+
+    if known {
+        Ok(format!("SIG{}", name))
+    } else {
+        Err(format!("Unknown signal: {}", signal))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_normalize_signal() {
+        assert_eq!(normalize_signal("SIGTERM").unwrap(), "SIGTERM");
+        assert_eq!(normalize_signal("hup").unwrap(), "SIGHUP");
+        assert_eq!(normalize_signal(" SigUsr1 ").unwrap(), "SIGUSR1");
+        assert_eq!(normalize_signal("SIGRTMIN+3").unwrap(), "SIGRTMIN+3");
+        assert_eq!(normalize_signal("9").unwrap(), "9");
+    }
+
+    #[test]
+    fn test_normalize_signal_rejects_unknown_signals() {
+        assert!(normalize_signal("SIGFOO").is_err());
+        assert!(normalize_signal("0").is_err());
+        assert!(normalize_signal("65").is_err());
+        assert!(normalize_signal("RTMIN+99").is_err());
+    }
+}
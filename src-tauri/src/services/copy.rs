@@ -0,0 +1,278 @@
+use super::files::{join_path, normalize_path};
+use super::progress::ProgressReporter;
+use crate::entities::{CopyDirection, CopyOptions, CopyProgress, CopyResult, FileEntryType};
+use crate::services::FilesService;
+use bollard::container::{DownloadFromContainerOptions, UploadToContainerOptions};
+use bollard::Docker;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use futures_util::StreamExt;
+use std::io::{self, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+use tauri::AppHandle;
+use tokio_util::io::{ReaderStream, StreamReader, SyncIoBridge};
+use tracing::{debug, instrument, warn};
+use uuid::Uuid;
+
+/// Event emitted while a copy is running.
+const COPY_PROGRESS_EVENT: &str = "container-copy-progress";
+
+const TAR_BLOCK_SIZE: u64 = 512;
+
+#[derive(Default, Debug)]
+pub struct CopyService {}
+
+impl CopyService {
+    /// Copy a host file or directory into `container_dir`. Modes and modification
+    /// times are taken from the host files. Owners are not: bollard cannot ask the
+    /// engine to keep them (`copyUIDGID`), so the copies belong to the container's root.
+    #[instrument(skip_all, err)]
+    pub async fn copy_to_container(
+        app_handle: AppHandle,
+        docker: &Docker,
+        container_id: &str,
+        host_path: &str,
+        container_dir: &str,
+        options: CopyOptions,
+    ) -> Result<CopyResult, String> {
+        let source = PathBuf::from(host_path);
+        let metadata = std::fs::symlink_metadata(&source)
+            .map_err(|e| format!("Failed to read {}: {}", host_path, e))?;
+        let name = source
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .ok_or_else(|| format!("Cannot copy {}", host_path))?;
+
+        let container_dir = normalize_path(container_dir)?;
+        let destination = join_path(&container_dir, &name);
+        if !options.overwrite
+            && FilesService::path_exists(docker, container_id, &destination).await?
+        {
+            return Err(format!(
+                "{} already exists in the container, enable overwrite to replace it",
+                destination
+            ));
+        }
+
+        debug!(
+            "Copying {} to {} in container {}",
+            host_path, container_dir, container_id
+        );
+
+        // The archive is built in a temporary file first, so the exact upload size is known
+        let archive =
+            tokio::task::spawn_blocking(move || build_archive(&source, &name, metadata.is_dir()))
+                .await
+                .map_err(|e| format!("Failed to build archive: {}", e))?
+                .map_err(|e| format!("Failed to build archive of {}: {}", host_path, e))?;
+        let total_bytes = archive
+            .metadata()
+            .map_err(|e| format!("Failed to build archive: {}", e))?
+            .len();
+
+        let transfer_id = options
+            .transfer_id
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        let reporter = ProgressReporter::new(
+            app_handle,
+            COPY_PROGRESS_EVENT,
+            CopyProgress::new(
+                &transfer_id,
+                container_id,
+                CopyDirection::ToContainer,
+                Some(total_bytes),
+            ),
+        );
+
+        let progress = reporter.clone();
+        let body =
+            ReaderStream::new(tokio::fs::File::from_std(archive)).scan((), move |_, chunk| {
+                let chunk = match chunk {
+                    Ok(chunk) => {
+                        progress.advance(chunk.len() as u64);
+                        Some(chunk)
+                    }
+                    Err(e) => {
+                        warn!("Failed to read archive for upload: {}", e);
+                        None
+                    }
+                };
+                futures_util::future::ready(chunk)
+            });
+
+        let upload_options = UploadToContainerOptions {
+            path: container_dir,
+            no_overwrite_dir_non_dir: (!options.overwrite).to_string(),
+        };
+        let result = docker
+            .upload_to_container_streaming(container_id, Some(upload_options), body)
+            .await;
+        let bytes_transferred = reporter.finish();
+
+        result.map_err(|e| format!("Failed to copy {} to container: {}", host_path, e))?;
+
+        if bytes_transferred != total_bytes {
+            return Err(format!(
+                "Failed to copy {} to container: upload was interrupted",
+                host_path
+            ));
+        }
+
+        Ok(CopyResult {
+            transfer_id,
+            bytes_transferred,
+            ownership_preserved: false,
+        })
+    }
+
+    /// Copy a file or directory out of a container into `host_dir`.
+    /// Modes and modification times are kept, owners only when the app is
+    /// allowed to change them.
+    #[instrument(skip_all, err)]
+    pub async fn copy_from_container(
+        app_handle: AppHandle,
+        docker: &Docker,
+        container_id: &str,
+        container_path: &str,
+        host_dir: &str,
+        options: CopyOptions,
+    ) -> Result<CopyResult, String> {
+        let container_path = normalize_path(container_path)?;
+        if container_path == "/" {
+            return Err("Cannot copy the whole container filesystem".to_string());
+        }
+        let entry = FilesService::stat_path(docker, container_id, &container_path).await?;
+
+        let host_dir = PathBuf::from(host_dir);
+        if !host_dir.is_dir() {
+            return Err(format!("{} is not a directory", host_dir.display()));
+        }
+        let destination = host_dir.join(&entry.name);
+        if !options.overwrite && std::fs::symlink_metadata(&destination).is_ok() {
+            return Err(format!(
+                "{} already exists, enable overwrite to replace it",
+                destination.display()
+            ));
+        }
+
+        debug!(
+            "Copying {} from container {} to {}",
+            container_path,
+            container_id,
+            host_dir.display()
+        );
+
+        let transfer_id = options
+            .transfer_id
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        // A single file is sent as one header, the padded content and the end-of-archive
+        // marker. The archive of a directory has no known size before it is fully read.
+        let total_bytes = (entry.entry_type == FileEntryType::File).then(|| {
+            TAR_BLOCK_SIZE
+                + entry.size.div_ceil(TAR_BLOCK_SIZE) * TAR_BLOCK_SIZE
+                + 2 * TAR_BLOCK_SIZE
+        });
+        let reporter = ProgressReporter::new(
+            app_handle,
+            COPY_PROGRESS_EVENT,
+            CopyProgress::new(
+                &transfer_id,
+                container_id,
+                CopyDirection::FromContainer,
+                total_bytes,
+            ),
+        );
+
+        let progress = reporter.clone();
+        let stream = docker
+            .download_from_container(
+                container_id,
+                Some(DownloadFromContainerOptions {
+                    path: container_path.clone(),
+                }),
+            )
+            .map(move |chunk| {
+                let chunk = chunk.map_err(io::Error::other)?;
+                progress.advance(chunk.len() as u64);
+                Ok::<_, io::Error>(chunk)
+            });
+        let reader = SyncIoBridge::new(StreamReader::new(Box::pin(stream)));
+
+        let overwrite = options.overwrite;
+        let result = tokio::task::spawn_blocking(move || {
+            unpack_archive(tar::Archive::new(reader), &host_dir, overwrite)
+        })
+        .await
+        .map_err(|e| format!("Failed to unpack archive: {}", e));
+        let bytes_transferred = reporter.finish();
+
+        let ownership_preserved = result?
+            .map_err(|e| format!("Failed to copy {} from container: {}", container_path, e))?;
+
+        Ok(CopyResult {
+            transfer_id,
+            bytes_transferred,
+            ownership_preserved,
+        })
+    }
+}
+
+/// Write a gzipped tar of `source` named `name` into an anonymous temporary file.
+fn build_archive(source: &Path, name: &str, is_dir: bool) -> io::Result<std::fs::File> {
+    let file = tempfile::tempfile()?;
+    let mut builder = tar::Builder::new(GzEncoder::new(file, Compression::fast()));
+    builder.mode(tar::HeaderMode::Complete);
+    builder.follow_symlinks(false);
+
+    if is_dir {
+        builder.append_dir_all(name, source)?;
+    } else {
+        builder.append_path_with_name(source, name)?;
+    }
+
+    let mut file = builder.into_inner()?.finish()?;
+    file.seek(SeekFrom::Start(0))?;
+    Ok(file)
+}
+
+/// Unpack every entry below `dir`. Returns whether all owners could be restored.
+fn unpack_archive<R: io::Read>(
+    mut archive: tar::Archive<R>,
+    dir: &Path,
+    overwrite: bool,
+) -> io::Result<bool> {
+    archive.set_overwrite(overwrite);
+    let mut ownership_preserved = true;
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        entry.set_preserve_permissions(true);
+        entry.set_preserve_mtime(true);
+
+        let path = dir.join(entry.path()?);
+        // Entries pointing outside of `dir` are skipped
+        if !entry.unpack_in(dir)? {
+            continue;
+        }
+
+        #[cfg(unix)]
+        {
+            let header = entry.header();
+            let uid = header.uid().ok().and_then(|uid| u32::try_from(uid).ok());
+            let gid = header.gid().ok().and_then(|gid| u32::try_from(gid).ok());
+            if std::os::unix::fs::lchown(&path, uid, gid).is_err() {
+                ownership_preserved = false;
+            }
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = path;
+            ownership_preserved = false;
+        }
+    }
+
+    if !ownership_preserved {
+        debug!("Could not restore the owners of some copied files");
+    }
+    Ok(ownership_preserved)
+}
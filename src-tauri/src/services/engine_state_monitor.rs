@@ -445,9 +445,23 @@ impl EngineStateMonitor {
             }
         }
 
+        // Images are compared by ID and all of their tags and digests, so commits,
+        // retags and removed tags show up even when the number of images stays the same
+        if old_state.images.len() != new_state.images.len() {
+            return true;
+        }
+
+        for (id, new_image) in &new_state.images {
+            match old_state.images.get(id) {
+                Some(old_image)
+                    if old_image.repo_tags == new_image.repo_tags
+                        && old_image.repo_digests == new_image.repo_digests => {}
+                _ => return true,
+            }
+        }
+
         // Check other entities for changes
-        old_state.images.len() != new_state.images.len()
-            || old_state.volumes.len() != new_state.volumes.len()
+        old_state.volumes.len() != new_state.volumes.len()
             || old_state.networks.len() != new_state.networks.len()
             || old_state.engine_status != new_state.engine_status
     }
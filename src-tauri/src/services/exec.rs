@@ -0,0 +1,317 @@
+use crate::entities::{ExecOutput, ExecSessionClosed, ExecSessionOptions};
+use crate::services::SubscriptionRegistry;
+use bollard::container::LogOutput;
+use bollard::exec::{CreateExecOptions, ResizeExecOptions, StartExecOptions, StartExecResults};
+use bollard::Docker;
+use futures_util::{Stream, StreamExt};
+use std::pin::Pin;
+use tauri::{AppHandle, Emitter};
+use tokio::io::{AsyncWrite, AsyncWriteExt};
+use tokio::sync::mpsc;
+use tracing::{debug, instrument, warn};
+use uuid::Uuid;
+
+/// Starts bash when it is available and falls back to sh otherwise.
+const DEFAULT_SHELL_COMMAND: [&str; 3] = [
+    "/bin/sh",
+    "-c",
+    "if [ -x /bin/bash ]; then exec /bin/bash; else exec /bin/sh; fi",
+];
+
+type ExecOutputStream =
+    Pin<Box<dyn Stream<Item = Result<LogOutput, bollard::errors::Error>> + Send>>;
+type ExecInput = Pin<Box<dyn AsyncWrite + Send>>;
+
+/// Handle kept for every running session so commands can reach it by session id.
+#[derive(Debug, Clone)]
+struct ExecSessionHandle {
+    exec_id: String,
+    stdin: mpsc::UnboundedSender<Vec<u8>>,
+}
+
+lazy_static::lazy_static! {
+    static ref EXEC_SESSIONS: SubscriptionRegistry<ExecSessionHandle> = SubscriptionRegistry::default();
+}
+
+#[derive(Default, Debug)]
+pub struct ExecService {}
+
+impl ExecService {
+    /// Start an interactive process with a TTY inside a running container.
+    /// Output is emitted as `exec-output` events and `exec-closed` is sent
+    /// once the process exits. Returns the session id.
+    #[instrument(skip_all, err)]
+    pub async fn open_session(
+        app_handle: AppHandle,
+        docker: Docker,
+        container_id: &str,
+        options: ExecSessionOptions,
+    ) -> Result<String, String> {
+        let command = options.command.unwrap_or_else(|| {
+            DEFAULT_SHELL_COMMAND
+                .iter()
+                .map(|arg| arg.to_string())
+                .collect()
+        });
+
+        let mut env = vec!["TERM=xterm-256color".to_string()];
+        env.extend(options.env);
+
+        let exec = docker
+            .create_exec(
+                container_id,
+                CreateExecOptions::<String> {
+                    attach_stdin: Some(true),
+                    attach_stdout: Some(true),
+                    attach_stderr: Some(true),
+                    tty: Some(true),
+                    cmd: Some(command),
+                    env: Some(env),
+                    user: options.user,
+                    working_dir: options.working_dir,
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| format!("Failed to create exec instance: {}", e))?;
+
+        let start_options = StartExecOptions {
+            detach: false,
+            tty: true,
+            output_capacity: None,
+        };
+        let (output, input) = match docker
+            .start_exec(&exec.id, Some(start_options))
+            .await
+            .map_err(|e| format!("Failed to start exec instance: {}", e))?
+        {
+            StartExecResults::Attached { output, input } => (output, input),
+            StartExecResults::Detached => {
+                return Err("Exec instance started detached, cannot attach".to_string())
+            }
+        };
+
+        if let (Some(cols), Some(rows)) = (options.cols, options.rows) {
+            if let Err(e) = Self::resize_exec(&docker, &exec.id, cols, rows).await {
+                warn!("Failed to set initial terminal size: {}", e);
+            }
+        }
+
+        let session_id = Uuid::new_v4().to_string();
+        debug!(
+            "Opened exec session {} ({}) in container {}",
+            session_id, exec.id, container_id
+        );
+
+        let (stdin_tx, stdin_rx) = mpsc::unbounded_channel();
+        let handle = ExecSessionHandle {
+            exec_id: exec.id.clone(),
+            stdin: stdin_tx,
+        };
+
+        EXEC_SESSIONS
+            .spawn_with(
+                session_id.clone(),
+                handle,
+                Self::run_session(
+                    app_handle,
+                    docker,
+                    session_id.clone(),
+                    exec.id,
+                    output,
+                    input,
+                    stdin_rx,
+                ),
+            )
+            .await?;
+
+        Ok(session_id)
+    }
+
+    /// Send keyboard input to the process of a session.
+    #[instrument(skip_all, err)]
+    pub async fn write_stdin(session_id: &str, data: &str) -> Result<(), String> {
+        let session = Self::get_session(session_id).await?;
+        session
+            .stdin
+            .send(data.as_bytes().to_vec())
+            .map_err(|_| format!("Exec session {} is closed", session_id))
+    }
+
+    /// Resize the TTY of a session after the terminal view changed size.
+    #[instrument(skip_all, err)]
+    pub async fn resize(
+        docker: &Docker,
+        session_id: &str,
+        cols: u16,
+        rows: u16,
+    ) -> Result<(), String> {
+        let session = Self::get_session(session_id).await?;
+        Self::resize_exec(docker, &session.exec_id, cols, rows).await
+    }
+
+    /// Close a session. Dropping the attached connection hangs up the process.
+    #[instrument(skip_all, err)]
+    pub async fn close_session(session_id: &str) -> Result<(), String> {
+        if EXEC_SESSIONS.cancel(session_id).await {
+            Ok(())
+        } else {
+            Err(format!("Exec session {} not found", session_id))
+        }
+    }
+
+    async fn get_session(session_id: &str) -> Result<ExecSessionHandle, String> {
+        EXEC_SESSIONS
+            .get(session_id)
+            .await
+            .ok_or_else(|| format!("Exec session {} not found", session_id))
+    }
+
+    async fn resize_exec(
+        docker: &Docker,
+        exec_id: &str,
+        cols: u16,
+        rows: u16,
+    ) -> Result<(), String> {
+        if cols == 0 || rows == 0 {
+            return Err("Terminal size must be greater than 0".to_string());
+        }
+
+        docker
+            .resize_exec(
+                exec_id,
+                ResizeExecOptions {
+                    width: cols,
+                    height: rows,
+                },
+            )
+            .await
+            .map_err(|e| format!("Failed to resize terminal: {}", e))
+    }
+
+    async fn run_session(
+        app_handle: AppHandle,
+        docker: Docker,
+        session_id: String,
+        exec_id: String,
+        mut output: ExecOutputStream,
+        mut input: ExecInput,
+        mut stdin_rx: mpsc::UnboundedReceiver<Vec<u8>>,
+    ) {
+        let mut decoder = Utf8StreamDecoder::default();
+
+        let error = loop {
+            tokio::select! {
+                chunk = output.next() => match chunk {
+                    Some(Ok(chunk)) => {
+                        let data = decoder.decode(&chunk.into_bytes());
+                        if data.is_empty() {
+                            continue;
+                        }
+
+                        let event = ExecOutput {
+                            session_id: session_id.clone(),
+                            data,
+                        };
+                        if let Err(e) = app_handle.emit("exec-output", &event) {
+                            warn!("Failed to emit exec output: {}", e);
+                        }
+                    }
+                    Some(Err(e)) => break Some(format!("Error reading exec output: {}", e)),
+                    None => break None,
+                },
+                data = stdin_rx.recv() => match data {
+                    Some(data) => {
+                        if let Err(e) = input.write_all(&data).await {
+                            break Some(format!("Failed to write to exec stdin: {}", e));
+                        }
+                        if let Err(e) = input.flush().await {
+                            break Some(format!("Failed to write to exec stdin: {}", e));
+                        }
+                    }
+                    None => break None,
+                },
+            }
+        };
+
+        let exit_code = match docker.inspect_exec(&exec_id).await {
+            Ok(inspect) => inspect.exit_code,
+            Err(e) => {
+                debug!("Failed to inspect finished exec {}: {}", exec_id, e);
+                None
+            }
+        };
+
+        let closed = ExecSessionClosed {
+            session_id,
+            exit_code,
+            error,
+        };
+        if let Err(e) = app_handle.emit("exec-closed", &closed) {
+            warn!("Failed to emit exec session closed event: {}", e);
+        }
+    }
+}
+
+/// Turns a byte stream into text without breaking multi-byte characters
+/// that are split across two chunks.
+#[derive(Default, Debug)]
+struct Utf8StreamDecoder {
+    pending: Vec<u8>,
+}
+
+impl Utf8StreamDecoder {
+    fn decode(&mut self, chunk: &[u8]) -> String {
+        self.pending.extend_from_slice(chunk);
+
+        let mut text = String::new();
+        loop {
+            match std::str::from_utf8(&self.pending) {
+                Ok(valid) => {
+                    text.push_str(valid);
+                    self.pending.clear();
+                    return text;
+                }
+                Err(e) => {
+                    let valid_up_to = e.valid_up_to();
+                    // The prefix up to `valid_up_to` is valid UTF-8
+                    text.push_str(&String::from_utf8_lossy(&self.pending[..valid_up_to]));
+
+                    match e.error_len() {
+                        // Incomplete character at the end, wait for the next chunk
+                        None => {
+                            self.pending.drain(..valid_up_to);
+                            return text;
+                        }
+                        // Invalid bytes, replace them and keep going
+                        Some(len) => {
+                            text.push(char::REPLACEMENT_CHARACTER);
+                            self.pending.drain(..valid_up_to + len);
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decoder_keeps_split_characters() {
+        let mut decoder = Utf8StreamDecoder::default();
+        let bytes = "héllo".as_bytes();
+
+        // Split in the middle of the two-byte "é"
+        assert_eq!(decoder.decode(&bytes[..2]), "h");
+        assert_eq!(decoder.decode(&bytes[2..]), "éllo");
+    }
+
+    #[test]
+    fn test_decoder_replaces_invalid_bytes() {
+        let mut decoder = Utf8StreamDecoder::default();
+        assert_eq!(decoder.decode(b"a\xffb"), "a\u{fffd}b");
+    }
+}
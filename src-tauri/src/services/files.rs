@@ -0,0 +1,536 @@
+use crate::entities::{ChangeNode, ContainerChanges, FileEntry, FileEntryType, FilePreview};
+use crate::services::ProcessesService;
+use bollard::container::DownloadFromContainerOptions;
+use bollard::Docker;
+use futures_util::StreamExt;
+use std::io::{self, Read};
+use std::path::{Component, Path};
+use tokio_util::io::{StreamReader, SyncIoBridge};
+use tracing::{debug, instrument};
+
+/// Default amount of data returned by a file preview.
+const DEFAULT_PREVIEW_BYTES: u64 = 64 * 1024;
+
+/// Upper bound for a file preview, bigger files should be copied out instead.
+const MAX_PREVIEW_BYTES: u64 = 1024 * 1024;
+
+/// How many symlinks are followed before giving up on a preview.
+const MAX_SYMLINK_DEPTH: usize = 8;
+
+/// Changed paths whose size is looked up, one archive request each.
+const MAX_SIZED_CHANGES: usize = 500;
+
+/// Archive requests running at the same time while sizes are looked up.
+const STAT_CONCURRENCY: usize = 8;
+
+/// Prints the `stat` fields, name and link target of every entry of the directory
+/// given as `$1`, each terminated by a NUL byte. Needs `sh`, `stat` and `readlink`.
+const LIST_SCRIPT: &str = r#"cd -- "$1" || exit 1
+for f in * .[!.]* ..?*; do
+  [ -e "$f" ] || [ -L "$f" ] || continue
+  meta=$(stat -c '%f %s %Y %u %g' -- "$f") || exit 1
+  printf '%s\0%s\0%s\0' "$meta" "$f" "$(readlink -- "$f")"
+done"#;
+
+type ArchiveReader = tar::Archive<Box<dyn Read + Send>>;
+
+enum PreviewStep {
+    Preview(FilePreview),
+    /// The path is a symlink, preview its target instead.
+    Symlink(String),
+}
+
+/// Browses container filesystems through the archive API. Unlike `exec`,
+/// this works for stopped containers and for images that ship without `ls`.
+/// Only directory listings try `stat` through `exec` first, as the archive API
+/// hands out a directory with everything below it.
+#[derive(Default, Debug)]
+pub struct FilesService {}
+
+impl FilesService {
+    /// List the direct children of a directory, directories first.
+    #[instrument(skip_all, err)]
+    pub async fn list_directory(
+        docker: &Docker,
+        container_id: &str,
+        path: &str,
+    ) -> Result<Vec<FileEntry>, String> {
+        let path = normalize_path(path)?;
+        debug!("Listing {} in container {}", path, container_id);
+
+        let mut entries = match Self::list_directory_with_stat(docker, container_id, &path).await {
+            Ok(entries) => entries,
+            Err(e) => {
+                debug!(
+                    "stat failed for container {}, reading the archive of {}: {}",
+                    container_id, path, e
+                );
+                Self::list_directory_from_archive(docker, container_id, &path).await?
+            }
+        };
+
+        entries.sort_by(|a, b| {
+            let a_dir = a.entry_type == FileEntryType::Directory;
+            let b_dir = b.entry_type == FileEntryType::Directory;
+            b_dir.cmp(&a_dir).then_with(|| a.name.cmp(&b.name))
+        });
+
+        Ok(entries)
+    }
+
+    /// List a directory of a running container without transferring its content.
+    async fn list_directory_with_stat(
+        docker: &Docker,
+        container_id: &str,
+        path: &str,
+    ) -> Result<Vec<FileEntry>, String> {
+        let output = ProcessesService::run_command(
+            docker,
+            container_id,
+            &["sh", "-c", LIST_SCRIPT, "sh", path],
+        )
+        .await?;
+        parse_listing(path, &output)
+    }
+
+    /// List a directory from its archive. Nested entries are skipped, but still
+    /// have to be read as the engine sends them in between the direct children.
+    async fn list_directory_from_archive(
+        docker: &Docker,
+        container_id: &str,
+        path: &str,
+    ) -> Result<Vec<FileEntry>, String> {
+        // A trailing slash makes the engine follow a symlinked directory
+        let request_path = if path == "/" {
+            path.to_string()
+        } else {
+            format!("{}/", path)
+        };
+
+        let dir = path.to_string();
+        Self::read_archive(docker, container_id, &request_path, move |mut archive| {
+            let mut entries = Vec::new();
+            let mut root_depth = None;
+
+            for entry in archive.entries().map_err(|e| e.to_string())? {
+                let entry = entry.map_err(|e| e.to_string())?;
+                let entry_path = entry.path().map_err(|e| e.to_string())?.into_owned();
+                let depth = path_depth(&entry_path);
+
+                // The first entry is the directory itself, everything else is nested in it
+                let Some(root_depth) = root_depth else {
+                    if !entry.header().entry_type().is_dir() {
+                        return Err(format!("{} is not a directory", dir));
+                    }
+                    root_depth = Some(depth);
+                    continue;
+                };
+
+                if depth != root_depth + 1 {
+                    continue;
+                }
+
+                let Some(name) = entry_path
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                else {
+                    continue;
+                };
+                let full_path = join_path(&dir, &name);
+                entries.push(to_file_entry(&entry, name, full_path));
+            }
+
+            Ok(entries)
+        })
+        .await
+    }
+
+    /// Paths added, modified or deleted in a container relative to its image,
+    /// grouped into a tree. Sizes of changed files are joined in from the archive API.
+    #[instrument(skip_all, err)]
+    pub async fn container_changes(
+        docker: &Docker,
+        container_id: &str,
+    ) -> Result<ContainerChanges, String> {
+        let changes = docker
+            .container_changes(container_id)
+            .await
+            .map_err(|e| format!("Failed to get container changes: {}", e))?
+            .unwrap_or_default();
+        debug!(
+            "Container {} has {} changed paths",
+            container_id,
+            changes.len()
+        );
+
+        let mut root = ChangeNode::tree(
+            changes
+                .into_iter()
+                .map(|change| (change.path, change.kind.into())),
+        );
+
+        let leaves = root.existing_leaves();
+        let sizes_complete = leaves.len() <= MAX_SIZED_CHANGES;
+        let entries: Vec<FileEntry> = futures_util::stream::iter(leaves)
+            .take(MAX_SIZED_CHANGES)
+            .map(|path| async move { Self::stat_path(docker, container_id, &path).await })
+            .buffer_unordered(STAT_CONCURRENCY)
+            // Paths can disappear between listing the changes and looking them up
+            .filter_map(|entry| async move { entry.ok() })
+            .collect()
+            .await;
+
+        for entry in entries {
+            if let Some(node) = root.find_mut(&entry.path) {
+                node.entry_type = Some(entry.entry_type);
+                node.size = Some(entry.size);
+            }
+        }
+
+        Ok(ContainerChanges {
+            container_id: container_id.to_string(),
+            root,
+            sizes_complete,
+        })
+    }
+
+    /// Describe a single path without following it if it is a symlink.
+    #[instrument(skip_all, err)]
+    pub async fn stat_path(
+        docker: &Docker,
+        container_id: &str,
+        path: &str,
+    ) -> Result<FileEntry, String> {
+        let path = normalize_path(path)?;
+        debug!("Stat {} in container {}", path, container_id);
+
+        let full_path = path.clone();
+        Self::read_archive(docker, container_id, &path, move |mut archive| {
+            let entry = archive
+                .entries()
+                .map_err(|e| e.to_string())?
+                .next()
+                .ok_or_else(|| format!("{} not found", full_path))?
+                .map_err(|e| e.to_string())?;
+
+            let name = Path::new(&full_path)
+                .file_name()
+                .map(|n| n.to_string_lossy().to_string())
+                .unwrap_or_else(|| "/".to_string());
+            Ok(to_file_entry(&entry, name, full_path))
+        })
+        .await
+    }
+
+    /// Read the beginning of a text file. Symlinks are followed, binary files
+    /// are reported as such without content.
+    #[instrument(skip_all, err)]
+    pub async fn preview_file(
+        docker: &Docker,
+        container_id: &str,
+        path: &str,
+        max_bytes: Option<u64>,
+    ) -> Result<FilePreview, String> {
+        let limit = max_bytes
+            .unwrap_or(DEFAULT_PREVIEW_BYTES)
+            .clamp(1, MAX_PREVIEW_BYTES);
+        let mut path = normalize_path(path)?;
+        debug!("Previewing {} in container {}", path, container_id);
+
+        for _ in 0..=MAX_SYMLINK_DEPTH {
+            let file_path = path.clone();
+            let preview = Self::read_archive(docker, container_id, &path, move |mut archive| {
+                let mut entry = archive
+                    .entries()
+                    .map_err(|e| e.to_string())?
+                    .next()
+                    .ok_or_else(|| format!("{} not found", file_path))?
+                    .map_err(|e| e.to_string())?;
+
+                match entry.header().entry_type() {
+                    tar::EntryType::Symlink => {
+                        let target = entry
+                            .link_name()
+                            .map_err(|e| e.to_string())?
+                            .ok_or_else(|| format!("{} is a broken symlink", file_path))?;
+                        return Ok(PreviewStep::Symlink(resolve_link(
+                            &file_path,
+                            &target.to_string_lossy(),
+                        )));
+                    }
+                    tar::EntryType::Directory => {
+                        return Err(format!("{} is a directory", file_path));
+                    }
+                    entry_type if !entry_type.is_file() => {
+                        return Err(format!("{} is not a regular file", file_path));
+                    }
+                    _ => {}
+                }
+
+                let size = entry.size();
+                let mut buffer = Vec::new();
+                (&mut entry)
+                    .take(limit)
+                    .read_to_end(&mut buffer)
+                    .map_err(|e| e.to_string())?;
+                let truncated = size > buffer.len() as u64;
+
+                let (content, binary) = decode_text(&buffer, truncated);
+                Ok(PreviewStep::Preview(FilePreview {
+                    path: file_path,
+                    size,
+                    content,
+                    truncated,
+                    binary,
+                }))
+            })
+            .await?;
+
+            match preview {
+                PreviewStep::Preview(preview) => return Ok(preview),
+                PreviewStep::Symlink(target) => path = target,
+            }
+        }
+
+        Err("Too many levels of symbolic links".to_string())
+    }
+
+    /// Check whether `path` exists inside the container, without following symlinks.
+    #[instrument(skip_all, err)]
+    pub async fn path_exists(
+        docker: &Docker,
+        container_id: &str,
+        path: &str,
+    ) -> Result<bool, String> {
+        let mut stream = docker.download_from_container(
+            container_id,
+            Some(DownloadFromContainerOptions {
+                path: path.to_string(),
+            }),
+        );
+
+        // Only the response status matters, the rest of the archive is dropped
+        match stream.next().await {
+            Some(Ok(_)) | None => Ok(true),
+            Some(Err(bollard::errors::Error::DockerResponseServerError {
+                status_code: 404,
+                message,
+            })) if !message.contains("No such container") => Ok(false),
+            Some(Err(e)) => Err(format!("Failed to check {}: {}", path, e)),
+        }
+    }
+
+    /// Download `path` as a tar archive and hand it to `read` on a blocking thread.
+    /// The archive is streamed, so `read` can stop early without fetching the rest.
+    async fn read_archive<T, F>(
+        docker: &Docker,
+        container_id: &str,
+        path: &str,
+        read: F,
+    ) -> Result<T, String>
+    where
+        T: Send + 'static,
+        F: FnOnce(ArchiveReader) -> Result<T, String> + Send + 'static,
+    {
+        let stream = docker
+            .download_from_container(
+                container_id,
+                Some(DownloadFromContainerOptions {
+                    path: path.to_string(),
+                }),
+            )
+            .map(|chunk| chunk.map_err(io::Error::other));
+        let reader = SyncIoBridge::new(StreamReader::new(Box::pin(stream)));
+        let archive = tar::Archive::new(Box::new(reader) as Box<dyn Read + Send>);
+
+        tokio::task::spawn_blocking(move || read(archive))
+            .await
+            .map_err(|e| format!("Failed to read container archive: {}", e))?
+            .map_err(|e| format!("Failed to read {}: {}", path, e))
+    }
+}
+
+fn to_file_entry<R: Read>(entry: &tar::Entry<R>, name: String, path: String) -> FileEntry {
+    let header = entry.header();
+    let link_target = entry
+        .link_name()
+        .ok()
+        .flatten()
+        .map(|target| target.to_string_lossy().to_string());
+
+    FileEntry {
+        name,
+        path,
+        entry_type: header.entry_type().into(),
+        size: entry.size(),
+        mode: header.mode().unwrap_or(0) & 0o7777,
+        mtime: header.mtime().unwrap_or(0),
+        uid: header.uid().unwrap_or(0),
+        gid: header.gid().unwrap_or(0),
+        link_target,
+    }
+}
+
+/// Turn the output of `LIST_SCRIPT` into entries of `dir`.
+fn parse_listing(dir: &str, output: &str) -> Result<Vec<FileEntry>, String> {
+    let fields: Vec<&str> = output.split('\0').collect();
+    fields
+        .chunks_exact(3)
+        .map(|entry| {
+            let (meta, name, link_target) = (entry[0], entry[1], entry[2]);
+            let meta: Vec<u64> = meta
+                .split(' ')
+                .enumerate()
+                .map(|(i, field)| match i {
+                    0 => u64::from_str_radix(field, 16),
+                    _ => field.parse(),
+                })
+                .collect::<Result<_, _>>()
+                .map_err(|e| format!("Invalid stat output for {}: {}", name, e))?;
+            let [raw_mode, size, mtime, uid, gid] = meta[..] else {
+                return Err(format!("Invalid stat output for {}", name));
+            };
+
+            let entry_type = match raw_mode & 0o170000 {
+                0o100000 => FileEntryType::File,
+                0o040000 => FileEntryType::Directory,
+                0o120000 => FileEntryType::Symlink,
+                _ => FileEntryType::Other,
+            };
+            Ok(FileEntry {
+                name: name.to_string(),
+                path: join_path(dir, name),
+                link_target: (entry_type == FileEntryType::Symlink)
+                    .then(|| link_target.to_string()),
+                entry_type,
+                size,
+                mode: (raw_mode & 0o7777) as u32,
+                mtime,
+                uid,
+                gid,
+            })
+        })
+        .collect()
+}
+
+/// Content as text, or `(empty, true)` for binary data. A multi-byte
+/// character cut off by truncation does not make the file binary.
+fn decode_text(buffer: &[u8], truncated: bool) -> (String, bool) {
+    if buffer.contains(&0) {
+        return (String::new(), true);
+    }
+
+    match std::str::from_utf8(buffer) {
+        Ok(text) => (text.to_string(), false),
+        Err(e) if truncated && e.error_len().is_none() => (
+            String::from_utf8_lossy(&buffer[..e.valid_up_to()]).to_string(),
+            false,
+        ),
+        Err(_) => (String::new(), true),
+    }
+}
+
+/// Number of named components, ignoring `/` and `.` so that the entries
+/// of `/`, `./etc` and `etc` are all compared the same way.
+fn path_depth(path: &Path) -> usize {
+    path.components()
+        .filter(|c| matches!(c, Component::Normal(_) | Component::ParentDir))
+        .count()
+}
+
+pub(crate) fn normalize_path(path: &str) -> Result<String, String> {
+    if !path.starts_with('/') {
+        return Err(format!("Path must be absolute: {}", path));
+    }
+
+    let trimmed = path.trim_end_matches('/');
+    Ok(if trimmed.is_empty() {
+        "/".to_string()
+    } else {
+        trimmed.to_string()
+    })
+}
+
+pub(crate) fn join_path(dir: &str, name: &str) -> String {
+    if dir == "/" {
+        format!("/{}", name)
+    } else {
+        format!("{}/{}", dir, name)
+    }
+}
+
+/// Absolute path a symlink at `link_path` points to.
+fn resolve_link(link_path: &str, target: &str) -> String {
+    let mut components: Vec<&str> = Vec::new();
+    if !target.starts_with('/') {
+        components.extend(link_path.split('/').filter(|c| !c.is_empty()));
+        components.pop();
+    }
+
+    for component in target.split('/') {
+        match component {
+            "" | "." => {}
+            ".." => {
+                components.pop();
+            }
+            component => components.push(component),
+        }
+    }
+
+    format!("/{}", components.join("/"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_link() {
+        assert_eq!(resolve_link("/lib", "usr/lib"), "/usr/lib");
+        assert_eq!(
+            resolve_link("/etc/localtime", "../usr/share/zoneinfo/UTC"),
+            "/usr/share/zoneinfo/UTC"
+        );
+        assert_eq!(resolve_link("/usr/bin/sh", "/bin/dash"), "/bin/dash");
+        assert_eq!(resolve_link("/a", "../../b"), "/b");
+    }
+
+    #[test]
+    fn test_path_depth_ignores_root_and_current_dir() {
+        assert_eq!(path_depth(Path::new("/")), 0);
+        assert_eq!(path_depth(Path::new("./")), 0);
+        assert_eq!(path_depth(Path::new("etc")), 1);
+        assert_eq!(path_depth(Path::new("./etc/passwd")), 2);
+    }
+
+    #[test]
+    fn test_parse_listing() {
+        let output = "41ed 4096 1700000000 0 0\0etc\0\0\
+                      a1ff 7 1700000001 0 0\0lib\0usr/lib\0\
+                      81a4 12 1700000002 1000 1000\0.env\0\0";
+        let entries = parse_listing("/app", output).unwrap();
+
+        assert_eq!(entries.len(), 3);
+        assert_eq!(entries[0].entry_type, FileEntryType::Directory);
+        assert_eq!(entries[0].mode, 0o755);
+        assert_eq!(entries[0].path, "/app/etc");
+        assert_eq!(entries[0].link_target, None);
+        assert_eq!(entries[1].entry_type, FileEntryType::Symlink);
+        assert_eq!(entries[1].link_target.as_deref(), Some("usr/lib"));
+        assert_eq!(entries[2].entry_type, FileEntryType::File);
+        assert_eq!((entries[2].size, entries[2].uid), (12, 1000));
+        assert_eq!(entries[2].mtime, 1700000002);
+
+        assert!(parse_listing("/", "").unwrap().is_empty());
+        assert!(parse_listing("/", "zz\0bad\0\0").is_err());
+    }
+
+    #[test]
+    fn test_decode_text() {
+        assert_eq!(decode_text(b"hello", false), ("hello".to_string(), false));
+        assert_eq!(decode_text(b"\x7fELF\0\0", false), (String::new(), true));
+        // "é" cut in half by the preview limit
+        assert_eq!(decode_text(b"caf\xc3", true), ("caf".to_string(), false));
+        assert_eq!(decode_text(b"caf\xc3", false), (String::new(), true));
+    }
+}
@@ -1,10 +1,36 @@
-use crate::entities::{Image, PruneResult};
+use crate::entities::{
+    registry_host, server_address, split_reference, validate_image_reference, with_default_tag,
+    BuildImageOptions, BuildOutputTracker, BuildProgress, BuildResult, CredentialHelperReply,
+    DockerConfigFile, DockerIgnore, Image, ImageDetails, LayerProgressTracker, PruneResult,
+    PullProgress, PullResult, PushProgress, PushProgressTracker, PushResult, RegistryCredentials,
+};
+use crate::services::SubscriptionRegistry;
 use bollard::container::ListContainersOptions;
-use bollard::image::{CreateImageOptions, ListImagesOptions, RemoveImageOptions};
+use bollard::image::{
+    BuildImageOptions as BollardBuildImageOptions, CreateImageOptions, ListImagesOptions,
+    PushImageOptions, RemoveImageOptions, TagImageOptions,
+};
 use bollard::models::ImageSummary;
 use bollard::Docker;
+use futures_util::StreamExt;
 use std::collections::{HashMap, HashSet};
-use tracing::{debug, instrument};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::oneshot;
+use tracing::{debug, instrument, warn};
+use uuid::Uuid;
+
+/// Minimum time between two progress events of a pull that only advance byte counts.
+const LAYER_PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+lazy_static::lazy_static! {
+    static ref IMAGE_PULLS: SubscriptionRegistry = SubscriptionRegistry::default();
+}
 
 #[derive(Default, Debug)]
 pub struct ImagesService {}
@@ -48,56 +74,29 @@ impl ImagesService {
 
         let result: Vec<Image> = images
             .iter()
-            .map(|image| {
-                let mut image_name = None;
-                let mut image_tag = None;
-
-                if !image.repo_tags.is_empty() {
-                    let tag_full = image.repo_tags[0].clone();
-                    let parts: Vec<&str> = tag_full.splitn(2, ':').collect();
-
-                    if parts.len() == 2 {
-                        image_name = Some(parts[0].to_string());
-                        image_tag = Some(parts[1].to_string());
-                    } else if parts.len() == 1 {
-                        // Handle case where there's no tag (just repository name)
-                        image_name = Some(parts[0].to_string());
-                        image_tag = None;
-                    }
-                } else {
-                    let id = image.id.clone();
-                    if id.starts_with("sha256:") {
-                        // For untagged images with SHA256 IDs, use a shortened version as name
-                        // and indicate it's untagged rather than using a misleading tag
-                        image_name = Some(format!(
-                            "<untagged>@{}",
-                            id.split(":").nth(1).unwrap_or("unknown")
-                        ));
-                        image_tag = None; // Keep as None to indicate no tag
-                    } else {
-                        image_name = Some(format!("<untagged>@{}", id));
-                        image_tag = None; // Keep as None to indicate no tag
-                    }
-                }
-
-                // Check if this image is in use by any container
-                let in_use = used_image_ids.contains(&image.id);
-
-                Image {
-                    id: image.id.clone(),
-                    repository: image_name,
-                    tag: image_tag,
-                    image_id: image.id.clone(),
-                    created: image.created,
-                    size: image.size,
-                    in_use,
-                }
-            })
+            .map(|image| Image::new(image, used_image_ids.contains(&image.id)))
             .collect();
 
         Ok(result)
     }
 
+    /// Inspect an image along with the history of its layers.
+    #[instrument(skip_all, err)]
+    pub async fn inspect_image(docker: &Docker, image: &str) -> Result<ImageDetails, String> {
+        let inspect = docker
+            .inspect_image(image)
+            .await
+            .map_err(|e| format!("Failed to inspect image {}: {}", image, e))?;
+        let history = docker
+            .image_history(image)
+            .await
+            .map_err(|e| format!("Failed to get history of image {}: {}", image, e))?;
+
+        let mut details = ImageDetails::from(inspect);
+        details.history = history.into_iter().map(|item| item.into()).collect();
+        Ok(details)
+    }
+
     #[instrument(skip_all, err)]
     pub async fn perform_prune(docker: &Docker) -> Result<PruneResult, String> {
         // By default Docker only prunes dangling (untagged) images.
@@ -145,104 +144,523 @@ impl ImagesService {
         Ok(())
     }
 
+    /// Remove one tag of an image without deleting the image. The last tag of an
+    /// image cannot be removed this way, as the engine would delete the image with it.
+    #[instrument(skip_all, err)]
+    pub async fn untag_image(docker: &Docker, reference: &str) -> Result<(), String> {
+        let reference = with_default_tag(reference);
+        debug!("Untagging image: {}", reference);
+
+        let image = docker
+            .inspect_image(&reference)
+            .await
+            .map_err(|e| format!("Failed to inspect image {}: {}", reference, e))?;
+        let repo_tags = image.repo_tags.unwrap_or_default();
+        if !repo_tags.contains(&reference) {
+            return Err(format!("{} is not a tag of an image", reference));
+        }
+        if repo_tags.len() == 1 {
+            return Err(format!(
+                "{} is the only tag of image {}, delete the image instead",
+                reference,
+                image.id.unwrap_or_default()
+            ));
+        }
+
+        let options = RemoveImageOptions {
+            force: false,
+            noprune: true,
+        };
+        docker
+            .remove_image(&reference, Some(options), None)
+            .await
+            .map_err(|e| format!("Failed to untag image {}: {}", reference, e))?;
+
+        debug!("Successfully untagged image: {}", reference);
+        Ok(())
+    }
+
+    /// Pull an image, emitting `image-pull-progress` events with the progress of
+    /// every layer, and a last one with `done` set. The pull runs until the engine
+    /// reports the digest or final status, or until it is cancelled with `cancel_pull`.
     #[instrument(skip_all, err)]
     pub async fn pull_image(
+        app_handle: AppHandle,
         docker: &Docker,
         image_name: &str,
         tag: &str,
         registry: &str,
-    ) -> Result<(), String> {
-        debug!(
-            "Pulling image: {}:{} from registry: {}",
-            image_name, tag, registry
-        );
-
-        // Construct the full image name with tag
-        let full_image_name = if registry != "docker.io" {
+        pull_id: Option<String>,
+    ) -> Result<PullResult, String> {
+        let image = if registry != "docker.io" {
             format!("{}/{}:{}", registry, image_name, tag)
         } else {
             format!("{}:{}", image_name, tag)
         };
+        let pull_id = pull_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+        debug!("Pulling image {} as {}", image, pull_id);
+
+        let (sender, receiver) = oneshot::channel();
+        IMAGE_PULLS
+            .spawn(
+                pull_id.clone(),
+                Self::stream_pull(
+                    app_handle.clone(),
+                    docker.clone(),
+                    image.clone(),
+                    pull_id.clone(),
+                    sender,
+                ),
+            )
+            .await
+            .map_err(|e| format!("Failed to start pull: {}", e))?;
+
+        match receiver.await {
+            Ok(result) => result,
+            // The task was aborted before it could report back
+            Err(_) => {
+                let error = format!("Pull of {} was cancelled", image);
+                let progress = PullProgress {
+                    pull_id,
+                    image,
+                    layers: Vec::new(),
+                    percent: 0.0,
+                    status: None,
+                    digest: None,
+                    done: true,
+                    error: Some(error.clone()),
+                };
+                if let Err(e) = app_handle.emit("image-pull-progress", &progress) {
+                    warn!("Failed to emit image pull progress: {}", e);
+                }
+                Err(error)
+            }
+        }
+    }
+
+    /// Abort a running pull. The engine stops pulling once the connection is closed.
+    #[instrument(skip_all, err)]
+    pub async fn cancel_pull(pull_id: &str) -> Result<(), String> {
+        if IMAGE_PULLS.cancel(pull_id).await {
+            Ok(())
+        } else {
+            Err(format!("Pull {} not found", pull_id))
+        }
+    }
+
+    async fn stream_pull(
+        app_handle: AppHandle,
+        docker: Docker,
+        image: String,
+        pull_id: String,
+        sender: oneshot::Sender<Result<PullResult, String>>,
+    ) {
+        let options = CreateImageOptions {
+            from_image: image.clone(),
+            ..Default::default()
+        };
+        let mut stream = docker.create_image(Some(options), None, None);
+        let mut tracker = LayerProgressTracker::default();
+        let mut last_emit = Instant::now();
+
+        while let Some(message) = stream.next().await {
+            let changed = match message {
+                Ok(info) => tracker.update(
+                    info.id,
+                    info.status,
+                    info.progress_detail,
+                    info.error
+                        .or_else(|| info.error_detail.and_then(|detail| detail.message)),
+                ),
+                Err(e) => tracker.update(None, None, None, Some(e.to_string())),
+            };
+            if tracker.error.is_some() {
+                break;
+            }
+            if changed || last_emit.elapsed() >= LAYER_PROGRESS_INTERVAL {
+                last_emit = Instant::now();
+                Self::emit_pull_progress(&app_handle, &pull_id, &image, &tracker, false);
+            }
+        }
+
+        // A pull that went through ends with the digest or a `Status:` line
+        let finished = tracker.digest.is_some()
+            || tracker
+                .status
+                .as_deref()
+                .is_some_and(|status| status.starts_with("Status:"));
+        if tracker.error.is_none() && !finished {
+            tracker.error = Some("The engine ended the pull without a final status".to_string());
+        }
+
+        let result = match &tracker.error {
+            Some(error) => Err(format!("Failed to pull image {}: {}", image, error)),
+            None => {
+                let inspect = docker.inspect_image(&image).await.ok();
+                Ok(PullResult {
+                    pull_id: pull_id.clone(),
+                    image: image.clone(),
+                    image_id: inspect.as_ref().and_then(|inspect| inspect.id.clone()),
+                    digest: tracker.digest.clone().or_else(|| {
+                        inspect
+                            .and_then(|inspect| inspect.repo_digests)
+                            .and_then(|digests| digests.into_iter().next())
+                            .and_then(|digest| digest.split_once('@').map(|(_, d)| d.to_string()))
+                    }),
+                    up_to_date: tracker
+                        .status
+                        .as_deref()
+                        .is_some_and(|status| status.contains("Image is up to date")),
+                })
+            }
+        };
+        match &result {
+            Ok(_) => debug!("Pulled image {}", image),
+            Err(e) => debug!("{}", e),
+        }
+
+        Self::emit_pull_progress(&app_handle, &pull_id, &image, &tracker, true);
+        // The caller may have gone away, nothing left to report to then
+        let _ = sender.send(result);
+    }
+
+    fn emit_pull_progress(
+        app_handle: &AppHandle,
+        pull_id: &str,
+        image: &str,
+        tracker: &LayerProgressTracker,
+        done: bool,
+    ) {
+        let progress = PullProgress {
+            pull_id: pull_id.to_string(),
+            image: image.to_string(),
+            layers: tracker.layers.clone(),
+            percent: match done && tracker.error.is_none() {
+                true => 100.0,
+                false => tracker.percent(),
+            },
+            status: tracker.status.clone(),
+            digest: tracker.digest.clone(),
+            done,
+            error: tracker.error.clone(),
+        };
+        if let Err(e) = app_handle.emit("image-pull-progress", &progress) {
+            warn!("Failed to emit image pull progress: {}", e);
+        }
+    }
+
+    /// Add `target` as a new `repository[:tag]` of an image.
+    #[instrument(skip_all, err)]
+    pub async fn tag_image(docker: &Docker, image: &str, target: &str) -> Result<(), String> {
+        let (repo, tag) = split_reference(target);
+        let errors = validate_image_reference(Some(repo), tag);
+        if !errors.is_empty() {
+            return Err(errors.join("; "));
+        }
+        debug!("Tagging image {} as {}", image, target);
+
+        let options = TagImageOptions {
+            repo,
+            tag: tag.unwrap_or("latest"),
+        };
+        docker
+            .tag_image(image, Some(options))
+            .await
+            .map_err(|e| format!("Failed to tag image {} as {}: {}", image, target, e))
+    }
+
+    /// Push a tag of an image, emitting `image-push-progress` events as layers are
+    /// pushed and a last one with `done` set. Without explicit credentials the
+    /// ones the Docker CLI stored for the registry are used, if any.
+    #[instrument(skip_all, err)]
+    pub async fn push_image(
+        app_handle: AppHandle,
+        docker: &Docker,
+        reference: &str,
+        credentials: Option<RegistryCredentials>,
+        push_id: Option<String>,
+    ) -> Result<PushResult, String> {
+        let image = with_default_tag(reference);
+        if image.contains('@') {
+            return Err(format!("Cannot push {}, only tags can be pushed", image));
+        }
+        let (repository, tag) = split_reference(&image);
+        let host = registry_host(&image);
+        let credentials = match credentials {
+            Some(credentials) => Some(credentials),
+            None => Self::stored_credentials(host).await,
+        };
+        let push_id = push_id.unwrap_or_else(|| Uuid::new_v4().to_string());
+        debug!("Pushing image {} as {}", image, push_id);
+
+        let options = PushImageOptions {
+            tag: tag.unwrap_or("latest"),
+        };
+        let credentials =
+            credentials.map(|credentials| credentials.to_docker(&server_address(host)));
+        let mut stream = docker.push_image(repository, Some(options), credentials);
+        let mut tracker = PushProgressTracker::default();
+
+        while let Some(message) = stream.next().await {
+            let changed = match message {
+                Ok(info) => tracker.update(info.status, info.error),
+                Err(e) => tracker.update(None, Some(e.to_string())),
+            };
+            if tracker.error.is_some() {
+                break;
+            }
+            if changed {
+                Self::emit_push_progress(&app_handle, &push_id, &image, &tracker, false);
+            }
+        }
+        drop(stream);
+
+        // A push that went through ends with the digest of the manifest
+        if tracker.error.is_none() && tracker.digest.is_none() {
+            tracker.error = Some("The engine ended the push without a digest".to_string());
+        }
+        let result = match (&tracker.error, &tracker.digest) {
+            (None, Some(digest)) => Ok(PushResult {
+                push_id: push_id.clone(),
+                image: image.clone(),
+                digest: digest.clone(),
+            }),
+            (error, _) => Err(format!(
+                "Failed to push image {}: {}",
+                image,
+                error.clone().unwrap_or_default()
+            )),
+        };
+        match &result {
+            Ok(_) => debug!("Pushed image {}", image),
+            Err(e) => debug!("{}", e),
+        }
 
-        debug!("Full image name for pull: {}", full_image_name);
+        Self::emit_push_progress(&app_handle, &push_id, &image, &tracker, true);
+        result
+    }
 
-        // Create options for pulling the image
-        let mut options = CreateImageOptions::default();
-        options.from_image = full_image_name.clone();
+    fn emit_push_progress(
+        app_handle: &AppHandle,
+        push_id: &str,
+        image: &str,
+        tracker: &PushProgressTracker,
+        done: bool,
+    ) {
+        let progress = PushProgress {
+            push_id: push_id.to_string(),
+            image: image.to_string(),
+            layers: tracker.layers,
+            layers_done: tracker.layers_done,
+            percent: match done && tracker.error.is_none() {
+                true => 100.0,
+                false => tracker.percent(),
+            },
+            status: tracker.status.clone(),
+            digest: tracker.digest.clone(),
+            done,
+            error: tracker.error.clone(),
+        };
+        if let Err(e) = app_handle.emit("image-push-progress", &progress) {
+            warn!("Failed to emit image push progress: {}", e);
+        }
+    }
 
-        debug!("Pull options: {:?}", options);
+    /// Credentials the Docker CLI stored for a registry. As with the CLI, a credential
+    /// helper configured for the registry takes precedence over the config file.
+    async fn stored_credentials(host: &str) -> Option<RegistryCredentials> {
+        let path = std::env::var_os("DOCKER_CONFIG")
+            .map(PathBuf::from)
+            .or_else(|| dirs::home_dir().map(|home| home.join(".docker")))?
+            .join("config.json");
+        let content = tokio::fs::read_to_string(&path).await.ok()?;
+        let config = match DockerConfigFile::parse(&content) {
+            Ok(config) => config,
+            Err(e) => {
+                warn!("Failed to read {}: {}", path.display(), e);
+                return None;
+            }
+        };
 
-        // Test Docker connectivity first
-        if let Err(e) = docker.ping().await {
+        if let Some(helper) = config.credential_helper(host) {
+            match Self::helper_credentials(helper, &server_address(host)).await {
+                Ok(Some(credentials)) => return Some(credentials),
+                Ok(None) => debug!("No credentials for {} in {}", host, helper),
+                Err(e) => warn!("{}", e),
+            }
+        }
+        config.credentials(host)
+    }
+
+    /// Ask `docker-credential-<helper>` for the credentials of a registry.
+    async fn helper_credentials(
+        helper: &str,
+        server: &str,
+    ) -> Result<Option<RegistryCredentials>, String> {
+        let program = format!("docker-credential-{}", helper);
+        let mut child = tokio::process::Command::new(&program)
+            .arg("get")
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()
+            .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+
+        // The helper reads the server address until stdin is closed
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin
+                .write_all(server.as_bytes())
+                .await
+                .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+        }
+        let output = child
+            .wait_with_output()
+            .await
+            .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+
+        // Helpers exit with an error when they have nothing stored for the server
+        if !output.status.success() {
+            return Ok(None);
+        }
+        let reply: CredentialHelperReply = serde_json::from_slice(&output.stdout)
+            .map_err(|e| format!("Invalid reply from {}: {}", program, e))?;
+        Ok(Some(reply.into()))
+    }
+
+    /// Build an image from a local context directory. The context is sent as a
+    /// tarball without the paths matched by its `.dockerignore`. Output lines are
+    /// emitted as `image-build-progress` events, with a last one that has `done` set.
+    #[instrument(skip_all, err)]
+    pub async fn build_image(
+        app_handle: AppHandle,
+        docker: &Docker,
+        options: BuildImageOptions,
+    ) -> Result<BuildResult, String> {
+        options.validate()?;
+
+        let context = PathBuf::from(&options.context);
+        if !context.is_dir() {
+            return Err(format!("{} is not a directory", options.context));
+        }
+        let dockerfile = options.dockerfile();
+        if !context.join(&dockerfile).is_file() {
             return Err(format!(
-                "Docker connection failed: {}. Please ensure Docker is running and accessible.",
-                e
+                "Dockerfile {} not found in {}",
+                dockerfile, options.context
             ));
         }
+        let build_id = options
+            .build_id
+            .clone()
+            .unwrap_or_else(|| Uuid::new_v4().to_string());
+        debug!("Building image from {} as {}", options.context, build_id);
+
+        let archive_dockerfile = dockerfile.clone();
+        let archive = tokio::task::spawn_blocking(move || {
+            write_context_archive(&context, &archive_dockerfile)
+        })
+        .await
+        .map_err(|e| format!("Failed to read build context {}: {}", options.context, e))?
+        .map_err(|e| format!("Failed to read build context {}: {}", options.context, e))?;
+
+        let build_options = BollardBuildImageOptions {
+            dockerfile,
+            t: options.tags.first().cloned().unwrap_or_default(),
+            buildargs: options.build_args.clone(),
+            target: options.target.clone().unwrap_or_default(),
+            nocache: options.no_cache,
+            pull: options.pull,
+            rm: true,
+            forcerm: true,
+            ..Default::default()
+        };
+        let mut stream = docker.build_image(build_options, None, Some(archive.into()));
+        let mut tracker = BuildOutputTracker::default();
+
+        while let Some(message) = stream.next().await {
+            let lines = match message {
+                Ok(info) => tracker.update(
+                    info.stream,
+                    info.aux.and_then(|aux| aux.id),
+                    info.error
+                        .or_else(|| info.error_detail.and_then(|detail| detail.message)),
+                ),
+                Err(e) => tracker.update(None, None, Some(e.to_string())),
+            };
+            if !lines.is_empty() {
+                Self::emit_build_progress(&app_handle, &build_id, lines, &tracker, None, false);
+            }
+            if tracker.error.is_some() {
+                break;
+            }
+        }
+        drop(stream);
+        let lines: Vec<String> = tracker.flush().into_iter().collect();
 
-        debug!("Docker connection successful, attempting to pull image");
+        if tracker.error.is_none() && tracker.image_id.is_none() {
+            tracker.error = Some("The engine ended the build without an image".to_string());
+        }
+        let result = match (tracker.failure(), &tracker.image_id) {
+            (None, Some(image_id)) => {
+                let extra_tags = options.tags.get(1..).unwrap_or_default();
+                Self::tag_built_image(docker, image_id, extra_tags)
+                    .await
+                    .map(|image_id| BuildResult {
+                        build_id: build_id.clone(),
+                        image_id,
+                        tags: options.tags.clone(),
+                        steps: tracker.step.as_ref().map_or(0, |step| step.number),
+                    })
+            }
+            (failure, _) => Err(format!(
+                "Failed to build image: {}",
+                failure.unwrap_or_default()
+            )),
+        };
+        match &result {
+            Ok(built) => debug!("Built image {}", built.image_id),
+            Err(e) => debug!("{}", e),
+        }
 
-        // Pull the image using create_image (which is the correct API for pulling)
-        let mut stream = docker.create_image(Some(options), None, None);
+        let error = result.as_ref().err().cloned();
+        Self::emit_build_progress(&app_handle, &build_id, lines, &tracker, error, true);
+        result
+    }
 
-        use futures_util::StreamExt;
-
-        let mut has_success = false;
-        let mut last_error = None;
-        let mut response_count = 0;
-
-        while let Some(create_result) = stream.next().await {
-            response_count += 1;
-            debug!("Received response {}: {:?}", response_count, create_result);
-
-            match create_result {
-                Ok(create_info) => {
-                    debug!("Pull response: {:?}", create_info);
-
-                    if let Some(status) = create_info.status {
-                        debug!("Pull status: {}", status);
-                        if status.contains("Download complete")
-                            || status.contains("Pull complete")
-                            || status.contains("Status: Downloaded newer image")
-                        {
-                            has_success = true;
-                        }
-                    }
-
-                    if let Some(id) = create_info.id {
-                        debug!("Created image with ID: {}", id);
-                        has_success = true;
-                    }
-
-                    if let Some(error) = create_info.error {
-                        debug!("Pull error: {}", error);
-                        last_error = Some(error);
-                    }
-                }
-                Err(e) => {
-                    debug!("Pull stream error: {}", e);
-                    last_error = Some(e.to_string());
-                }
-            }
+    /// Add the tags the build could not apply itself and return the full image id.
+    async fn tag_built_image(
+        docker: &Docker,
+        image_id: &str,
+        tags: &[String],
+    ) -> Result<String, String> {
+        for reference in tags {
+            Self::tag_image(docker, image_id, reference).await?;
         }
 
-        debug!(
-            "Stream completed. Response count: {}, Has success: {}",
-            response_count, has_success
-        );
+        let inspect = docker
+            .inspect_image(image_id)
+            .await
+            .map_err(|e| format!("Failed to inspect image {}: {}", image_id, e))?;
+        Ok(inspect.id.unwrap_or_else(|| image_id.to_string()))
+    }
 
-        if has_success {
-            debug!("Successfully pulled image: {}", full_image_name);
-            Ok(())
-        } else if let Some(error) = last_error {
-            Err(format!(
-                "Failed to pull image {}: {}",
-                full_image_name, error
-            ))
-        } else if response_count == 0 {
-            Err(format!("Failed to pull image {}: No response from Docker. This might indicate a network issue or the image doesn't exist.", full_image_name))
-        } else {
-            Err(format!("Failed to pull image {}: Pull operation completed but no success indicators were found ({} responses received). The image might already exist or there was an issue during the pull process.", full_image_name, response_count))
+    fn emit_build_progress(
+        app_handle: &AppHandle,
+        build_id: &str,
+        lines: Vec<String>,
+        tracker: &BuildOutputTracker,
+        error: Option<String>,
+        done: bool,
+    ) {
+        let progress = BuildProgress {
+            build_id: build_id.to_string(),
+            lines,
+            step: tracker.step.clone(),
+            done,
+            error,
+        };
+        if let Err(e) = app_handle.emit("image-build-progress", &progress) {
+            warn!("Failed to emit image build progress: {}", e);
         }
     }
 
@@ -406,3 +824,84 @@ impl ImagesService {
         Ok(all_tags)
     }
 }
+
+/// Tar the build context in memory, as bollard 0.18 only takes the context of a
+/// build as one buffer. The Dockerfile and `.dockerignore` are always sent, as
+/// the engine needs them even when they are ignored.
+fn write_context_archive(context: &Path, dockerfile: &str) -> io::Result<Vec<u8>> {
+    let ignore = match fs::read_to_string(context.join(".dockerignore")) {
+        Ok(content) => DockerIgnore::parse(&content),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => DockerIgnore::default(),
+        Err(e) => return Err(e),
+    };
+    let kept = [dockerfile, ".dockerignore"];
+
+    let mut builder = tar::Builder::new(Vec::new());
+    builder.follow_symlinks(false);
+    append_context_dir(&mut builder, context, "", &ignore, &kept)?;
+    builder.into_inner()
+}
+
+fn append_context_dir<W: Write>(
+    builder: &mut tar::Builder<W>,
+    context: &Path,
+    relative: &str,
+    ignore: &DockerIgnore,
+    kept: &[&str],
+) -> io::Result<()> {
+    let mut entries = fs::read_dir(context.join(relative))?.collect::<io::Result<Vec<_>>>()?;
+    entries.sort_by_key(|entry| entry.file_name());
+
+    for entry in entries {
+        let name = entry.file_name().to_string_lossy().to_string();
+        let path = match relative {
+            "" => name,
+            relative => format!("{}/{}", relative, name),
+        };
+        let excluded = ignore.is_excluded(&path) && !kept.contains(&path.as_str());
+
+        if entry.file_type()?.is_dir() {
+            // Exceptions may re-include paths below an excluded directory, and
+            // kept files have to be found even when their directory is excluded
+            let holds_kept = kept.iter().any(|kept| {
+                kept.strip_prefix(path.as_str())
+                    .is_some_and(|rest| rest.starts_with('/'))
+            });
+            if excluded && !ignore.has_exceptions() && !holds_kept {
+                continue;
+            }
+            if !excluded {
+                builder.append_path_with_name(entry.path(), &path)?;
+            }
+            append_context_dir(builder, context, &path, ignore, kept)?;
+        } else if !excluded {
+            builder.append_path_with_name(entry.path(), &path)?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_context_archive_keeps_dockerfile_in_excluded_dir() {
+        let context = tempfile::tempdir().unwrap();
+        fs::create_dir(context.path().join("docker")).unwrap();
+        fs::write(context.path().join("docker/Dockerfile"), "FROM alpine\n").unwrap();
+        fs::write(context.path().join("docker/notes.txt"), "").unwrap();
+        fs::write(context.path().join("app.py"), "").unwrap();
+        fs::write(context.path().join(".dockerignore"), "docker/\n").unwrap();
+
+        let archive = write_context_archive(context.path(), "docker/Dockerfile").unwrap();
+        let mut paths: Vec<String> = tar::Archive::new(archive.as_slice())
+            .entries()
+            .unwrap()
+            .map(|entry| entry.unwrap().path().unwrap().to_string_lossy().to_string())
+            .collect();
+        paths.sort();
+
+        assert_eq!(paths, vec![".dockerignore", "app.py", "docker/Dockerfile"]);
+    }
+}
@@ -0,0 +1,168 @@
+use crate::entities::{
+    LogBatch, LogLine, LogStream, LogSubscriptionClosed, LogSubscriptionOptions,
+};
+use crate::services::SubscriptionRegistry;
+use bollard::container::{LogOutput, LogsOptions};
+use bollard::Docker;
+use futures_util::StreamExt;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tracing::{debug, instrument, warn};
+use uuid::Uuid;
+
+/// How often buffered log lines are flushed to the frontend.
+const LOG_BATCH_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Flush earlier than the interval once this many lines are buffered.
+const LOG_BATCH_MAX_LINES: usize = 500;
+
+lazy_static::lazy_static! {
+    static ref LOG_SUBSCRIPTIONS: SubscriptionRegistry = SubscriptionRegistry::default();
+}
+
+#[derive(Default, Debug)]
+pub struct LogsService {}
+
+impl LogsService {
+    /// Open a follow-mode log stream for a container and push batches of lines
+    /// to the frontend as `container-logs` events until the container stops or
+    /// the subscription is cancelled. Returns the subscription id.
+    #[instrument(skip_all, err)]
+    pub async fn subscribe(
+        app_handle: AppHandle,
+        docker: Docker,
+        container_id: String,
+        options: LogSubscriptionOptions,
+    ) -> Result<String, String> {
+        // Fail early for unknown containers instead of emitting a closed event right away
+        docker
+            .inspect_container(&container_id, None)
+            .await
+            .map_err(|e| format!("Failed to inspect container: {}", e))?;
+
+        let logs_options = LogsOptions::<String> {
+            follow: true,
+            stdout: true,
+            stderr: true,
+            since: options.since.unwrap_or_default(),
+            until: options.until.unwrap_or_default(),
+            timestamps: options.timestamps,
+            tail: options
+                .tail
+                .map(|tail| tail.to_string())
+                .unwrap_or_else(|| "all".to_string()),
+        };
+
+        let subscription_id = Uuid::new_v4().to_string();
+        debug!(
+            "Opening log subscription {} for container {}",
+            subscription_id, container_id
+        );
+
+        LOG_SUBSCRIPTIONS
+            .spawn(
+                subscription_id.clone(),
+                Self::stream_logs(
+                    app_handle,
+                    docker,
+                    container_id,
+                    subscription_id.clone(),
+                    logs_options,
+                ),
+            )
+            .await?;
+
+        Ok(subscription_id)
+    }
+
+    /// Cancel a running log subscription.
+    #[instrument(skip_all, err)]
+    pub async fn unsubscribe(subscription_id: &str) -> Result<(), String> {
+        if LOG_SUBSCRIPTIONS.cancel(subscription_id).await {
+            Ok(())
+        } else {
+            Err(format!("Log subscription {} not found", subscription_id))
+        }
+    }
+
+    async fn stream_logs(
+        app_handle: AppHandle,
+        docker: Docker,
+        container_id: String,
+        subscription_id: String,
+        options: LogsOptions<String>,
+    ) {
+        let mut stream = docker.logs(&container_id, Some(options));
+        let mut interval = tokio::time::interval(LOG_BATCH_INTERVAL);
+        let mut lines: Vec<LogLine> = Vec::new();
+
+        let error = loop {
+            tokio::select! {
+                entry = stream.next() => match entry {
+                    Some(Ok(output)) => {
+                        lines.extend(Self::split_output(output));
+                        if lines.len() >= LOG_BATCH_MAX_LINES {
+                            Self::flush(&app_handle, &subscription_id, &container_id, &mut lines);
+                        }
+                    }
+                    Some(Err(e)) => break Some(format!("Error reading log stream: {}", e)),
+                    None => break None,
+                },
+                _ = interval.tick() => {
+                    Self::flush(&app_handle, &subscription_id, &container_id, &mut lines);
+                }
+            }
+        };
+
+        Self::flush(&app_handle, &subscription_id, &container_id, &mut lines);
+
+        let closed = LogSubscriptionClosed {
+            subscription_id,
+            container_id,
+            error,
+        };
+        if let Err(e) = app_handle.emit("container-logs-closed", &closed) {
+            warn!("Failed to emit log subscription closed event: {}", e);
+        }
+    }
+
+    /// Emit the buffered lines as a single batch and clear the buffer.
+    fn flush(
+        app_handle: &AppHandle,
+        subscription_id: &str,
+        container_id: &str,
+        lines: &mut Vec<LogLine>,
+    ) {
+        if lines.is_empty() {
+            return;
+        }
+
+        let batch = LogBatch {
+            subscription_id: subscription_id.to_string(),
+            container_id: container_id.to_string(),
+            lines: std::mem::take(lines),
+        };
+        if let Err(e) = app_handle.emit("container-logs", &batch) {
+            warn!("Failed to emit container logs batch: {}", e);
+        }
+    }
+
+    /// A single frame from the engine may hold several lines; split them
+    /// and keep the stream label on each one.
+    fn split_output(output: LogOutput) -> Vec<LogLine> {
+        let (stream, message) = match output {
+            LogOutput::StdOut { message } => (LogStream::Stdout, message),
+            LogOutput::StdErr { message } => (LogStream::Stderr, message),
+            LogOutput::Console { message } => (LogStream::Console, message),
+            LogOutput::StdIn { .. } => return Vec::new(),
+        };
+
+        String::from_utf8_lossy(&message)
+            .split_terminator('\n')
+            .map(|line| LogLine {
+                stream,
+                message: line.trim_end_matches('\r').to_string(),
+            })
+            .collect()
+    }
+}
@@ -1,16 +1,37 @@
+mod archive;
+mod bulk;
+mod compose;
 mod config;
 mod containers;
+mod copy;
 pub(crate) mod engine;
 pub mod engine_state_monitor;
+mod exec;
+mod files;
 mod images;
+mod logs;
 mod networks;
+mod processes;
+mod progress;
 pub(crate) mod shell;
+mod stats;
+mod subscriptions;
 mod updater;
 mod volumes;
 
+pub use archive::*;
+pub use bulk::*;
+pub use compose::*;
 pub use config::*;
 pub use containers::*;
+pub use copy::*;
+pub use exec::*;
+pub use files::*;
 pub use images::*;
+pub use logs::*;
 pub use networks::*;
+pub use processes::*;
+pub use stats::*;
+pub use subscriptions::*;
 pub use updater::*;
 pub use volumes::*;
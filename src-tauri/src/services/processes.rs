@@ -0,0 +1,535 @@
+use crate::entities::{
+    ContainerProcess, ProcessList, ProcessListOptions, ProcessListUpdate, ProcessSource,
+    ProcessSubscriptionEnded, ProcessSubscriptionOptions, DEFAULT_PS_ARGS,
+};
+use crate::services::SubscriptionRegistry;
+use bollard::container::{LogOutput, TopOptions};
+use bollard::exec::{CreateExecOptions, StartExecOptions, StartExecResults};
+use bollard::Docker;
+use chrono::{DateTime, Local, TimeDelta};
+use futures_util::StreamExt;
+use std::collections::HashMap;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, instrument, warn};
+use uuid::Uuid;
+
+/// How often the process list is refreshed when the subscriber does not ask for anything else.
+const DEFAULT_PROCESS_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Every refresh runs `ps` on the engine host, so it should not happen too often.
+const MIN_PROCESS_INTERVAL: Duration = Duration::from_millis(500);
+
+/// Clock ticks per second used in `/proc/<pid>/stat`. 100 on every common Linux build.
+const CLOCK_TICKS: f64 = 100.0;
+
+/// Columns of a process list read from `/proc`, matching `DEFAULT_PS_ARGS`.
+const PROC_TITLES: [&str; 8] = [
+    "PID", "PPID", "USER", "%CPU", "%MEM", "RSS", "STIME", "COMMAND",
+];
+
+/// Dumps what is needed to build a process list from `/proc` with nothing
+/// more than `sh` and `cat`. The processes of the script itself are skipped.
+const PROC_SCRIPT: &str = r##"echo "#self $$"
+echo '#uptime'; cat /proc/uptime
+echo '#meminfo'; cat /proc/meminfo
+echo '#passwd'; cat /etc/passwd 2>/dev/null
+for dir in /proc/[0-9]*; do
+  [ -r "$dir/stat" ] || continue
+  echo "#pid ${dir#/proc/}"
+  cat "$dir/stat" "$dir/status" 2>/dev/null
+  echo '#cmdline'
+  cat "$dir/cmdline" 2>/dev/null
+  echo
+done"##;
+
+lazy_static::lazy_static! {
+    static ref PROCESS_SUBSCRIPTIONS: SubscriptionRegistry = SubscriptionRegistry::default();
+}
+
+#[derive(Default, Debug)]
+pub struct ProcessesService {}
+
+impl ProcessesService {
+    /// List the processes of a running container with `ps`. When the engine
+    /// cannot run `ps` with the default arguments, the list is read from
+    /// `/proc` inside the container instead.
+    #[instrument(skip_all, err)]
+    pub async fn list_processes(
+        docker: &Docker,
+        container_id: &str,
+        options: &ProcessListOptions,
+    ) -> Result<ProcessList, String> {
+        let ps_args = options
+            .ps_args
+            .as_deref()
+            .map(str::trim)
+            .filter(|args| !args.is_empty());
+
+        let top = docker
+            .top_processes(
+                container_id,
+                Some(TopOptions {
+                    ps_args: ps_args.unwrap_or(DEFAULT_PS_ARGS),
+                }),
+            )
+            .await;
+
+        match top {
+            Ok(top) => {
+                let titles = top.titles.unwrap_or_default();
+                let processes = top
+                    .processes
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|values| ContainerProcess::from_row(&titles, values))
+                    .collect();
+
+                Ok(ProcessList {
+                    container_id: container_id.to_string(),
+                    source: ProcessSource::Ps,
+                    titles,
+                    processes,
+                })
+            }
+            // Custom arguments only make sense for `ps`, so their errors are reported as is
+            Err(e) if ps_args.is_some() => Err(format!("Failed to list processes: {}", e)),
+            Err(e) => {
+                debug!(
+                    "ps failed for container {}, reading /proc: {}",
+                    container_id, e
+                );
+                Self::list_processes_from_proc(docker, container_id)
+                    .await
+                    .map_err(|proc_error| {
+                        format!(
+                            "Failed to list processes: {}; reading /proc failed too: {}",
+                            e, proc_error
+                        )
+                    })
+            }
+        }
+    }
+
+    /// Start refreshing the process list of a container. Lists are emitted as
+    /// `container-processes` events and `container-processes-ended` is sent when
+    /// the container stops or the list cannot be read. Returns the subscription id.
+    #[instrument(skip_all, err)]
+    pub async fn subscribe(
+        app_handle: AppHandle,
+        docker: Docker,
+        options: ProcessSubscriptionOptions,
+    ) -> Result<String, String> {
+        let interval = options
+            .interval_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_PROCESS_INTERVAL)
+            .max(MIN_PROCESS_INTERVAL);
+        let list_options = ProcessListOptions {
+            ps_args: options.ps_args,
+        };
+
+        // The first list is read right away so bad arguments or a stopped container fail here
+        let first = Self::list_processes(&docker, &options.container_id, &list_options).await?;
+
+        let subscription_id = Uuid::new_v4().to_string();
+        debug!(
+            "Opening process subscription {} for container {}",
+            subscription_id, options.container_id
+        );
+
+        PROCESS_SUBSCRIPTIONS
+            .spawn(
+                subscription_id.clone(),
+                Self::refresh_processes(
+                    app_handle,
+                    docker,
+                    subscription_id.clone(),
+                    first,
+                    list_options,
+                    interval,
+                ),
+            )
+            .await?;
+
+        Ok(subscription_id)
+    }
+
+    /// Cancel a running process list subscription.
+    #[instrument(skip_all, err)]
+    pub async fn unsubscribe(subscription_id: &str) -> Result<(), String> {
+        if PROCESS_SUBSCRIPTIONS.cancel(subscription_id).await {
+            Ok(())
+        } else {
+            Err(format!(
+                "Process subscription {} not found",
+                subscription_id
+            ))
+        }
+    }
+
+    async fn refresh_processes(
+        app_handle: AppHandle,
+        docker: Docker,
+        subscription_id: String,
+        first: ProcessList,
+        options: ProcessListOptions,
+        interval: Duration,
+    ) {
+        let container_id = first.container_id.clone();
+        Self::emit_list(&app_handle, &subscription_id, first);
+
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick completes immediately and the first list was already sent
+        ticker.tick().await;
+
+        let error = loop {
+            ticker.tick().await;
+
+            match Self::list_processes(&docker, &container_id, &options).await {
+                Ok(list) => Self::emit_list(&app_handle, &subscription_id, list),
+                Err(e) => {
+                    // A stopped container is the normal end of a subscription
+                    let running = docker
+                        .inspect_container(&container_id, None)
+                        .await
+                        .ok()
+                        .and_then(|container| container.state)
+                        .and_then(|state| state.running)
+                        .unwrap_or(false);
+                    break if running { Some(e) } else { None };
+                }
+            }
+        };
+
+        let ended = ProcessSubscriptionEnded {
+            subscription_id: subscription_id.clone(),
+            container_id,
+            error,
+        };
+        if let Err(e) = app_handle.emit("container-processes-ended", &ended) {
+            warn!("Failed to emit process subscription ended event: {}", e);
+        }
+        debug!("Process subscription {} finished", subscription_id);
+    }
+
+    fn emit_list(app_handle: &AppHandle, subscription_id: &str, list: ProcessList) {
+        let update = ProcessListUpdate {
+            subscription_id: subscription_id.to_string(),
+            list,
+        };
+        if let Err(e) = app_handle.emit("container-processes", &update) {
+            warn!("Failed to emit container processes: {}", e);
+        }
+    }
+
+    async fn list_processes_from_proc(
+        docker: &Docker,
+        container_id: &str,
+    ) -> Result<ProcessList, String> {
+        let output = Self::run_command(docker, container_id, &["sh", "-c", PROC_SCRIPT]).await?;
+        let titles: Vec<String> = PROC_TITLES.iter().map(|title| title.to_string()).collect();
+        let processes = parse_proc_dump(&output, Local::now())
+            .into_iter()
+            .map(|values| ContainerProcess::from_row(&titles, values))
+            .collect();
+
+        Ok(ProcessList {
+            container_id: container_id.to_string(),
+            source: ProcessSource::Proc,
+            titles,
+            processes,
+        })
+    }
+
+    /// Run a command inside the container and return its standard output.
+    pub(crate) async fn run_command(
+        docker: &Docker,
+        container_id: &str,
+        command: &[&str],
+    ) -> Result<String, String> {
+        let exec = docker
+            .create_exec(
+                container_id,
+                CreateExecOptions {
+                    attach_stdout: Some(true),
+                    attach_stderr: Some(true),
+                    cmd: Some(command.to_vec()),
+                    ..Default::default()
+                },
+            )
+            .await
+            .map_err(|e| format!("Failed to create exec instance: {}", e))?;
+
+        let start_options = StartExecOptions {
+            detach: false,
+            tty: false,
+            output_capacity: None,
+        };
+        let StartExecResults::Attached { mut output, .. } = docker
+            .start_exec(&exec.id, Some(start_options))
+            .await
+            .map_err(|e| format!("Failed to start exec instance: {}", e))?
+        else {
+            return Err("Exec instance started detached, cannot read its output".to_string());
+        };
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        while let Some(chunk) = output.next().await {
+            match chunk.map_err(|e| format!("Failed to read command output: {}", e))? {
+                LogOutput::StdOut { message } => stdout.extend_from_slice(&message),
+                LogOutput::StdErr { message } => stderr.extend_from_slice(&message),
+                _ => {}
+            }
+        }
+
+        let exit_code = docker
+            .inspect_exec(&exec.id)
+            .await
+            .map_err(|e| format!("Failed to inspect exec instance: {}", e))?
+            .exit_code;
+        match exit_code {
+            Some(0) => Ok(String::from_utf8_lossy(&stdout).to_string()),
+            code => Err(format!(
+                "Command exited with code {}: {}",
+                code.map(|code| code.to_string())
+                    .unwrap_or_else(|| "unknown".to_string()),
+                String::from_utf8_lossy(&stderr).trim()
+            )),
+        }
+    }
+}
+
+/// A process as found in `/proc`, before it is turned into a row.
+#[derive(Debug, Default)]
+struct ProcEntry {
+    pid: u32,
+    ppid: u32,
+    uid: Option<u32>,
+    /// CPU time in clock ticks.
+    cpu_ticks: u64,
+    /// Start time in clock ticks after boot.
+    start_ticks: u64,
+    rss_kib: Option<u64>,
+    name: String,
+    cmdline: String,
+}
+
+/// Turn the output of `PROC_SCRIPT` into rows with the `PROC_TITLES` columns.
+fn parse_proc_dump(output: &str, now: DateTime<Local>) -> Vec<Vec<String>> {
+    let mut own_pid = None;
+    let mut uptime = 0.0;
+    let mut memory_total_kib = 0;
+    let mut users = HashMap::new();
+    let mut entries: Vec<ProcEntry> = Vec::new();
+
+    let mut section = "";
+    for line in output.lines() {
+        if let Some(pid) = line.strip_prefix("#self ") {
+            own_pid = pid.trim().parse::<u32>().ok();
+            continue;
+        }
+        if let Some(pid) = line.strip_prefix("#pid ") {
+            section = "pid";
+            entries.push(ProcEntry {
+                pid: pid.trim().parse().unwrap_or_default(),
+                ..Default::default()
+            });
+            continue;
+        }
+        if line.starts_with('#') && !line.contains(' ') {
+            section = &line[1..];
+            continue;
+        }
+
+        match section {
+            "uptime" => {
+                uptime = line
+                    .split_whitespace()
+                    .next()
+                    .and_then(|value| value.parse().ok())
+                    .unwrap_or(0.0);
+            }
+            "meminfo" => {
+                if let Some(value) = line.strip_prefix("MemTotal:") {
+                    memory_total_kib = leading_number(value).unwrap_or(0);
+                }
+            }
+            "passwd" => {
+                let fields: Vec<&str> = line.split(':').collect();
+                if let (Some(name), Some(uid)) = (fields.first(), fields.get(2)) {
+                    if let Ok(uid) = uid.parse::<u32>() {
+                        users.entry(uid).or_insert_with(|| name.to_string());
+                    }
+                }
+            }
+            "pid" => {
+                if let Some(entry) = entries.last_mut() {
+                    parse_proc_line(entry, line);
+                }
+            }
+            "cmdline" => {
+                if let Some(entry) = entries.last_mut() {
+                    entry.cmdline = line.replace('\0', " ").trim().to_string();
+                }
+                section = "";
+            }
+            _ => {}
+        }
+    }
+
+    let boot_time = now - TimeDelta::milliseconds((uptime * 1000.0) as i64);
+    entries
+        .into_iter()
+        .filter(|entry| entry.pid != 0 && Some(entry.pid) != own_pid && Some(entry.ppid) != own_pid)
+        .map(|entry| {
+            let started_after_boot = entry.start_ticks as f64 / CLOCK_TICKS;
+            let running_for = uptime - started_after_boot;
+            let cpu_percent = if running_for > 0.0 {
+                entry.cpu_ticks as f64 / CLOCK_TICKS / running_for * 100.0
+            } else {
+                0.0
+            };
+            let rss_kib = entry.rss_kib.unwrap_or(0);
+            let memory_percent = if memory_total_kib > 0 {
+                rss_kib as f64 / memory_total_kib as f64 * 100.0
+            } else {
+                0.0
+            };
+            let start = boot_time + TimeDelta::milliseconds((started_after_boot * 1000.0) as i64);
+            // Same format as the STIME column of ps
+            let start_time = if now - start < TimeDelta::days(1) {
+                start.format("%H:%M").to_string()
+            } else {
+                start.format("%b%d").to_string()
+            };
+            let user = entry
+                .uid
+                .map(|uid| users.get(&uid).cloned().unwrap_or_else(|| uid.to_string()))
+                .unwrap_or_default();
+            // Kernel threads and zombies have no command line
+            let command = if entry.cmdline.is_empty() {
+                format!("[{}]", entry.name)
+            } else {
+                entry.cmdline
+            };
+
+            vec![
+                entry.pid.to_string(),
+                entry.ppid.to_string(),
+                user,
+                format!("{:.1}", cpu_percent),
+                format!("{:.1}", memory_percent),
+                rss_kib.to_string(),
+                start_time,
+                command,
+            ]
+        })
+        .collect()
+}
+
+/// Read a line of `/proc/<pid>/stat` or `/proc/<pid>/status` into `entry`.
+fn parse_proc_line(entry: &mut ProcEntry, line: &str) {
+    if let Some(value) = line.strip_prefix("Uid:") {
+        // Real, effective, saved and filesystem uid. ps shows the effective one
+        entry.uid = value
+            .split_whitespace()
+            .nth(1)
+            .and_then(|uid| uid.parse().ok());
+    } else if let Some(value) = line.strip_prefix("VmRSS:") {
+        entry.rss_kib = leading_number(value);
+    } else if let (Some(open), Some(close)) = (line.find('('), line.rfind(')')) {
+        // The process name is in parentheses and may contain spaces and parentheses itself
+        if open > close || !line[..open].trim().chars().all(|c| c.is_ascii_digit()) {
+            return;
+        }
+        entry.name = line[open + 1..close].to_string();
+
+        // Fields after the name, starting with the state (field 3 of proc(5))
+        let fields: Vec<&str> = line[close + 1..].split_whitespace().collect();
+        let field = |number: usize| {
+            fields
+                .get(number - 3)
+                .and_then(|value| value.parse::<u64>().ok())
+                .unwrap_or(0)
+        };
+        entry.ppid = field(4) as u32;
+        entry.cpu_ticks = field(14) + field(15);
+        entry.start_ticks = field(22);
+    }
+}
+
+fn leading_number(value: &str) -> Option<u64> {
+    value.split_whitespace().next()?.parse().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_parse_proc_dump() {
+        let output = "#self 57\n\
+            #uptime\n\
+            1000.00 3900.00\n\
+            #meminfo\n\
+            MemTotal:        1000000 kB\n\
+            MemFree:          500000 kB\n\
+            #passwd\n\
+            root:x:0:0:root:/root:/bin/sh\n\
+            app:x:1000:1000::/home/app:/bin/sh\n\
+            #pid 1\n\
+            1 (my (app)) S 0 1 1 0 -1 4194560 100 0 0 0 4000 1000 0 0 20 0 1 0 50000 1000 250 1 0\n\
+            Name:\tmy (app)\n\
+            Uid:\t1000\t1000\t1000\t1000\n\
+            VmRSS:\t    10000 kB\n\
+            #cmdline\n\
+            /usr/bin/app\0--port\08080\0\n\
+            #pid 57\n\
+            57 (sh) S 0 57 1 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 99990 100 10 1 0\n\
+            Uid:\t0\t0\t0\t0\n\
+            #cmdline\n\
+            sh\0-c\0\n\
+            #pid 58\n\
+            58 (cat) R 57 57 1 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 99995 100 10 1 0\n\
+            #cmdline\n\
+            cat\0\n";
+        let now = Local.with_ymd_and_hms(2026, 10, 17, 12, 0, 0).unwrap();
+
+        let rows = parse_proc_dump(output, now);
+
+        assert_eq!(rows.len(), 1);
+        let row = &rows[0];
+        assert_eq!(row[0], "1");
+        assert_eq!(row[1], "0");
+        assert_eq!(row[2], "app");
+        // 50s of CPU time over the 500s the process has been running
+        assert_eq!(row[3], "10.0");
+        assert_eq!(row[4], "1.0");
+        assert_eq!(row[5], "10000");
+        // Started 500s before now
+        assert_eq!(row[6], "11:51");
+        assert_eq!(row[7], "/usr/bin/app --port 8080");
+    }
+
+    #[test]
+    fn test_kernel_thread_without_cmdline() {
+        let output = "#self 10\n\
+            #uptime\n\
+            100.0 100.0\n\
+            #pid 2\n\
+            2 (kthreadd) S 0 0 0 0 -1 0 0 0 0 0 0 0 0 0 20 0 1 0 1 0 0\n\
+            #cmdline\n\
+            \n";
+        let now = Local.with_ymd_and_hms(2026, 10, 17, 12, 0, 0).unwrap();
+
+        let rows = parse_proc_dump(output, now);
+
+        assert_eq!(rows[0][2], "");
+        assert_eq!(rows[0][7], "[kthreadd]");
+    }
+}
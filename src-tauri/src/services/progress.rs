@@ -0,0 +1,114 @@
+use crate::entities::{ArchiveProgress, CopyProgress};
+use serde::Serialize;
+use std::io;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tauri::{AppHandle, Emitter};
+use tracing::warn;
+
+/// Minimum time between two progress events of a transfer.
+const PROGRESS_INTERVAL: Duration = Duration::from_millis(200);
+
+/// Event payload of a transfer that counts moved bytes.
+pub(crate) trait TransferProgress: Serialize + Send + 'static {
+    fn advance(&mut self, bytes: u64);
+
+    /// Mark the transfer as done and return the number of bytes moved.
+    fn finish(&mut self) -> u64;
+}
+
+impl TransferProgress for CopyProgress {
+    fn advance(&mut self, bytes: u64) {
+        self.bytes_transferred += bytes;
+    }
+
+    fn finish(&mut self) -> u64 {
+        self.done = true;
+        self.bytes_transferred
+    }
+}
+
+impl TransferProgress for ArchiveProgress {
+    fn advance(&mut self, bytes: u64) {
+        self.bytes_transferred += bytes;
+    }
+
+    fn finish(&mut self) -> u64 {
+        self.done = true;
+        self.bytes_transferred
+    }
+}
+
+/// Throttled emitter of progress events, shared with the stream or
+/// reader that moves the data.
+pub(crate) struct ProgressReporter<P> {
+    app_handle: AppHandle,
+    event: &'static str,
+    state: Arc<Mutex<(P, Instant)>>,
+}
+
+impl<P> Clone for ProgressReporter<P> {
+    fn clone(&self) -> Self {
+        Self {
+            app_handle: self.app_handle.clone(),
+            event: self.event,
+            state: self.state.clone(),
+        }
+    }
+}
+
+impl<P: TransferProgress> ProgressReporter<P> {
+    pub(crate) fn new(app_handle: AppHandle, event: &'static str, progress: P) -> Self {
+        Self {
+            app_handle,
+            event,
+            state: Arc::new(Mutex::new((progress, Instant::now()))),
+        }
+    }
+
+    pub(crate) fn advance(&self, bytes: u64) {
+        let mut state = self.state.lock().unwrap();
+        let (progress, last_emit) = &mut *state;
+        progress.advance(bytes);
+
+        if last_emit.elapsed() >= PROGRESS_INTERVAL {
+            *last_emit = Instant::now();
+            self.emit(progress);
+        }
+    }
+
+    /// Emit the final event and return the number of bytes moved.
+    pub(crate) fn finish(&self) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        let (progress, _) = &mut *state;
+        let bytes = progress.finish();
+        self.emit(progress);
+        bytes
+    }
+
+    fn emit(&self, progress: &P) {
+        if let Err(e) = self.app_handle.emit(self.event, progress) {
+            warn!("Failed to emit {}: {}", self.event, e);
+        }
+    }
+}
+
+/// Reader that reports every read to a `ProgressReporter`.
+pub(crate) struct ProgressReader<R, P> {
+    inner: R,
+    reporter: ProgressReporter<P>,
+}
+
+impl<R: io::Read, P: TransferProgress> ProgressReader<R, P> {
+    pub(crate) fn new(inner: R, reporter: ProgressReporter<P>) -> Self {
+        Self { inner, reporter }
+    }
+}
+
+impl<R: io::Read, P: TransferProgress> io::Read for ProgressReader<R, P> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        self.reporter.advance(read as u64);
+        Ok(read)
+    }
+}
@@ -0,0 +1,525 @@
+use crate::entities::{ContainerStats, StatsBatch, StatsStreamEnded, StatsSubscriptionOptions};
+use crate::services::SubscriptionRegistry;
+use bollard::container::{
+    BlkioStats, CPUStats, ListContainersOptions, MemoryStats, MemoryStatsStats, Stats, StatsOptions,
+};
+use bollard::models::EventMessage;
+use bollard::system::EventsOptions;
+use bollard::Docker;
+use chrono::{DateTime, FixedOffset};
+use futures_util::stream::{self, SelectAll};
+use futures_util::{Stream, StreamExt};
+use std::collections::HashMap;
+use std::pin::Pin;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, instrument, warn};
+use uuid::Uuid;
+
+/// How often stats are pushed when the subscriber does not ask for anything else.
+const DEFAULT_STATS_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The engine samples about once per second, pushing much faster only repeats values.
+const MIN_STATS_INTERVAL: Duration = Duration::from_millis(500);
+
+lazy_static::lazy_static! {
+    static ref STATS_SUBSCRIPTIONS: SubscriptionRegistry = SubscriptionRegistry::default();
+}
+
+/// Samples of one container stream, tagged with the container id and the
+/// generation of the stream. `None` marks the end of the stream.
+type SampleStream = Pin<
+    Box<dyn Stream<Item = (String, u64, Option<Result<Stats, bollard::errors::Error>>)> + Send>,
+>;
+
+type EventStream = Pin<Box<dyn Stream<Item = Result<EventMessage, bollard::errors::Error>> + Send>>;
+
+#[derive(Default, Debug)]
+pub struct StatsService {}
+
+impl StatsService {
+    /// Start streaming stats of the requested containers, or of all running
+    /// containers when none are given. Batches are emitted as `container-stats`
+    /// events and `container-stats-ended` is sent for every container that stops.
+    /// Returns the subscription id.
+    #[instrument(skip_all, err)]
+    pub async fn subscribe(
+        app_handle: AppHandle,
+        docker: Docker,
+        options: StatsSubscriptionOptions,
+    ) -> Result<String, String> {
+        let interval = options
+            .interval_ms
+            .map(Duration::from_millis)
+            .unwrap_or(DEFAULT_STATS_INTERVAL)
+            .max(MIN_STATS_INTERVAL);
+        let watch_all = options.container_ids.is_empty();
+
+        let container_ids = if watch_all {
+            Self::running_container_ids(&docker).await?
+        } else {
+            let mut ids = Vec::new();
+            for id in &options.container_ids {
+                let container = docker
+                    .inspect_container(id, None)
+                    .await
+                    .map_err(|e| format!("Failed to inspect container {}: {}", id, e))?;
+                let running = container
+                    .state
+                    .and_then(|state| state.running)
+                    .unwrap_or(false);
+                if !running {
+                    return Err(format!("Container {} is not running", id));
+                }
+                ids.push(container.id.unwrap_or_else(|| id.clone()));
+            }
+            ids
+        };
+
+        let subscription_id = Uuid::new_v4().to_string();
+        debug!(
+            "Opening stats subscription {} for {} containers",
+            subscription_id,
+            container_ids.len()
+        );
+
+        STATS_SUBSCRIPTIONS
+            .spawn(
+                subscription_id.clone(),
+                Self::stream_stats(
+                    app_handle,
+                    docker,
+                    subscription_id.clone(),
+                    container_ids,
+                    watch_all,
+                    interval,
+                ),
+            )
+            .await?;
+
+        Ok(subscription_id)
+    }
+
+    /// Cancel a running stats subscription.
+    #[instrument(skip_all, err)]
+    pub async fn unsubscribe(subscription_id: &str) -> Result<(), String> {
+        if STATS_SUBSCRIPTIONS.cancel(subscription_id).await {
+            Ok(())
+        } else {
+            Err(format!("Stats subscription {} not found", subscription_id))
+        }
+    }
+
+    async fn running_container_ids(docker: &Docker) -> Result<Vec<String>, String> {
+        let containers = docker
+            .list_containers(Some(ListContainersOptions::<String> {
+                all: false,
+                ..Default::default()
+            }))
+            .await
+            .map_err(|e| format!("Failed to list containers: {}", e))?;
+
+        Ok(containers.into_iter().filter_map(|c| c.id).collect())
+    }
+
+    async fn stream_stats(
+        app_handle: AppHandle,
+        docker: Docker,
+        subscription_id: String,
+        container_ids: Vec<String>,
+        watch_all: bool,
+        interval: Duration,
+    ) {
+        let mut streams: SelectAll<SampleStream> = SelectAll::new();
+        let mut trackers: HashMap<String, StatsTracker> = HashMap::new();
+        let mut next_generation = 0;
+
+        for id in container_ids {
+            streams.push(Self::sample_stream(&docker, id.clone(), next_generation));
+            trackers.insert(id, StatsTracker::new(next_generation));
+            next_generation += 1;
+        }
+
+        // Container events tell us about stopped containers right away and,
+        // when watching everything, about the ones that start later
+        let mut filters = HashMap::new();
+        filters.insert("type".to_string(), vec!["container".to_string()]);
+        filters.insert(
+            "event".to_string(),
+            vec!["start".to_string(), "die".to_string()],
+        );
+        let mut events: EventStream = Box::pin(docker.events(Some(EventsOptions::<String> {
+            since: None,
+            until: None,
+            filters,
+        })));
+
+        let mut ticker = tokio::time::interval(interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+        let mut pending: HashMap<String, ContainerStats> = HashMap::new();
+
+        loop {
+            tokio::select! {
+                Some((id, generation, sample)) = streams.next(), if !streams.is_empty() => {
+                    let Some(tracker) = trackers.get_mut(&id).filter(|t| t.generation == generation) else {
+                        // Leftover of a stream that was already reported as ended
+                        continue;
+                    };
+
+                    match sample {
+                        Some(Ok(stats)) => {
+                            if let Some(stats) = tracker.update(&id, &stats) {
+                                pending.insert(id, stats);
+                            }
+                        }
+                        Some(Err(e)) => {
+                            let error = format!("Error reading stats: {}", e);
+                            Self::end_stream(&app_handle, &subscription_id, &id, Some(error));
+                            trackers.remove(&id);
+                            pending.remove(&id);
+                        }
+                        None => {
+                            Self::end_stream(&app_handle, &subscription_id, &id, None);
+                            trackers.remove(&id);
+                            pending.remove(&id);
+                        }
+                    }
+                }
+                event = events.next() => match event {
+                    Some(Ok(event)) => {
+                        let Some(id) = event.actor.and_then(|actor| actor.id) else {
+                            continue;
+                        };
+
+                        match event.action.as_deref() {
+                            Some("start") if watch_all && !trackers.contains_key(&id) => {
+                                debug!("Watching stats of started container {}", id);
+                                streams.push(Self::sample_stream(&docker, id.clone(), next_generation));
+                                trackers.insert(id, StatsTracker::new(next_generation));
+                                next_generation += 1;
+                            }
+                            Some("die") if trackers.remove(&id).is_some() => {
+                                pending.remove(&id);
+                                Self::end_stream(&app_handle, &subscription_id, &id, None);
+                            }
+                            _ => {}
+                        }
+                    }
+                    Some(Err(e)) => warn!("Error reading container events: {}", e),
+                    None => {
+                        // Streams still end on their own when containers stop
+                        warn!("Container events stream ended, new containers will not be picked up");
+                        events = Box::pin(stream::pending());
+                    }
+                },
+                _ = ticker.tick() => {
+                    Self::flush(&app_handle, &subscription_id, &mut pending);
+                }
+            }
+
+            if !watch_all && trackers.is_empty() {
+                break;
+            }
+        }
+
+        Self::flush(&app_handle, &subscription_id, &mut pending);
+        debug!("Stats subscription {} finished", subscription_id);
+    }
+
+    fn sample_stream(docker: &Docker, id: String, generation: u64) -> SampleStream {
+        let samples = docker.stats(
+            &id,
+            Some(StatsOptions {
+                stream: true,
+                one_shot: false,
+            }),
+        );
+
+        let tag = id.clone();
+        Box::pin(
+            samples
+                .map(move |sample| (tag.clone(), generation, Some(sample)))
+                .chain(stream::once(async move { (id, generation, None) })),
+        )
+    }
+
+    fn flush(
+        app_handle: &AppHandle,
+        subscription_id: &str,
+        pending: &mut HashMap<String, ContainerStats>,
+    ) {
+        if pending.is_empty() {
+            return;
+        }
+
+        let batch = StatsBatch {
+            subscription_id: subscription_id.to_string(),
+            stats: pending.drain().map(|(_, stats)| stats).collect(),
+        };
+        if let Err(e) = app_handle.emit("container-stats", &batch) {
+            warn!("Failed to emit container stats: {}", e);
+        }
+    }
+
+    fn end_stream(
+        app_handle: &AppHandle,
+        subscription_id: &str,
+        container_id: &str,
+        error: Option<String>,
+    ) {
+        debug!("Stats stream of container {} ended", container_id);
+
+        let ended = StatsStreamEnded {
+            subscription_id: subscription_id.to_string(),
+            container_id: container_id.to_string(),
+            error,
+        };
+        if let Err(e) = app_handle.emit("container-stats-ended", &ended) {
+            warn!("Failed to emit stats stream ended event: {}", e);
+        }
+    }
+}
+
+/// Cumulative counters of the previous sample, used to turn them into rates.
+#[derive(Debug, Clone, Copy)]
+struct PreviousSample {
+    read: DateTime<FixedOffset>,
+    network: (u64, u64),
+    block_io: (u64, u64),
+}
+
+#[derive(Debug)]
+struct StatsTracker {
+    generation: u64,
+    previous: Option<PreviousSample>,
+}
+
+impl StatsTracker {
+    fn new(generation: u64) -> Self {
+        Self {
+            generation,
+            previous: None,
+        }
+    }
+
+    /// Compute the stats of a sample. Returns `None` for the empty samples
+    /// the engine sends for containers that are not running.
+    fn update(&mut self, container_id: &str, stats: &Stats) -> Option<ContainerStats> {
+        let read = DateTime::parse_from_rfc3339(&stats.read).ok()?;
+        if read.timestamp() <= 0 {
+            return None;
+        }
+
+        let network = stats
+            .networks
+            .iter()
+            .flat_map(|networks| networks.values())
+            .fold((0, 0), |(rx, tx), net| {
+                (rx + net.rx_bytes, tx + net.tx_bytes)
+            });
+        let block_io = block_io_bytes(&stats.blkio_stats);
+
+        let (network_rate, block_io_rate) = match self.previous {
+            Some(previous) => {
+                let seconds = (read - previous.read).num_milliseconds() as f64 / 1000.0;
+                (
+                    rates(previous.network, network, seconds),
+                    rates(previous.block_io, block_io, seconds),
+                )
+            }
+            None => ((0.0, 0.0), (0.0, 0.0)),
+        };
+        self.previous = Some(PreviousSample {
+            read,
+            network,
+            block_io,
+        });
+
+        let memory_usage = memory_usage(&stats.memory_stats);
+        let memory_limit = stats.memory_stats.limit.unwrap_or(0);
+        let memory_percent = if memory_limit > 0 {
+            memory_usage as f64 / memory_limit as f64 * 100.0
+        } else {
+            0.0
+        };
+
+        Some(ContainerStats {
+            container_id: container_id.to_string(),
+            name: stats.name.trim_start_matches('/').to_string(),
+            read_at: stats.read.clone(),
+            cpu_percent: cpu_percent(&stats.cpu_stats, &stats.precpu_stats),
+            online_cpus: online_cpus(&stats.cpu_stats),
+            memory_usage,
+            memory_limit,
+            memory_percent,
+            network_rx_bytes: network.0,
+            network_tx_bytes: network.1,
+            network_rx_rate: network_rate.0,
+            network_tx_rate: network_rate.1,
+            block_read_bytes: block_io.0,
+            block_write_bytes: block_io.1,
+            block_read_rate: block_io_rate.0,
+            block_write_rate: block_io_rate.1,
+            pids: stats.pids_stats.current,
+        })
+    }
+}
+
+fn online_cpus(cpu: &CPUStats) -> u64 {
+    cpu.online_cpus
+        .filter(|cpus| *cpus > 0)
+        .or_else(|| {
+            cpu.cpu_usage
+                .percpu_usage
+                .as_ref()
+                .map(|usage| usage.len() as u64)
+        })
+        .unwrap_or(1)
+}
+
+/// Same formula as `docker stats`: the container's share of the host CPU time
+/// since the previous sample, scaled by the number of CPUs.
+fn cpu_percent(cpu: &CPUStats, precpu: &CPUStats) -> f64 {
+    // The first sample of a stream has no previous reading to compare with
+    let Some(previous_system) = precpu.system_cpu_usage.filter(|usage| *usage > 0) else {
+        return 0.0;
+    };
+
+    let cpu_delta = cpu
+        .cpu_usage
+        .total_usage
+        .saturating_sub(precpu.cpu_usage.total_usage);
+    let system_delta = cpu
+        .system_cpu_usage
+        .unwrap_or(0)
+        .saturating_sub(previous_system);
+    if cpu_delta == 0 || system_delta == 0 {
+        return 0.0;
+    }
+
+    cpu_delta as f64 / system_delta as f64 * online_cpus(cpu) as f64 * 100.0
+}
+
+/// Memory usage without the inactive page cache, which the kernel can reclaim
+/// at any time. The counter is named differently on cgroup v1 and v2.
+fn memory_usage(memory: &MemoryStats) -> u64 {
+    let usage = memory.usage.unwrap_or(0);
+    let inactive_file = match memory.stats {
+        Some(MemoryStatsStats::V1(stats)) => stats.total_inactive_file,
+        Some(MemoryStatsStats::V2(stats)) => stats.inactive_file,
+        None => 0,
+    };
+
+    if inactive_file < usage {
+        usage - inactive_file
+    } else {
+        usage
+    }
+}
+
+/// Total bytes read and written. cgroup v1 reports `Read`/`Write`, v2 `read`/`write`.
+fn block_io_bytes(blkio: &BlkioStats) -> (u64, u64) {
+    blkio
+        .io_service_bytes_recursive
+        .iter()
+        .flatten()
+        .fold((0, 0), |(read, write), entry| {
+            if entry.op.eq_ignore_ascii_case("read") {
+                (read + entry.value, write)
+            } else if entry.op.eq_ignore_ascii_case("write") {
+                (read, write + entry.value)
+            } else {
+                (read, write)
+            }
+        })
+}
+
+/// Per second rates of two counters. Counters going backwards (e.g. after an
+/// interface was re-created) are treated as no activity.
+fn rates(previous: (u64, u64), current: (u64, u64), seconds: f64) -> (f64, f64) {
+    if seconds <= 0.0 {
+        return (0.0, 0.0);
+    }
+
+    (
+        current.0.saturating_sub(previous.0) as f64 / seconds,
+        current.1.saturating_sub(previous.1) as f64 / seconds,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bollard::container::{BlkioStatsEntry, CPUUsage, ThrottlingData};
+
+    fn cpu_stats(total_usage: u64, system_cpu_usage: Option<u64>) -> CPUStats {
+        CPUStats {
+            cpu_usage: CPUUsage {
+                percpu_usage: None,
+                usage_in_usermode: 0,
+                total_usage,
+                usage_in_kernelmode: 0,
+            },
+            system_cpu_usage,
+            online_cpus: Some(4),
+            throttling_data: ThrottlingData {
+                periods: 0,
+                throttled_periods: 0,
+                throttled_time: 0,
+            },
+        }
+    }
+
+    fn blkio_entry(op: &str, value: u64) -> BlkioStatsEntry {
+        BlkioStatsEntry {
+            major: 8,
+            minor: 0,
+            op: op.to_string(),
+            value,
+        }
+    }
+
+    #[test]
+    fn test_cpu_percent() {
+        let precpu = cpu_stats(1_000, Some(10_000));
+        let cpu = cpu_stats(1_500, Some(12_000));
+        // 500 of 2000 host ticks on a 4 CPU host
+        assert_eq!(cpu_percent(&cpu, &precpu), 100.0);
+    }
+
+    #[test]
+    fn test_cpu_percent_without_previous_sample() {
+        let precpu = cpu_stats(0, None);
+        let cpu = cpu_stats(1_500, Some(12_000));
+        assert_eq!(cpu_percent(&cpu, &precpu), 0.0);
+    }
+
+    #[test]
+    fn test_block_io_bytes_for_cgroup_v1_and_v2() {
+        let blkio = BlkioStats {
+            io_service_bytes_recursive: Some(vec![
+                blkio_entry("Read", 100),
+                blkio_entry("Write", 20),
+                blkio_entry("Total", 120),
+                blkio_entry("read", 1),
+                blkio_entry("write", 2),
+            ]),
+            io_serviced_recursive: None,
+            io_queue_recursive: None,
+            io_service_time_recursive: None,
+            io_wait_time_recursive: None,
+            io_merged_recursive: None,
+            io_time_recursive: None,
+            sectors_recursive: None,
+        };
+        assert_eq!(block_io_bytes(&blkio), (101, 22));
+    }
+
+    #[test]
+    fn test_rates() {
+        assert_eq!(rates((100, 100), (300, 150), 2.0), (100.0, 25.0));
+        assert_eq!(rates((300, 100), (100, 100), 1.0), (0.0, 0.0));
+        assert_eq!(rates((0, 0), (100, 100), 0.0), (0.0, 0.0));
+    }
+}
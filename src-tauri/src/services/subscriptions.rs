@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::future::Future;
+use tokio::sync::Mutex;
+use tokio::task::AbortHandle;
+use tracing::debug;
+
+#[derive(Debug)]
+struct Subscription<T> {
+    handle: AbortHandle,
+    data: T,
+}
+
+/// Keeps track of long-running streaming tasks (logs, stats, exec sessions, ...)
+/// so that the frontend can cancel them by the id it received when subscribing.
+/// Each task can carry some extra data, e.g. the stdin sender of an exec session.
+#[derive(Debug)]
+pub struct SubscriptionRegistry<T = ()> {
+    tasks: Mutex<HashMap<String, Subscription<T>>>,
+}
+
+impl<T> Default for SubscriptionRegistry<T> {
+    fn default() -> Self {
+        Self {
+            tasks: Mutex::new(HashMap::new()),
+        }
+    }
+}
+
+impl SubscriptionRegistry<()> {
+    /// Spawn `future` as a background task registered under `id`, unless a task
+    /// is already registered under it. The task removes itself from the registry
+    /// once it completes.
+    pub async fn spawn<F>(&'static self, id: String, future: F) -> Result<(), String>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        self.spawn_with(id, (), future).await
+    }
+}
+
+impl<T: Clone + Send + 'static> SubscriptionRegistry<T> {
+    /// Same as [`SubscriptionRegistry::spawn`], attaching `data` to the registered task.
+    pub async fn spawn_with<F>(&'static self, id: String, data: T, future: F) -> Result<(), String>
+    where
+        F: Future<Output = ()> + Send + 'static,
+    {
+        // Hold the lock while spawning so a task that finishes immediately
+        // cannot try to unregister itself before it has been registered.
+        let mut tasks = self.tasks.lock().await;
+        if tasks.contains_key(&id) {
+            return Err(format!("{} is already running", id));
+        }
+
+        let task_id = id.clone();
+        let handle = tokio::spawn(async move {
+            future.await;
+            self.tasks.lock().await.remove(&task_id);
+            debug!("Subscription {} finished", task_id);
+        });
+
+        tasks.insert(
+            id,
+            Subscription {
+                handle: handle.abort_handle(),
+                data,
+            },
+        );
+        Ok(())
+    }
+
+    /// Data attached to the task registered under `id`, if it is still running.
+    pub async fn get(&self, id: &str) -> Option<T> {
+        self.tasks
+            .lock()
+            .await
+            .get(id)
+            .map(|subscription| subscription.data.clone())
+    }
+
+    /// Abort the task registered under `id`. Returns `false` if there is no such task.
+    pub async fn cancel(&self, id: &str) -> bool {
+        match self.tasks.lock().await.remove(id) {
+            Some(subscription) => {
+                subscription.handle.abort();
+                debug!("Subscription {} cancelled", id);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_spawn_rejects_running_id() {
+        let registry: &'static SubscriptionRegistry = Box::leak(Box::default());
+
+        registry
+            .spawn("pull".to_string(), std::future::pending())
+            .await
+            .unwrap();
+        assert!(registry.spawn("pull".to_string(), async {}).await.is_err());
+
+        assert!(registry.cancel("pull").await);
+        registry.spawn("pull".to_string(), async {}).await.unwrap();
+    }
+}
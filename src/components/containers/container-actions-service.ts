@@ -6,6 +6,12 @@ export interface ContainerActionOptions {
   onSelectionChange?: (selected: string[]) => void;
 }
 
+interface BulkOperationReport {
+  total: number;
+  failed: number;
+  results: { id: string; success: boolean; error?: string }[];
+}
+
 export class ContainerActionService {
   private static async executeAction(
     action: string,
@@ -13,7 +19,17 @@ export class ContainerActionService {
     options: ContainerActionOptions = {}
   ) {
     try {
-      await invoke(action, params);
+      const report = await invoke<BulkOperationReport | null>(action, params);
+
+      // Bulk actions keep going when some containers fail and report them instead
+      if (report && report.failed > 0) {
+        const errors = report.results
+          .filter(result => !result.success)
+          .map(result => `${result.id.slice(0, 12)}: ${result.error}`);
+        throw new Error(
+          `${report.failed} of ${report.total} failed (${errors.join('; ')})`
+        );
+      }
 
       const actionName = action
         .replace('_container', '')
@@ -3,6 +3,8 @@ export interface Image {
   id: string;
   repository?: string | null;
   tag?: string | null;
+  repo_tags: string[];
+  repo_digests: string[];
   image_id: string;
   created: number;
   size: number;
@@ -92,6 +92,7 @@ export class ConfigService {
           auto_update_settings: false,
         },
         sidebar_collapsed: false,
+        containers: {},
       };
       this.notifySubscribers();
     } finally {
@@ -22,10 +22,15 @@ export interface StartupSettings {
   auto_update_settings: boolean;
 }
 
+export interface ContainerSettings {
+  default_stop_timeout?: number; // seconds
+}
+
 export interface AppConfig {
   theme: Theme;
   language: Language;
   telemetry: TelemetrySettings;
   startup: StartupSettings;
   sidebar_collapsed: boolean;
+  containers: ContainerSettings;
 }